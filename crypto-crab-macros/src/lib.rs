@@ -1,10 +1,14 @@
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse::Parse, parse_macro_input, DeriveInput, Lit, LitStr, MetaNameValue, Token};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse::Parse, parse_macro_input, punctuated::Punctuated, DeriveInput, Ident, Lit, LitStr,
+    MetaNameValue, Token,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Resolution {
     Tick,
     Second,
@@ -24,74 +28,279 @@ impl Resolution {
             _ => None,
         }
     }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Tick => "tick",
+            Self::Second => "second",
+            Self::Minute => "minute",
+            Self::Hour => "hour",
+            Self::Day => "day",
+        }
+    }
+
+    fn variant(self) -> Ident {
+        let name = format!("{:?}", self);
+        Ident::new(&name, Span::call_site())
+    }
 }
 
-struct SymbolResolution {
-    pub symbol: LitStr,
+struct PairResolution {
+    pub base: String,
+    pub quote: String,
     pub resolution: Resolution,
 }
 
-fn parse_name_value(input: &syn::parse::ParseStream, ident: &str) -> syn::Result<MetaNameValue> {
-    let nv = input.parse::<MetaNameValue>()?;
-    if nv.path.is_ident(ident) {
-        Ok(nv)
-    } else {
-        Err(syn::parse::Error::new(
-            input.span(),
-            format!("Invalid name-value pair. Expected ident: '{}'", ident),
-        ))
+impl PairResolution {
+    fn pair_name(&self) -> String {
+        format!("{}/{}", self.base, self.quote)
+    }
+
+    // e.g. `btcusd` for BTC/USD
+    fn ident_name(&self) -> String {
+        format!("{}{}", self.base, self.quote).to_ascii_lowercase()
+    }
+
+    fn field(&self) -> Ident {
+        format_ident!("{}_{}", self.ident_name(), self.resolution.name())
+    }
+}
+
+// The upper case base and quote assets of a pair like "BTC/USD".
+fn split_pair(pair: &str) -> Option<(String, String)> {
+    let (base, quote) = pair.split_once('/')?;
+    let valid = |asset: &str| !asset.is_empty() && asset.chars().all(|c| c.is_ascii_alphanumeric());
+    (valid(base) && valid(quote)).then(|| (base.to_ascii_uppercase(), quote.to_ascii_uppercase()))
+}
+
+fn string_literal(nv: &MetaNameValue) -> syn::Result<LitStr> {
+    match &nv.lit {
+        Lit::Str(s) => Ok(s.clone()),
+        lit => Err(syn::Error::new_spanned(
+            lit,
+            "pair/resolution attribute expected string-literals.\nFor example: (pair = \"BTC/USD\", resolution = \"tick\")",
+        )),
     }
 }
 
-impl Parse for SymbolResolution {
+impl Parse for PairResolution {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let symbol = parse_name_value(&input, "symbol")?;
-        input.parse::<Token![,]>()?;
-        let resolution = parse_name_value(&input, "resolution")?;
-
-        match (symbol.lit, resolution.lit) {
-            (Lit::Str(symbol), Lit::Str(resolution)) => {
-                if let Some(resolution) = Resolution::try_from_str(resolution.value()) {
-                    Ok(Self {
-                        symbol, resolution
-                    })
-                } else {
-                    Err(syn::parse::Error::new(
-                        input.span(), "invalid resolution type"
-                    ))
-                }
+        let mut pair = None;
+        let mut resolution = None;
+
+        for nv in Punctuated::<MetaNameValue, Token![,]>::parse_terminated(input)? {
+            let slot = if nv.path.is_ident("pair") {
+                &mut pair
+            } else if nv.path.is_ident("resolution") {
+                &mut resolution
+            } else {
+                return Err(syn::Error::new_spanned(
+                    &nv.path,
+                    "Invalid name-value pair. Expected ident: 'pair' or 'resolution'",
+                ));
+            };
+
+            if slot.is_some() {
+                return Err(syn::Error::new_spanned(
+                    &nv.path,
+                    "duplicate name-value pair",
+                ));
             }
-            _ => Err(syn::parse::Error::new(
-                input.span(),
-                "symbol/resolution attribute expected string-literals.\nFor example: (symbol = \"BTCUSD\", resolution = \"tick\")",
-            )),
+            *slot = Some(string_literal(&nv)?);
         }
+
+        let (pair, resolution) = match (pair, resolution) {
+            (Some(pair), Some(resolution)) => (pair, resolution),
+            (None, _) => return Err(input.error("missing name-value pair: 'pair'")),
+            (_, None) => return Err(input.error("missing name-value pair: 'resolution'")),
+        };
+
+        let (base, quote) = split_pair(&pair.value()).ok_or_else(|| {
+            syn::Error::new(
+                pair.span(),
+                format!(
+                    "invalid pair '{}'. Expected the base and quote assets separated by a slash, e.g. \"BTC/USD\"",
+                    pair.value()
+                ),
+            )
+        })?;
+
+        let resolution = Resolution::try_from_str(resolution.value()).ok_or_else(|| {
+            syn::Error::new(
+                resolution.span(),
+                "invalid resolution type. Expected one of: tick, second, minute, hour, day",
+            )
+        })?;
+
+        Ok(Self {
+            base,
+            quote,
+            resolution,
+        })
     }
 }
 
-#[proc_macro_derive(Algorithm, attributes(algo))]
-pub fn derive_algorithm(input: TokenStream) -> TokenStream {
-    let ast = parse_macro_input!(input as DeriveInput);
+fn parse_attributes(ast: &DeriveInput) -> syn::Result<Vec<PairResolution>> {
+    let mut subscriptions = Vec::<PairResolution>::new();
 
-    let data_funcs_name = ast
-        .attrs
-        .iter()
-        .filter(|attr| attr.path.is_ident("algo"))
-        .map(|attr| match attr.parse_args::<SymbolResolution>() {
-            Ok(sr) => sr,
-            Err(e) => panic!("{:?}", e),
-        })
-        .map(|sr| quote::format_ident!("get_{}", sr.symbol.value().to_ascii_lowercase()));
+    for attr in ast.attrs.iter().filter(|attr| attr.path.is_ident("algo")) {
+        let sr = attr.parse_args::<PairResolution>()?;
+        if subscriptions
+            .iter()
+            .any(|other| other.field() == sr.field())
+        {
+            return Err(syn::Error::new_spanned(
+                attr,
+                format!(
+                    "pair '{}' is already declared with resolution '{}'",
+                    sr.pair_name(),
+                    sr.resolution.name()
+                ),
+            ));
+        }
+        subscriptions.push(sr);
+    }
+
+    if subscriptions.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &ast.ident,
+            "expected at least one attribute. For example: #[algo(pair = \"BTC/USD\", resolution = \"tick\")]",
+        ));
+    }
+
+    Ok(subscriptions)
+}
+
+fn data_type(resolution: Resolution) -> TokenStream2 {
+    match resolution {
+        Resolution::Tick => quote!(crypto_crab::tick::Tick),
+        _ => quote!(crypto_crab::candle::Candle),
+    }
+}
+
+fn expand(ast: &DeriveInput) -> syn::Result<TokenStream2> {
+    let subscriptions = parse_attributes(ast)?;
 
     let name = &ast.ident;
-    let data_name = quote::format_ident!("{}{}", name, "AlgorithmData");
+    let vis = &ast.vis;
+    let data_name = format_ident!("{}{}", name, "AlgorithmData");
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
 
-    TokenStream::from(quote! {
-        pub struct #data_name;
+    let fields = subscriptions.iter().map(|sr| {
+        let field = sr.field();
+        let ty = data_type(sr.resolution);
+        quote!(#field: Option<#ty>)
+    });
+
+    let getters = subscriptions.iter().map(|sr| {
+        let field = sr.field();
+        let ty = data_type(sr.resolution);
+        let getter = format_ident!("get_{}", field);
+        let doc = format!(
+            "The latest {} of {}, or `None` if there was none yet.",
+            match sr.resolution {
+                Resolution::Tick => "trade".to_string(),
+                resolution => format!("{} candle", resolution.name()),
+            },
+            sr.pair_name()
+        );
+        // pairs declared with a single resolution also get a shorthand getter
+        let unique = subscriptions
+            .iter()
+            .filter(|other| other.pair_name() == sr.pair_name())
+            .count()
+            == 1;
+        let shorthand = unique.then(|| {
+            let getter = format_ident!("get_{}", sr.ident_name());
+            quote! {
+                #[doc = #doc]
+                pub fn #getter(&self) -> Option<&#ty> {
+                    self.#field.as_ref()
+                }
+            }
+        });
+
+        quote! {
+            #[doc = #doc]
+            pub fn #getter(&self) -> Option<&#ty> {
+                self.#field.as_ref()
+            }
+
+            #shorthand
+        }
+    });
+
+    let subscription_list = subscriptions.iter().map(|sr| {
+        let (base, quote, resolution) = (&sr.base, &sr.quote, sr.resolution.variant());
+        quote!((crypto_crab::algo::Pair::new(#base, #quote), crypto_crab::period::Resolution::#resolution))
+    });
+
+    let candle_arms = subscriptions
+        .iter()
+        .filter(|sr| sr.resolution != Resolution::Tick)
+        .map(|sr| {
+            let (field, base, quote, resolution) =
+                (sr.field(), &sr.base, &sr.quote, sr.resolution.variant());
+            quote! {
+                (#base, #quote, crypto_crab::period::Resolution::#resolution) => {
+                    self.#field = Some(candle.clone());
+                    true
+                }
+            }
+        });
+
+    let tick_arms = subscriptions
+        .iter()
+        .filter(|sr| sr.resolution == Resolution::Tick)
+        .map(|sr| {
+            let (field, base, quote) = (sr.field(), &sr.base, &sr.quote);
+            quote! {
+                (#base, #quote) => {
+                    self.#field = Some(tick.clone());
+                    true
+                }
+            }
+        });
+
+    Ok(quote! {
+        #[derive(Debug, Default, Clone)]
+        #vis struct #data_name {
+            #(#fields,)*
+        }
 
         impl #data_name {
-            #(fn #data_funcs_name (&self) {})*
+            #(#getters)*
+        }
+
+        impl crypto_crab::algo::MarketData for #data_name {
+            fn subscriptions() -> Vec<(crypto_crab::algo::Pair, crypto_crab::period::Resolution)> {
+                vec![#(#subscription_list,)*]
+            }
+
+            #[allow(unused_variables)]
+            fn update(
+                &mut self,
+                pair: &crypto_crab::algo::Pair,
+                resolution: crypto_crab::period::Resolution,
+                candle: &crypto_crab::candle::Candle,
+            ) -> bool {
+                match (pair.base.as_str(), pair.quote.as_str(), resolution) {
+                    #(#candle_arms)*
+                    _ => false,
+                }
+            }
+
+            #[allow(unused_variables)]
+            fn update_tick(
+                &mut self,
+                pair: &crypto_crab::algo::Pair,
+                tick: &crypto_crab::tick::Tick,
+            ) -> bool {
+                match (pair.base.as_str(), pair.quote.as_str()) {
+                    #(#tick_arms)*
+                    _ => false,
+                }
+            }
         }
 
         impl #impl_generics crypto_crab::algo::AlgorithmData for #name #ty_generics #where_clause {
@@ -99,3 +308,12 @@ pub fn derive_algorithm(input: TokenStream) -> TokenStream {
         }
     })
 }
+
+#[proc_macro_derive(Algorithm, attributes(algo))]
+pub fn derive_algorithm(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    expand(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,891 @@
+use crate::{
+    algo::{Event, Pair},
+    candle::Candle,
+    decimal::Decimal,
+    period::{Period, Resolution},
+    tick::Tick,
+    time::{DateTime, Duration},
+};
+use chrono::{TimeZone, Utc};
+use gemini::common::{Trade, TradeKind};
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::{AddAssign, SubAssign},
+};
+
+/// Aggregates ticks into candles of a fixed interval, aligned to multiples of the
+/// interval since the UNIX epoch or another origin.
+///
+/// A candle is complete once a tick of a later interval arrives or the aggregator is
+/// flushed past its end. Intervals without ticks produce flat candles at the previous
+/// close with zero volume, unless disabled with `skip_empty`. Ticks of intervals
+/// which are already complete are late and dropped.
+///
+/// With `with_revisions`, ticks pushed with an id, e.g. trades, can be retracted from
+/// recent candles by that id, e.g. when they are broken.
+#[derive(Debug, Clone)]
+pub struct BarAggregator {
+    interval: Duration,
+    origin: DateTime,
+    bar: Option<Candle>,
+    // The end of the most recently completed candle.
+    completed: Option<DateTime>,
+    prev_close: Option<Decimal>,
+    fill_empty: bool,
+    late: usize,
+    // The number of completed candles whose ticks are kept, `None` if no ticks are kept.
+    revisions: Option<usize>,
+    // The ticks of the current candle, with their ids if pushed with one.
+    ticks: Ticks,
+    recent: VecDeque<(Candle, Ticks)>,
+}
+
+// Ticks with the ids they were pushed with, if any.
+type Ticks = Vec<(Option<u64>, Tick)>;
+
+/// What retracting a tick from a `BarAggregator` changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Retraction {
+    /// The tick was removed from the current candle.
+    Current,
+    /// The tick was removed from a completed candle.
+    Revised { previous: Candle, revised: Candle },
+    /// The tick wasn't found, e.g. because its candle completed too long ago.
+    NotFound,
+}
+
+fn open(time: DateTime, tick: &Tick) -> Candle {
+    Candle {
+        time,
+        open: tick.price,
+        high: tick.price,
+        low: tick.price,
+        close: tick.price,
+        volume: tick.quantity,
+    }
+}
+
+fn extend(bar: &mut Candle, tick: &Tick) {
+    bar.high = bar.high.max(tick.price);
+    bar.low = bar.low.min(tick.price);
+    bar.close = tick.price;
+    bar.volume += tick.quantity;
+}
+
+fn flat(time: DateTime, close: Decimal) -> Candle {
+    Candle {
+        time,
+        open: close,
+        high: close,
+        low: close,
+        close,
+        volume: Decimal::ZERO,
+    }
+}
+
+// The candle of `ticks`, or `None` if there are none.
+fn aggregate(time: DateTime, ticks: &[(Option<u64>, Tick)]) -> Option<Candle> {
+    let ((_, first), rest) = ticks.split_first()?;
+    let mut bar = open(time, first);
+    rest.iter().for_each(|(_, tick)| extend(&mut bar, tick));
+    Some(bar)
+}
+
+impl BarAggregator {
+    /// # Panics
+    ///
+    /// If `interval` isn't positive.
+    pub fn new(interval: Duration) -> Self {
+        assert!(
+            interval > Duration::zero(),
+            "BarAggregator interval must be positive"
+        );
+
+        Self {
+            interval,
+            origin: Utc.timestamp_opt(0, 0).unwrap(),
+            bar: None,
+            completed: None,
+            prev_close: None,
+            fill_empty: true,
+            late: 0,
+            revisions: None,
+            ticks: Vec::new(),
+            recent: VecDeque::new(),
+        }
+    }
+
+    pub fn from_period<P: Period>() -> Self {
+        Self::new(P::to_duration())
+    }
+
+    /// The candles of `resolution`, aligned like Gemini's, or `None` for ticks.
+    pub fn from_resolution(resolution: Resolution) -> Option<Self> {
+        Some(Self::new(resolution.to_duration()?).with_origin(resolution.origin()))
+    }
+
+    /// Aligns the candles to multiples of the interval since `origin`.
+    pub fn with_origin(mut self, origin: DateTime) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Doesn't produce candles for intervals without ticks.
+    pub fn skip_empty(mut self) -> Self {
+        self.fill_empty = false;
+        self
+    }
+
+    /// Keeps the ticks of the current candle and the last `revisions` completed ones, so
+    /// that the ones pushed with an id can be retracted. With `0` ticks are only retracted
+    /// from the current candle, without revisions they aren't retracted at all.
+    pub fn with_revisions(mut self, revisions: usize) -> Self {
+        self.revisions = Some(revisions);
+        self
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// The incomplete candle of the current interval.
+    pub fn current(&self) -> Option<&Candle> {
+        self.bar.as_ref()
+    }
+
+    /// The number of ticks which were dropped because they arrived late.
+    pub fn late_ticks(&self) -> usize {
+        self.late
+    }
+
+    fn start(&self, time: DateTime) -> DateTime {
+        let (time, interval) = (time.timestamp_millis(), self.interval.num_milliseconds());
+        let offset = (time - self.origin.timestamp_millis()).rem_euclid(interval);
+        Utc.timestamp_millis_opt(time - offset).unwrap()
+    }
+
+    /// The start of the oldest candle ticks can still be retracted from, or added to.
+    pub fn horizon(&self) -> Option<DateTime> {
+        self.recent
+            .front()
+            .map(|(bar, _)| bar.time)
+            .or_else(|| self.bar.as_ref().map(|bar| bar.time))
+            .or(self.completed)
+    }
+
+    /// Adds a tick. Returns the candles it completed, oldest first.
+    pub fn push(&mut self, tick: &Tick) -> Vec<Candle> {
+        self.insert(None, tick)
+    }
+
+    /// Adds a tick like `push` which can be retracted by `id` later, see
+    /// `with_revisions`.
+    pub fn push_with_id(&mut self, id: u64, tick: &Tick) -> Vec<Candle> {
+        self.insert(Some(id), tick)
+    }
+
+    fn insert(&mut self, id: Option<u64>, tick: &Tick) -> Vec<Candle> {
+        let start = self.start(tick.time);
+        let boundary = self.bar.as_ref().map(|bar| bar.time).or(self.completed);
+        if matches!(boundary, Some(boundary) if start < boundary) {
+            self.late += 1;
+            return Vec::new();
+        }
+
+        let completed = self.flush(start);
+        match &mut self.bar {
+            Some(bar) => extend(bar, tick),
+            None => self.bar = Some(open(start, tick)),
+        }
+        if self.revisions.is_some() {
+            self.ticks.push((id, tick.clone()));
+        }
+        completed
+    }
+
+    /// Removes the tick pushed with `id` at `time`, e.g. a broken trade, from its
+    /// candle. Only the current candle and the completed candles kept with
+    /// `with_revisions` are revised, later candles aren't, e.g. flat candles at the
+    /// close of a revised one.
+    pub fn retract(&mut self, id: u64, time: DateTime) -> Retraction {
+        if let Some(position) = self.ticks.iter().position(|(i, _)| *i == Some(id)) {
+            self.ticks.remove(position);
+            self.bar = self
+                .bar
+                .as_ref()
+                .and_then(|bar| aggregate(bar.time, &self.ticks));
+            return Retraction::Current;
+        }
+
+        let start = self.start(time);
+        let index = match self.recent.iter().position(|(bar, _)| bar.time == start) {
+            Some(index) => index,
+            None => return Retraction::NotFound,
+        };
+        let prev_close = index.checked_sub(1).map(|i| self.recent[i].0.close);
+        let (bar, ticks) = &mut self.recent[index];
+        match ticks.iter().position(|(i, _)| *i == Some(id)) {
+            Some(position) => ticks.remove(position),
+            None => return Retraction::NotFound,
+        };
+
+        // a candle without ticks left is flat at the previous close
+        let revised = aggregate(bar.time, ticks)
+            .unwrap_or_else(|| flat(bar.time, prev_close.unwrap_or(bar.open)));
+        let previous = std::mem::replace(bar, revised.clone());
+        if index + 1 == self.recent.len() {
+            self.prev_close = Some(revised.close);
+        }
+        Retraction::Revised { previous, revised }
+    }
+
+    fn keep(&mut self, bar: &Candle, ticks: Ticks) {
+        if let Some(revisions) = self.revisions {
+            self.recent.push_back((bar.clone(), ticks));
+            while self.recent.len() > revisions {
+                self.recent.pop_front();
+            }
+        }
+    }
+
+    /// Completes the candles which end at or before `now`, e.g. when no ticks
+    /// arrived for a while. Returns the completed candles, oldest first.
+    pub fn flush(&mut self, now: DateTime) -> Vec<Candle> {
+        let mut completed = Vec::new();
+
+        if let Some(bar) = &self.bar {
+            if bar.time + self.interval > now {
+                return completed;
+            }
+
+            let bar = self.bar.take().unwrap();
+            self.completed = Some(bar.time + self.interval);
+            self.prev_close = Some(bar.close);
+            let ticks = std::mem::take(&mut self.ticks);
+            self.keep(&bar, ticks);
+            completed.push(bar);
+        }
+
+        if let (true, Some(close), Some(mut next)) =
+            (self.fill_empty, self.prev_close, self.completed)
+        {
+            while next + self.interval <= now {
+                let bar = flat(next, close);
+                self.keep(&bar, Vec::new());
+                completed.push(bar);
+                next += self.interval;
+            }
+            self.completed = Some(next);
+        }
+
+        completed
+    }
+}
+
+/// Which kinds of trades are aggregated into candles, broken trades never are. By
+/// default continuous and auction trades are, while block trades, which are negotiated
+/// off the order book, aren't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeFilter {
+    pub continuous: bool,
+    pub auction: bool,
+    pub block: bool,
+}
+
+impl Default for TradeFilter {
+    fn default() -> Self {
+        Self {
+            continuous: true,
+            auction: true,
+            block: false,
+        }
+    }
+}
+
+impl TradeFilter {
+    pub fn all() -> Self {
+        Self {
+            continuous: true,
+            auction: true,
+            block: true,
+        }
+    }
+
+    pub fn accepts(&self, trade: &Trade) -> bool {
+        !trade.broken && self.accepts_kind(trade.kind)
+    }
+
+    pub fn accepts_kind(&self, kind: TradeKind) -> bool {
+        match kind {
+            TradeKind::Continuous => self.continuous,
+            TradeKind::Auction => self.auction,
+            TradeKind::Block => self.block,
+        }
+    }
+}
+
+/// The quantity and notional value of some trades.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Volume {
+    pub quantity: Decimal,
+    pub notional: Decimal,
+}
+
+impl Volume {
+    /// The volume weighted average price, `None` without volume.
+    pub fn vwap(&self) -> Option<Decimal> {
+        (!self.quantity.is_zero()).then(|| self.notional / self.quantity)
+    }
+}
+
+impl From<&Trade> for Volume {
+    fn from(trade: &Trade) -> Self {
+        Self {
+            quantity: trade.quantity,
+            notional: trade.quantity * trade.price,
+        }
+    }
+}
+
+impl AddAssign for Volume {
+    fn add_assign(&mut self, other: Self) {
+        self.quantity += other.quantity;
+        self.notional += other.notional;
+    }
+}
+
+impl SubAssign for Volume {
+    fn sub_assign(&mut self, other: Self) {
+        self.quantity -= other.quantity;
+        self.notional -= other.notional;
+    }
+}
+
+/// The volume of the trades of a pair by kind, whether they are aggregated into
+/// candles or not.
+///
+/// Trades are counted once by id, so overlapping REST trade histories can be pushed,
+/// and a break of a counted trade moves its volume to `broken`. The ids are only kept
+/// back to the horizon set with `prune`, earlier trades are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct TradeVolumes {
+    pub continuous: Volume,
+    pub auction: Volume,
+    pub block: Volume,
+    pub broken: Volume,
+    // The time of each trade pushed since the horizon and whether it was broken, by id.
+    trades: HashMap<u64, (DateTime, bool)>,
+    horizon: Option<DateTime>,
+}
+
+impl TradeVolumes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn kind_mut(&mut self, kind: TradeKind) -> &mut Volume {
+        match kind {
+            TradeKind::Continuous => &mut self.continuous,
+            TradeKind::Auction => &mut self.auction,
+            TradeKind::Block => &mut self.block,
+        }
+    }
+
+    /// Counts a trade. Returns `false` if it was known already, i.e. pushed before
+    /// or, for unbroken trades, pushed broken before, or is before the horizon.
+    pub fn push(&mut self, trade: &Trade) -> bool {
+        if matches!(self.horizon, Some(horizon) if trade.time < horizon) {
+            return false;
+        }
+
+        let volume = Volume::from(trade);
+        let known = self.trades.get(&trade.tid).map(|(_, broken)| *broken);
+        match (known, trade.broken) {
+            (None, false) => *self.kind_mut(trade.kind) += volume,
+            (None, true) => self.broken += volume,
+            (Some(false), true) => {
+                *self.kind_mut(trade.kind) -= volume;
+                self.broken += volume;
+            }
+            (Some(_), _) => return false,
+        }
+        self.trades.insert(trade.tid, (trade.time, trade.broken));
+        true
+    }
+
+    /// Whether the unbroken trade `tid` was counted.
+    pub fn counted(&self, tid: u64) -> bool {
+        matches!(self.trades.get(&tid), Some((_, false)))
+    }
+
+    /// Forgets the trades before `horizon`, trades before it are ignored from now on.
+    /// Earlier horizons than the current one are ignored.
+    pub fn prune(&mut self, horizon: DateTime) {
+        if matches!(self.horizon, Some(current) if current >= horizon) {
+            return;
+        }
+        self.trades.retain(|_, (time, _)| *time >= horizon);
+        self.horizon = Some(horizon);
+    }
+
+    /// The volume of the unbroken trades of every kind.
+    pub fn total(&self) -> Volume {
+        let mut total = self.continuous;
+        total += self.auction;
+        total += self.block;
+        total
+    }
+}
+
+/// A completed candle revised after a trade was retracted from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revision {
+    pub pair: Pair,
+    pub resolution: Resolution,
+    pub previous: Candle,
+    pub revised: Candle,
+}
+
+/// Turns the ticks of several pairs into the events for a set of subscriptions,
+/// e.g. `MarketData::subscriptions`, aggregating the ticks into candles of every
+/// subscribed resolution.
+///
+/// Gemini's trades can be pushed with their kind and breaks with `push_classified`,
+/// which only aggregates the trades its `TradeFilter` accepts and retracts broken
+/// trades from their candles.
+#[derive(Debug, Clone)]
+pub struct Aggregator {
+    ticks: Vec<Pair>,
+    bars: Vec<(Pair, Resolution, BarAggregator)>,
+    filter: TradeFilter,
+    volumes: HashMap<Pair, TradeVolumes>,
+    revisions: Vec<Revision>,
+}
+
+impl Aggregator {
+    pub fn new(subscriptions: &[(Pair, Resolution)]) -> Self {
+        let mut ticks = Vec::new();
+        let mut bars = Vec::new();
+
+        for (pair, resolution) in subscriptions {
+            match BarAggregator::from_resolution(*resolution) {
+                Some(bar) => bars.push((pair.clone(), *resolution, bar)),
+                None => ticks.push(pair.clone()),
+            }
+        }
+
+        Self {
+            ticks,
+            bars,
+            filter: TradeFilter::default(),
+            volumes: HashMap::new(),
+            revisions: Vec::new(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: TradeFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Keeps the trades of the current and the last `revisions` completed candles of
+    /// every resolution, so that broken trades can be retracted from them. Without it
+    /// broken trades aren't retracted.
+    pub fn with_revisions(mut self, revisions: usize) -> Self {
+        for (_, _, bars) in &mut self.bars {
+            bars.revisions = Some(revisions);
+        }
+        self
+    }
+
+    /// The volumes of the trades of `pair` pushed with `push_classified`.
+    pub fn volumes(&self, pair: &Pair) -> Option<&TradeVolumes> {
+        self.volumes.get(pair)
+    }
+
+    /// Removes and returns the completed candles revised since the last call, oldest
+    /// first.
+    pub fn take_revisions(&mut self) -> Vec<Revision> {
+        std::mem::take(&mut self.revisions)
+    }
+
+    /// Adds a tick of `pair`. Returns the completed candles followed by the tick
+    /// itself if it is subscribed to.
+    pub fn push(&mut self, pair: &Pair, tick: &Tick) -> Vec<Event> {
+        self.insert(pair, None, tick)
+    }
+
+    fn insert(&mut self, pair: &Pair, id: Option<u64>, tick: &Tick) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        for (p, resolution, bars) in &mut self.bars {
+            if p == pair {
+                let candles = bars.insert(id, tick);
+                events.extend(
+                    candles
+                        .into_iter()
+                        .map(|candle| Event::Candle(pair.clone(), *resolution, candle)),
+                );
+            }
+        }
+
+        if self.ticks.contains(pair) {
+            events.push(Event::Tick(pair.clone(), tick.clone()));
+        }
+        events
+    }
+
+    /// Adds a trade of Gemini's market data feed like `push`. Trades of symbols without
+    /// a pair are ignored.
+    pub fn push_trade(&mut self, trade: &gemini::ws::marketv2::trade::Trade) -> Vec<Event> {
+        match exchange_api::gemini::pair(trade.symbol) {
+            Some(pair) => self.push(&pair, &trade.into()),
+            None => Vec::new(),
+        }
+    }
+
+    // The start of the oldest candle of `pair`, or of any pair if none of it is
+    // subscribed to, which trades can still be retracted from or added to.
+    fn horizon(&self, pair: &Pair) -> Option<DateTime> {
+        let horizon = |any: bool| {
+            self.bars
+                .iter()
+                .filter(|(p, _, _)| any || p == pair)
+                .filter_map(|(_, _, bars)| bars.horizon())
+                .min()
+        };
+        horizon(false).or_else(|| horizon(true))
+    }
+
+    /// Adds a trade of `pair` like `push` if the filter accepts it. Every trade is
+    /// counted in the volumes of the pair, and repeated trades are ignored. The
+    /// volumes only remember the trades back to the oldest candle kept, see
+    /// `TradeVolumes::prune`.
+    ///
+    /// A break of a trade which was aggregated is retracted from its candles by id,
+    /// revising them if they were completed already, see `take_revisions`. Tick events
+    /// can't be retracted.
+    pub fn push_classified(&mut self, pair: &Pair, trade: &Trade) -> Vec<Event> {
+        let horizon = self.horizon(pair);
+        let volumes = self.volumes.entry(pair.clone()).or_default();
+        if let Some(horizon) = horizon {
+            volumes.prune(horizon);
+        }
+        let aggregated = volumes.counted(trade.tid) && self.filter.accepts_kind(trade.kind);
+        if !volumes.push(trade) {
+            return Vec::new();
+        }
+
+        if trade.broken {
+            if !aggregated {
+                return Vec::new();
+            }
+            for (p, resolution, bars) in &mut self.bars {
+                if p != pair {
+                    continue;
+                }
+                if let Retraction::Revised { previous, revised } =
+                    bars.retract(trade.tid, trade.time)
+                {
+                    self.revisions.push(Revision {
+                        pair: pair.clone(),
+                        resolution: *resolution,
+                        previous,
+                        revised,
+                    });
+                }
+            }
+            Vec::new()
+        } else if self.filter.accepts(trade) {
+            self.insert(pair, Some(trade.tid), &Tick::from(trade))
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Completes the candles of every pair and resolution which end at or before `now`.
+    pub fn flush(&mut self, now: DateTime) -> Vec<Event> {
+        self.bars
+            .iter_mut()
+            .flat_map(|(pair, resolution, bars)| {
+                let resolution = *resolution;
+                bars.flush(now)
+                    .into_iter()
+                    .map(move |candle| Event::Candle(pair.clone(), resolution, candle))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::period::Minute;
+    use rust_decimal_macros::dec;
+
+    fn time(seconds: i64) -> DateTime {
+        Utc.timestamp_opt(seconds, 0).unwrap()
+    }
+
+    fn tick(seconds: i64, price: Decimal, quantity: Decimal) -> Tick {
+        Tick {
+            time: time(seconds),
+            price,
+            quantity,
+        }
+    }
+
+    #[test]
+    fn test_bar_aggregator() {
+        let mut bars = BarAggregator::from_period::<Minute>();
+
+        assert!(bars.push(&tick(65, dec!(10), dec!(1))).is_empty());
+        assert!(bars.push(&tick(70, dec!(12), dec!(2))).is_empty());
+        assert!(bars.push(&tick(119, dec!(9), dec!(1))).is_empty());
+
+        let current = bars.current().unwrap();
+        assert_eq!(time(60), current.time);
+        assert_eq!(
+            (dec!(10), dec!(12), dec!(9), dec!(9), dec!(4)),
+            (
+                current.open,
+                current.high,
+                current.low,
+                current.close,
+                current.volume
+            )
+        );
+
+        // skips the intervals starting at 120 and 180
+        let completed = bars.push(&tick(245, dec!(11), dec!(1)));
+        assert_eq!(3, completed.len());
+        assert_eq!(time(60), completed[0].time);
+        assert_eq!(time(120), completed[1].time);
+        assert_eq!(dec!(9), completed[1].open);
+        assert_eq!(dec!(9), completed[2].close);
+        assert_eq!(dec!(0), completed[2].volume);
+        assert_eq!(time(240), bars.current().unwrap().time);
+
+        // late ticks are dropped
+        assert!(bars.push(&tick(200, dec!(100), dec!(1))).is_empty());
+        assert_eq!(1, bars.late_ticks());
+        assert_eq!(dec!(11), bars.current().unwrap().high);
+
+        assert!(bars.flush(time(299)).is_empty());
+        let completed = bars.flush(time(420));
+        assert_eq!(3, completed.len());
+        assert_eq!(time(360), completed[2].time);
+        assert!(bars.current().is_none());
+
+        assert!(bars.push(&tick(400, dec!(1), dec!(1))).is_empty());
+        assert_eq!(2, bars.late_ticks());
+    }
+
+    #[test]
+    fn test_bar_aggregator_skip_empty() {
+        let mut bars = BarAggregator::from_period::<Minute>().skip_empty();
+
+        bars.push(&tick(0, dec!(1), dec!(1)));
+        let completed = bars.push(&tick(300, dec!(2), dec!(1)));
+        assert_eq!(1, completed.len());
+        assert_eq!(time(0), completed[0].time);
+        assert_eq!(1, bars.flush(time(1000)).len());
+    }
+
+    #[test]
+    fn test_bar_aggregator_retract() {
+        let mut bars = BarAggregator::from_period::<Minute>().with_revisions(3);
+
+        bars.push_with_id(1, &tick(0, dec!(10), dec!(1)));
+        bars.push_with_id(2, &tick(10, dec!(12), dec!(1)));
+        bars.push_with_id(3, &tick(20, dec!(11), dec!(1)));
+        // ticks without an id can't be retracted
+        bars.push(&tick(30, dec!(11), dec!(1)));
+        assert_eq!(Retraction::NotFound, bars.retract(4, time(30)));
+        assert_eq!(Retraction::Current, bars.retract(2, time(10)));
+        assert_eq!(dec!(11), bars.current().unwrap().high);
+        assert_eq!(dec!(3), bars.current().unwrap().volume);
+
+        bars.push_with_id(5, &tick(70, dec!(13), dec!(1)));
+        let completed = bars.push_with_id(6, &tick(200, dec!(14), dec!(1)));
+        assert_eq!(2, completed.len());
+        assert_eq!(Some(time(0)), bars.horizon());
+
+        let revised = match bars.retract(5, time(70)) {
+            Retraction::Revised { previous, revised } => {
+                assert_eq!(completed[0], previous);
+                revised
+            }
+            retraction => panic!("unexpected {:?}", retraction),
+        };
+        // flat at the close of the previous candle
+        assert_eq!(time(60), revised.time);
+        assert_eq!((dec!(11), dec!(0)), (revised.high, revised.volume));
+
+        // too old, or never pushed
+        assert_eq!(1, bars.flush(time(240)).len());
+        assert_eq!(Some(time(60)), bars.horizon());
+        assert_eq!(Retraction::NotFound, bars.retract(1, time(0)));
+        assert_eq!(Retraction::NotFound, bars.retract(7, time(130)));
+
+        // without revisions no ticks are kept
+        let mut bars = BarAggregator::from_period::<Minute>();
+        bars.push_with_id(1, &tick(0, dec!(10), dec!(1)));
+        assert_eq!(Retraction::NotFound, bars.retract(1, time(0)));
+    }
+
+    fn trade(tid: u64, seconds: i64, price: Decimal, kind: TradeKind, broken: bool) -> Trade {
+        Trade {
+            tid,
+            time: time(seconds),
+            price,
+            quantity: dec!(2),
+            kind,
+            broken,
+        }
+    }
+
+    #[test]
+    fn test_trade_volumes() {
+        let mut volumes = TradeVolumes::new();
+
+        assert!(volumes.push(&trade(1, 0, dec!(10), TradeKind::Continuous, false)));
+        assert!(volumes.push(&trade(2, 0, dec!(20), TradeKind::Continuous, false)));
+        assert!(volumes.push(&trade(3, 0, dec!(30), TradeKind::Block, false)));
+        assert!(!volumes.push(&trade(1, 0, dec!(10), TradeKind::Continuous, false)));
+        assert_eq!(Some(dec!(15)), volumes.continuous.vwap());
+        assert_eq!(dec!(6), volumes.total().quantity);
+
+        assert!(volumes.push(&trade(2, 0, dec!(20), TradeKind::Continuous, true)));
+        assert!(!volumes.push(&trade(2, 0, dec!(20), TradeKind::Continuous, false)));
+        assert_eq!(Some(dec!(10)), volumes.continuous.vwap());
+        assert_eq!(dec!(2), volumes.broken.quantity);
+        assert_eq!(dec!(80), volumes.total().notional);
+
+        // pruned trades are forgotten and earlier ones ignored
+        assert!(volumes.push(&trade(4, 60, dec!(10), TradeKind::Continuous, false)));
+        volumes.prune(time(60));
+        assert_eq!(1, volumes.trades.len());
+        assert!(volumes.counted(4));
+        assert!(!volumes.push(&trade(3, 0, dec!(30), TradeKind::Block, true)));
+        assert_eq!(dec!(2), volumes.broken.quantity);
+    }
+
+    #[test]
+    fn test_aggregator_classified() {
+        let btc = &Pair::new("BTC", "USD");
+        let mut aggregator = Aggregator::new(&[(btc.clone(), Resolution::Minute)])
+            .with_filter(TradeFilter {
+                auction: false,
+                ..TradeFilter::default()
+            })
+            .with_revisions(10);
+
+        aggregator.push_classified(btc, &trade(1, 0, dec!(10), TradeKind::Continuous, false));
+        aggregator.push_classified(btc, &trade(2, 10, dec!(50), TradeKind::Auction, false));
+        aggregator.push_classified(btc, &trade(3, 20, dec!(90), TradeKind::Block, false));
+        aggregator.push_classified(btc, &trade(4, 30, dec!(12), TradeKind::Continuous, false));
+        let events =
+            aggregator.push_classified(btc, &trade(5, 60, dec!(11), TradeKind::Continuous, false));
+        assert!(matches!(
+            events.as_slice(),
+            [Event::Candle(_, _, candle)] if candle.high == dec!(12) && candle.volume == dec!(4)
+        ));
+        let volumes = aggregator.volumes(btc).unwrap();
+        assert_eq!(
+            (dec!(6), dec!(2), dec!(2)),
+            (
+                volumes.continuous.quantity,
+                volumes.auction.quantity,
+                volumes.block.quantity
+            )
+        );
+
+        // a break of a trade of a completed candle revises it
+        assert!(aggregator
+            .push_classified(btc, &trade(4, 30, dec!(12), TradeKind::Continuous, true))
+            .is_empty());
+        let revisions = aggregator.take_revisions();
+        assert_eq!(1, revisions.len());
+        assert_eq!(dec!(12), revisions[0].previous.close);
+        assert_eq!(
+            (dec!(10), dec!(2)),
+            (revisions[0].revised.close, revisions[0].revised.volume)
+        );
+        assert!(aggregator.take_revisions().is_empty());
+        assert_eq!(dec!(2), aggregator.volumes(btc).unwrap().broken.quantity);
+
+        // breaks of trades which weren't aggregated, or were never pushed unbroken,
+        // don't touch the candles even if they look alike
+        aggregator.push_classified(btc, &trade(3, 20, dec!(90), TradeKind::Block, true));
+        aggregator.push_classified(btc, &trade(6, 30, dec!(12), TradeKind::Continuous, true));
+        aggregator.push_classified(btc, &trade(7, 0, dec!(10), TradeKind::Continuous, true));
+        assert!(aggregator.take_revisions().is_empty());
+        assert_eq!(dec!(8), aggregator.volumes(btc).unwrap().broken.quantity);
+    }
+
+    #[test]
+    fn test_aggregator_prune() {
+        let btc = &Pair::new("BTC", "USD");
+        let mut aggregator = Aggregator::new(&[(btc.clone(), Resolution::Minute)]);
+
+        aggregator.push_classified(btc, &trade(1, 0, dec!(10), TradeKind::Continuous, false));
+        aggregator.push_classified(btc, &trade(2, 60, dec!(10), TradeKind::Continuous, false));
+        aggregator.push_classified(btc, &trade(3, 120, dec!(10), TradeKind::Continuous, false));
+        let volumes = aggregator.volumes(btc).unwrap();
+        assert_eq!(2, volumes.trades.len());
+        assert_eq!(dec!(6), volumes.continuous.quantity);
+
+        // without revisions breaks aren't retracted, and breaks before the current
+        // candle are ignored
+        aggregator.push_classified(btc, &trade(3, 120, dec!(10), TradeKind::Continuous, true));
+        aggregator.push_classified(btc, &trade(2, 60, dec!(10), TradeKind::Continuous, true));
+        assert!(aggregator.take_revisions().is_empty());
+        assert_eq!(dec!(2), aggregator.volumes(btc).unwrap().broken.quantity);
+        assert_eq!(dec!(2), aggregator.bars[0].2.current().unwrap().volume);
+    }
+
+    #[test]
+    fn test_aggregator() {
+        let (btc, eth) = (&Pair::new("BTC", "USD"), &Pair::new("ETH", "USD"));
+        let mut aggregator = Aggregator::new(&[
+            (btc.clone(), Resolution::Tick),
+            (btc.clone(), Resolution::Minute),
+            (btc.clone(), Resolution::Hour),
+            (eth.clone(), Resolution::Minute),
+        ]);
+
+        let events = aggregator.push(btc, &tick(0, dec!(1), dec!(1)));
+        assert!(matches!(
+            events.as_slice(),
+            [Event::Tick(pair, _)] if pair == btc
+        ));
+
+        // ether ticks aren't subscribed to
+        assert!(aggregator.push(eth, &tick(10, dec!(2), dec!(1))).is_empty());
+
+        let events = aggregator.push(btc, &tick(3600, dec!(3), dec!(1)));
+        let candles = events
+            .iter()
+            .filter(|event| matches!(event, Event::Candle(_, Resolution::Minute, _)))
+            .count();
+        assert_eq!(60, candles);
+        assert!(matches!(
+            &events[60],
+            Event::Candle(pair, Resolution::Hour, candle) if pair == btc && candle.close == dec!(1)
+        ));
+        assert!(matches!(events.last(), Some(Event::Tick(_, _))));
+
+        // completes the bitcoin candle and every ether candle of the first hour
+        let events = aggregator.flush(time(3660));
+        assert_eq!(62, events.len());
+        assert!(matches!(
+            &events[0],
+            Event::Candle(pair, Resolution::Minute, candle) if pair == btc && candle.time == time(3600)
+        ));
+        assert!(matches!(
+            &events[1],
+            Event::Candle(pair, Resolution::Minute, candle) if pair == eth && candle.volume == dec!(1)
+        ));
+    }
+}
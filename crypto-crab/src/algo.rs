@@ -1,7 +1,52 @@
+use crate::{candle::Candle, order::Broker, period::Resolution, tick::Tick};
+
+pub use exchange_api::Pair;
+
 pub trait AlgorithmData {
     type Data;
 }
 
+/// Market data which is delivered to an algorithm and kept up to date
+/// by whatever drives it, e.g. the backtester.
+///
+/// Usually implemented by the data struct generated by `#[derive(Algorithm)]`.
+pub trait MarketData: Default {
+    /// The pairs and resolutions the data consists of.
+    fn subscriptions() -> Vec<(Pair, Resolution)>;
+
+    /// Updates the data with a new candle of `pair`.
+    /// Returns `false` if the data doesn't track the pair at `resolution`.
+    fn update(&mut self, pair: &Pair, resolution: Resolution, candle: &Candle) -> bool;
+
+    /// Updates the data with a new trade of `pair`.
+    /// Returns `false` if the data doesn't track the pair's ticks.
+    fn update_tick(&mut self, pair: &Pair, tick: &Tick) -> bool;
+}
+
+/// A single update of market data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    Candle(Pair, Resolution, Candle),
+    Tick(Pair, Tick),
+}
+
+impl Event {
+    pub fn pair(&self) -> &Pair {
+        match self {
+            Self::Candle(pair, _, _) | Self::Tick(pair, _) => pair,
+        }
+    }
+
+    /// Updates `data` with the event. Returns `false` if the data doesn't track it.
+    pub fn apply<D: MarketData>(&self, data: &mut D) -> bool {
+        match self {
+            Self::Candle(pair, resolution, candle) => data.update(pair, *resolution, candle),
+            Self::Tick(pair, tick) => data.update_tick(pair, tick),
+        }
+    }
+}
+
 pub trait Algorithm: AlgorithmData {
-    fn on(&mut self, data: &Self::Data);
+    /// Called whenever new data is available. Orders are placed through `broker`.
+    fn on(&mut self, data: &Self::Data, broker: &mut dyn Broker);
 }
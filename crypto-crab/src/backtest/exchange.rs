@@ -0,0 +1,350 @@
+use crate::{
+    algo::Pair,
+    candle::Candle,
+    decimal::Decimal,
+    order::{validate, Broker, Fill, Liquidity, Order, OrderError, OrderId, OrderKind, Side},
+    time::DateTime,
+};
+use std::collections::HashMap;
+
+/// Determines the fee charged for a fill.
+pub trait FeeModel: Send {
+    fn fee(&self, notional: Decimal, liquidity: Liquidity) -> Decimal;
+}
+
+/// Fees as a fraction of the notional value, e.g. `0.001` for 10 basis points.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PercentFee {
+    pub maker: Decimal,
+    pub taker: Decimal,
+}
+
+impl FeeModel for PercentFee {
+    fn fee(&self, notional: Decimal, liquidity: Liquidity) -> Decimal {
+        match liquidity {
+            Liquidity::Maker => notional * self.maker,
+            Liquidity::Taker => notional * self.taker,
+        }
+    }
+}
+
+/// Determines the price a market order is executed at.
+pub trait SlippageModel: Send {
+    /// Adjusts `price`, the open of `candle`, for an order on `side`.
+    fn price(&self, side: Side, price: Decimal, candle: &Candle) -> Decimal;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoSlippage;
+
+impl SlippageModel for NoSlippage {
+    fn price(&self, _: Side, price: Decimal, _: &Candle) -> Decimal {
+        price
+    }
+}
+
+/// Moves the price against the order by a fraction of the price.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PercentSlippage(pub Decimal);
+
+impl SlippageModel for PercentSlippage {
+    fn price(&self, side: Side, price: Decimal, _: &Candle) -> Decimal {
+        price * (Decimal::ONE + side.sign() * self.0)
+    }
+}
+
+/// A simulated exchange which fills orders against candles.
+///
+/// Orders are only filled by candles which start at or after the time they were
+/// submitted: market orders at the open and limit orders at their limit price, or at the
+/// open if the candle gaps through it. Orders are always filled completely.
+pub struct SimulatedExchange {
+    cash: Decimal,
+    positions: HashMap<Pair, Decimal>,
+    // The last close of each pair, which positions are valued at.
+    prices: HashMap<Pair, Decimal>,
+    // The open orders and the time they were submitted at.
+    orders: Vec<(OrderId, Option<DateTime>, Order)>,
+    time: Option<DateTime>,
+    next_id: OrderId,
+    fills: Vec<Fill>,
+    fees: Box<dyn FeeModel>,
+    slippage: Box<dyn SlippageModel>,
+}
+
+impl SimulatedExchange {
+    pub fn new(cash: Decimal) -> Self {
+        Self {
+            cash,
+            positions: HashMap::new(),
+            prices: HashMap::new(),
+            orders: Vec::new(),
+            time: None,
+            next_id: 0,
+            fills: Vec::new(),
+            fees: Box::new(PercentFee::default()),
+            slippage: Box::new(NoSlippage),
+        }
+    }
+
+    pub fn set_fees(&mut self, fees: impl FeeModel + 'static) {
+        self.fees = Box::new(fees);
+    }
+
+    pub fn set_slippage(&mut self, slippage: impl SlippageModel + 'static) {
+        self.slippage = Box::new(slippage);
+    }
+
+    /// Sets the current time, which subsequently submitted orders are timestamped with.
+    pub fn set_time(&mut self, time: DateTime) {
+        self.time = Some(time);
+    }
+
+    /// Fills the open orders of `pair` which execute within `candle` and
+    /// values the pair at its close. Returns the new fills.
+    pub fn process(&mut self, pair: &Pair, candle: &Candle) -> &[Fill] {
+        let start = self.fills.len();
+        let orders = std::mem::take(&mut self.orders);
+
+        for (id, submitted, order) in orders {
+            let execution = match submitted {
+                Some(time) if candle.time < time => None,
+                _ => self.execution(&order, pair, candle),
+            };
+            match execution {
+                Some((price, liquidity)) => self.fill(id, order, price, liquidity, candle),
+                None => self.orders.push((id, submitted, order)),
+            }
+        }
+
+        self.prices.insert(pair.clone(), candle.close);
+        &self.fills[start..]
+    }
+
+    fn execution(
+        &self,
+        order: &Order,
+        pair: &Pair,
+        candle: &Candle,
+    ) -> Option<(Decimal, Liquidity)> {
+        if order.pair != *pair {
+            return None;
+        }
+
+        match (order.kind, order.side) {
+            (OrderKind::Market, side) => Some((
+                self.slippage.price(side, candle.open, candle),
+                Liquidity::Taker,
+            )),
+            (OrderKind::Limit(limit), Side::Buy) => {
+                (candle.low <= limit).then(|| (candle.open.min(limit), Liquidity::Maker))
+            }
+            (OrderKind::Limit(limit), Side::Sell) => {
+                (candle.high >= limit).then(|| (candle.open.max(limit), Liquidity::Maker))
+            }
+        }
+    }
+
+    fn fill(
+        &mut self,
+        order_id: OrderId,
+        order: Order,
+        price: Decimal,
+        liquidity: Liquidity,
+        candle: &Candle,
+    ) {
+        let notional = order.quantity * price;
+        let fee = self.fees.fee(notional, liquidity);
+
+        self.cash -= order.side.sign() * notional + fee;
+        *self.positions.entry(order.pair.clone()).or_default() +=
+            order.side.sign() * order.quantity;
+
+        self.fills.push(Fill {
+            order_id,
+            time: candle.time,
+            pair: order.pair,
+            side: order.side,
+            quantity: order.quantity,
+            price,
+            fee,
+            liquidity,
+        });
+    }
+
+    /// The cash plus the value of all positions at their last known price.
+    pub fn equity(&self) -> Decimal {
+        self.positions
+            .iter()
+            .filter_map(|(pair, quantity)| Some(*quantity * self.prices.get(pair)?))
+            .fold(self.cash, |equity, value| equity + value)
+    }
+
+    pub fn cancel_all(&mut self) {
+        self.orders.clear();
+    }
+
+    pub fn open_orders(&self) -> impl Iterator<Item = (OrderId, &Order)> {
+        self.orders.iter().map(|(id, _, order)| (*id, order))
+    }
+
+    pub fn fills(&self) -> &[Fill] {
+        &self.fills
+    }
+
+    pub fn into_fills(self) -> Vec<Fill> {
+        self.fills
+    }
+}
+
+impl Broker for SimulatedExchange {
+    fn submit(&mut self, order: Order) -> Result<OrderId, OrderError> {
+        validate(&order)?;
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.orders.push((id, self.time, order));
+        Ok(id)
+    }
+
+    fn cancel(&mut self, id: OrderId) -> bool {
+        let len = self.orders.len();
+        self.orders.retain(|(order_id, _, _)| *order_id != id);
+        self.orders.len() != len
+    }
+
+    fn position(&self, pair: &Pair) -> Decimal {
+        self.positions.get(pair).copied().unwrap_or_default()
+    }
+
+    fn cash(&self) -> Decimal {
+        self.cash
+    }
+
+    fn open_order_count(&self) -> usize {
+        self.orders.len()
+    }
+
+    fn open_quantity(&self, pair: &Pair) -> Decimal {
+        self.orders
+            .iter()
+            .filter(|(_, _, order)| order.pair == *pair)
+            .map(|(_, _, order)| order.side.sign() * order.quantity)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    fn btc() -> Pair {
+        Pair::new("BTC", "USD")
+    }
+
+    fn candle(open: Decimal, high: Decimal, low: Decimal, close: Decimal) -> Candle {
+        Candle {
+            time: Utc.timestamp_opt(0, 0).unwrap(),
+            open,
+            high,
+            low,
+            close,
+            volume: dec!(1),
+        }
+    }
+
+    #[test]
+    fn test_market_order() {
+        let mut exchange = SimulatedExchange::new(dec!(1000));
+        exchange.set_fees(PercentFee {
+            maker: dec!(0),
+            taker: dec!(0.01),
+        });
+        exchange.set_slippage(PercentSlippage(dec!(0.02)));
+
+        let order = Order::market(btc(), Side::Buy, dec!(2));
+        let id = exchange.submit(order).unwrap();
+
+        // other pairs don't fill the order
+        assert!(exchange
+            .process(
+                &Pair::new("ETH", "USD"),
+                &candle(dec!(1), dec!(1), dec!(1), dec!(1))
+            )
+            .is_empty());
+
+        let fills = exchange.process(&btc(), &candle(dec!(100), dec!(110), dec!(90), dec!(105)));
+        assert_eq!(1, fills.len());
+        assert_eq!(id, fills[0].order_id);
+        assert_eq!(dec!(102), fills[0].price);
+        assert_eq!(dec!(2.04), fills[0].fee);
+        assert_eq!(Liquidity::Taker, fills[0].liquidity);
+
+        assert_eq!(dec!(2), exchange.position(&btc()));
+        assert_eq!(dec!(793.96), exchange.cash());
+        assert_eq!(dec!(1003.96), exchange.equity());
+        assert_eq!(0, exchange.open_orders().count());
+    }
+
+    #[test]
+    fn test_limit_order() {
+        let mut exchange = SimulatedExchange::new(dec!(1000));
+        let buy = exchange
+            .submit(Order::limit(btc(), Side::Buy, dec!(1), dec!(95)))
+            .unwrap();
+        let sell = exchange
+            .submit(Order::limit(btc(), Side::Sell, dec!(1), dec!(120)))
+            .unwrap();
+
+        let fills = exchange.process(&btc(), &candle(dec!(100), dec!(110), dec!(96), dec!(100)));
+        assert!(fills.is_empty());
+
+        // gaps through the buy limit
+        let fills = exchange.process(&btc(), &candle(dec!(90), dec!(100), dec!(85), dec!(95)));
+        assert_eq!(1, fills.len());
+        assert_eq!(buy, fills[0].order_id);
+        assert_eq!(dec!(90), fills[0].price);
+        assert_eq!(Liquidity::Maker, fills[0].liquidity);
+
+        assert!(exchange.cancel(sell));
+        assert!(!exchange.cancel(sell));
+        assert!(exchange
+            .process(&btc(), &candle(dec!(130), dec!(130), dec!(130), dec!(130)))
+            .is_empty());
+        assert_eq!(dec!(1040), exchange.equity());
+    }
+
+    #[test]
+    fn test_submission_time() {
+        let mut exchange = SimulatedExchange::new(dec!(1000));
+        exchange.set_time(Utc.timestamp_opt(60, 0).unwrap());
+        exchange
+            .submit(Order::market(btc(), Side::Buy, dec!(1)))
+            .unwrap();
+
+        // started before the order was submitted
+        let early = candle(dec!(100), dec!(100), dec!(100), dec!(100));
+        assert!(exchange.process(&btc(), &early).is_empty());
+
+        let late = Candle {
+            time: Utc.timestamp_opt(60, 0).unwrap(),
+            ..early
+        };
+        assert_eq!(1, exchange.process(&btc(), &late).len());
+    }
+
+    #[test]
+    fn test_invalid_order() {
+        let mut exchange = SimulatedExchange::new(dec!(1000));
+        assert_eq!(
+            Err(OrderError::InvalidQuantity),
+            exchange.submit(Order::market(btc(), Side::Buy, dec!(0)))
+        );
+        assert_eq!(
+            Err(OrderError::InvalidPrice),
+            exchange.submit(Order::limit(btc(), Side::Buy, dec!(1), dec!(-1)))
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use super::report::Trade;
+use crate::{
+    decimal::{Decimal, MathematicalOps},
+    order::Fill,
+    time::{DateTime, Duration},
+};
+use market::stats::{moments::Moments, Stats};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Statistics of the closed trades. Averages are `None` if there are no trades
+/// of the kind they are taken over.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TradeStats {
+    pub count: usize,
+    pub win_rate: Option<Decimal>,
+    pub average_pnl: Option<Decimal>,
+    pub pnl_stddev: Option<Decimal>,
+    pub average_win: Option<Decimal>,
+    pub average_loss: Option<Decimal>,
+    pub largest_win: Option<Decimal>,
+    pub largest_loss: Option<Decimal>,
+    /// The gross profit of the winning trades over the gross loss of the losing ones.
+    pub profit_factor: Option<Decimal>,
+    /// The average time between the entry and exit of a trade, in seconds.
+    pub average_duration_secs: Option<i64>,
+}
+
+/// Performance metrics of an equity curve.
+///
+/// Ratios are annualized by the average interval between the equity samples and
+/// assume a risk free rate of zero. Metrics which are undefined for the given curve,
+/// e.g. because it is too short or has no spread, are `None`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Metrics {
+    pub start: Option<DateTime>,
+    pub end: Option<DateTime>,
+    pub initial_equity: Decimal,
+    pub final_equity: Decimal,
+    pub total_return: Option<Decimal>,
+    /// The compound annual growth rate.
+    pub cagr: Option<Decimal>,
+    /// The annualized standard deviation of the returns between equity samples.
+    pub volatility: Option<Decimal>,
+    pub sharpe: Option<Decimal>,
+    /// Like the Sharpe ratio, but only penalizing the deviation of negative returns.
+    pub sortino: Option<Decimal>,
+    pub skewness: Option<Decimal>,
+    /// The excess kurtosis of the returns.
+    pub kurtosis: Option<Decimal>,
+    /// The CAGR over the maximum drawdown.
+    pub calmar: Option<Decimal>,
+    /// The largest decline from a peak of the equity as a fraction of the peak.
+    pub max_drawdown: Decimal,
+    /// The longest time the equity stayed below a previous peak, in seconds.
+    pub max_drawdown_duration_secs: i64,
+    /// The fraction of equity samples at which a position was held.
+    pub exposure: Option<Decimal>,
+    /// The traded notional over the average equity.
+    pub turnover: Option<Decimal>,
+    pub trades: TradeStats,
+}
+
+impl Metrics {
+    pub fn new(
+        initial_equity: Decimal,
+        equity: &[(DateTime, Decimal)],
+        fills: &[Fill],
+        trades: &[Trade],
+    ) -> Self {
+        let final_equity = equity.last().map_or(initial_equity, |(_, e)| *e);
+        let total_return = (!equity.is_empty() && !initial_equity.is_zero())
+            .then(|| (final_equity - initial_equity) / initial_equity);
+
+        let returns = equity
+            .windows(2)
+            .filter_map(|pair| {
+                let ((_, prev), (_, equity)) = (pair[0], pair[1]);
+                (!prev.is_zero()).then(|| (equity - prev) / prev)
+            })
+            .collect::<Vec<_>>();
+        let stats = returns.iter().copied().collect::<Stats<Decimal>>();
+        let moments = returns.iter().copied().collect::<Moments<Decimal>>();
+        let periods = periods_per_year(equity);
+
+        let volatility = stats
+            .sample_stddev()
+            .zip(periods)
+            .and_then(|(stddev, periods)| Some(stddev * periods.sqrt()?));
+        let annual_mean = stats
+            .mean()
+            .zip(periods)
+            .map(|(mean, periods)| mean * periods);
+        let sharpe = annual_mean
+            .zip(volatility.filter(|v| !v.is_zero()))
+            .map(|(mean, volatility)| mean / volatility);
+        let sortino = annual_mean
+            .zip(downside_deviation(&returns).filter(|d| !d.is_zero()))
+            .zip(periods)
+            .and_then(|((mean, downside), periods)| Some(mean / (downside * periods.sqrt()?)));
+
+        let (max_drawdown, max_drawdown_duration) = drawdown(initial_equity, equity);
+        let cagr = cagr(initial_equity, equity);
+        let calmar = cagr
+            .filter(|_| !max_drawdown.is_zero())
+            .map(|cagr| cagr / max_drawdown);
+
+        let average_equity = equity
+            .iter()
+            .map(|(_, e)| *e)
+            .collect::<Stats<Decimal>>()
+            .mean()
+            .filter(|e| !e.is_zero());
+        let turnover = average_equity.map(|average| {
+            fills
+                .iter()
+                .map(|fill| fill.notional().abs())
+                .sum::<Decimal>()
+                / average
+        });
+
+        Self {
+            start: equity.first().map(|(time, _)| *time),
+            end: equity.last().map(|(time, _)| *time),
+            initial_equity,
+            final_equity,
+            total_return,
+            cagr,
+            volatility,
+            sharpe,
+            sortino,
+            skewness: moments.skewness(),
+            kurtosis: moments.kurtosis(),
+            calmar,
+            max_drawdown,
+            max_drawdown_duration_secs: max_drawdown_duration.num_seconds(),
+            exposure: exposure(equity, fills),
+            turnover,
+            trades: TradeStats::new(trades),
+        }
+    }
+}
+
+impl TradeStats {
+    pub fn new(trades: &[Trade]) -> Self {
+        let pnl = trades.iter().map(|t| t.pnl).collect::<Stats<Decimal>>();
+        let wins = trades
+            .iter()
+            .map(|t| t.pnl)
+            .filter(|pnl| *pnl > Decimal::ZERO)
+            .collect::<Vec<_>>();
+        let losses = trades
+            .iter()
+            .map(|t| t.pnl)
+            .filter(|pnl| *pnl < Decimal::ZERO)
+            .collect::<Vec<_>>();
+
+        let gross_profit = wins.iter().sum::<Decimal>();
+        let gross_loss = -losses.iter().sum::<Decimal>();
+        let count = Decimal::from(trades.len());
+
+        Self {
+            count: trades.len(),
+            win_rate: (!trades.is_empty()).then(|| Decimal::from(wins.len()) / count),
+            average_pnl: pnl.mean(),
+            pnl_stddev: pnl.sample_stddev(),
+            average_win: wins.iter().copied().collect::<Stats<Decimal>>().mean(),
+            average_loss: losses.iter().copied().collect::<Stats<Decimal>>().mean(),
+            largest_win: wins.iter().copied().max(),
+            largest_loss: losses.iter().copied().min(),
+            profit_factor: (!gross_loss.is_zero()).then(|| gross_profit / gross_loss),
+            average_duration_secs: (!trades.is_empty()).then(|| {
+                let total = trades
+                    .iter()
+                    .map(|t| (t.exit_time - t.entry_time).num_seconds())
+                    .sum::<i64>();
+                total / trades.len() as i64
+            }),
+        }
+    }
+}
+
+// The number of equity samples per year, by their average interval.
+fn periods_per_year(equity: &[(DateTime, Decimal)]) -> Option<Decimal> {
+    let (first, last) = (equity.first()?.0, equity.last()?.0);
+    let samples = equity.len() as i64 - 1;
+    let interval = (last - first).num_milliseconds() / samples.max(1);
+    (interval > 0)
+        .then(|| Decimal::from(Duration::days(365).num_milliseconds()) / Decimal::from(interval))
+}
+
+fn downside_deviation(returns: &[Decimal]) -> Option<Decimal> {
+    if returns.len() < 2 {
+        return None;
+    }
+    let squares = returns
+        .iter()
+        .map(|r| r.min(&Decimal::ZERO) * r.min(&Decimal::ZERO))
+        .sum::<Decimal>();
+    (squares / Decimal::from(returns.len() - 1)).sqrt()
+}
+
+fn cagr(initial_equity: Decimal, equity: &[(DateTime, Decimal)]) -> Option<Decimal> {
+    let (first, last) = (equity.first()?, equity.last()?);
+    let years = Decimal::from((last.0 - first.0).num_seconds())
+        / Decimal::from(Duration::days(365).num_seconds());
+    if initial_equity <= Decimal::ZERO || last.1 <= Decimal::ZERO || years.is_zero() {
+        return None;
+    }
+    Some((last.1 / initial_equity).checked_powd(Decimal::ONE / years)? - Decimal::ONE)
+}
+
+// The maximum drawdown and the longest time spent below a peak.
+fn drawdown(initial_equity: Decimal, equity: &[(DateTime, Decimal)]) -> (Decimal, Duration) {
+    let mut peak = (None::<DateTime>, initial_equity);
+    let (mut max_drawdown, mut max_duration) = (Decimal::ZERO, Duration::zero());
+
+    for (time, equity) in equity {
+        if *equity >= peak.1 {
+            peak = (Some(*time), *equity);
+            continue;
+        }
+        if peak.1 > Decimal::ZERO {
+            max_drawdown = max_drawdown.max((peak.1 - equity) / peak.1);
+        }
+        let since = peak.0.get_or_insert(*time);
+        max_duration = max_duration.max(*time - *since);
+    }
+
+    (max_drawdown, max_duration)
+}
+
+fn exposure(equity: &[(DateTime, Decimal)], fills: &[Fill]) -> Option<Decimal> {
+    if equity.is_empty() {
+        return None;
+    }
+
+    let mut fills = fills.iter().peekable();
+    let mut positions = HashMap::new();
+    let mut exposed = 0;
+    for (time, _) in equity {
+        while let Some(fill) = fills.next_if(|fill| fill.time <= *time) {
+            *positions.entry(&fill.pair).or_insert(Decimal::ZERO) +=
+                fill.side.sign() * fill.quantity;
+        }
+        if positions.values().any(|q| !q.is_zero()) {
+            exposed += 1;
+        }
+    }
+
+    Some(Decimal::from(exposed) / Decimal::from(equity.len()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algo::Pair;
+    use crate::order::{Liquidity, Side};
+    use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    fn time(day: i64) -> DateTime {
+        Utc.timestamp_opt(day * 86400, 0).unwrap()
+    }
+
+    fn trade(entry: i64, exit: i64, pnl: Decimal) -> Trade {
+        Trade {
+            pair: Pair::new("BTC", "USD"),
+            side: Side::Buy,
+            quantity: dec!(1),
+            entry_time: time(entry),
+            entry_price: dec!(100),
+            exit_time: time(exit),
+            exit_price: dec!(100) + pnl,
+            pnl,
+        }
+    }
+
+    fn fill(day: i64, side: Side) -> Fill {
+        Fill {
+            order_id: day as u64,
+            time: time(day),
+            pair: Pair::new("BTC", "USD"),
+            side,
+            quantity: dec!(1),
+            price: dec!(100),
+            fee: dec!(0),
+            liquidity: Liquidity::Taker,
+        }
+    }
+
+    #[test]
+    fn test_trade_stats() {
+        let stats = TradeStats::new(&[
+            trade(0, 1, dec!(30)),
+            trade(1, 3, dec!(-10)),
+            trade(3, 6, dec!(10)),
+            trade(6, 8, dec!(-20)),
+        ]);
+
+        assert_eq!(4, stats.count);
+        assert_eq!(Some(dec!(0.5)), stats.win_rate);
+        assert_eq!(Some(dec!(2.5)), stats.average_pnl);
+        assert_eq!(Some(dec!(20)), stats.average_win);
+        assert_eq!(Some(dec!(-15)), stats.average_loss);
+        assert_eq!(Some(dec!(30)), stats.largest_win);
+        assert_eq!(Some(dec!(-20)), stats.largest_loss);
+        assert_eq!(Some(dec!(40) / dec!(30)), stats.profit_factor);
+        assert_eq!(Some(2 * 86400), stats.average_duration_secs);
+
+        let empty = TradeStats::new(&[]);
+        assert_eq!(None, empty.win_rate);
+        assert_eq!(None, empty.profit_factor);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let equity = vec![
+            (time(0), dec!(100)),
+            (time(1), dec!(110)),
+            (time(2), dec!(99)),
+            (time(3), dec!(104.5)),
+            (time(4), dec!(121)),
+        ];
+        let fills = [fill(1, Side::Buy), fill(3, Side::Sell)];
+        let metrics = Metrics::new(dec!(100), &equity, &fills, &[]);
+
+        assert_eq!(Some(dec!(0.21)), metrics.total_return);
+        assert_eq!(dec!(0.1), metrics.max_drawdown);
+        assert_eq!(2 * 86400, metrics.max_drawdown_duration_secs);
+        assert_eq!(Some(dec!(0.4)), metrics.exposure);
+        assert_eq!(
+            Some((dec!(200) / dec!(106.9)).round_dp(10)),
+            metrics.turnover.map(|t| t.round_dp(10))
+        );
+
+        // returns of 10%, -10%, 5% and ~15.8%
+        let sharpe = metrics.sharpe.unwrap();
+        let sortino = metrics.sortino.unwrap();
+        assert!(sharpe > Decimal::ZERO && sortino > sharpe);
+        assert!(metrics.cagr.unwrap() > dec!(1000));
+        assert_eq!(
+            metrics.calmar.map(|c| c.round_dp(6)),
+            metrics.cagr.map(|c| (c / dec!(0.1)).round_dp(6))
+        );
+        assert_eq!(Some(time(0)), metrics.start);
+        assert_eq!(dec!(121), metrics.final_equity);
+    }
+
+    #[test]
+    fn test_metrics_empty() {
+        let metrics = Metrics::new(dec!(100), &[], &[], &[]);
+        assert_eq!(None, metrics.total_return);
+        assert_eq!(None, metrics.cagr);
+        assert_eq!(None, metrics.sharpe);
+        assert_eq!(None, metrics.exposure);
+        assert_eq!(dec!(0), metrics.max_drawdown);
+    }
+}
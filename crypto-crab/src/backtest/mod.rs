@@ -0,0 +1,373 @@
+pub mod exchange;
+pub mod metrics;
+pub mod optimize;
+pub mod report;
+pub mod tearsheet;
+
+use crate::{
+    algo::{Algorithm, MarketData, Pair},
+    candle::Candle,
+    decimal::Decimal,
+    period::Resolution,
+    risk::{Checked, RiskManager},
+    time::DateTime,
+};
+use exchange::{FeeModel, SimulatedExchange, SlippageModel};
+use report::Report;
+use std::{collections::BTreeMap, fs::File, io, io::BufReader, path::Path};
+
+/// Replays historical candles of one or more pairs into an algorithm in timestamp
+/// order and executes its orders on a `SimulatedExchange`.
+///
+/// Candles are delivered once they are complete, i.e. at the end of their interval.
+/// At each timestamp the exchange first fills the pending orders against the
+/// completed candles, then the algorithm is called with the updated data and
+/// finally the equity is valued at the closes of the candles.
+///
+/// Given a `RiskManager`, orders are checked against its limits, with the latest
+/// close of a pair as the reference price.
+pub struct Backtest {
+    initial_cash: Decimal,
+    // The candles by the time they are complete.
+    candles: BTreeMap<DateTime, Vec<(Pair, Resolution, Candle)>>,
+    exchange: SimulatedExchange,
+    risk: Option<RiskManager>,
+}
+
+impl Backtest {
+    pub fn new(initial_cash: Decimal) -> Self {
+        Self {
+            initial_cash,
+            candles: BTreeMap::new(),
+            exchange: SimulatedExchange::new(initial_cash),
+            risk: None,
+        }
+    }
+
+    pub fn with_risk(mut self, risk: RiskManager) -> Self {
+        self.risk = Some(risk);
+        self
+    }
+
+    pub fn with_fees(mut self, fees: impl FeeModel + 'static) -> Self {
+        self.exchange.set_fees(fees);
+        self
+    }
+
+    pub fn with_slippage(mut self, slippage: impl SlippageModel + 'static) -> Self {
+        self.exchange.set_slippage(slippage);
+        self
+    }
+
+    /// Adds the candles of `pair` at `resolution`, e.g. as returned by
+    /// `gemini::rest::candles` or `read_candles`. The candles may be in any order,
+    /// and a candle replaces an earlier one of the same pair, resolution and timestamp.
+    ///
+    /// # Panics
+    ///
+    /// If `resolution` is `Resolution::Tick`.
+    pub fn with_candles<C, I>(mut self, pair: Pair, resolution: Resolution, candles: I) -> Self
+    where
+        C: Into<Candle>,
+        I: IntoIterator<Item = C>,
+    {
+        let duration = resolution
+            .to_duration()
+            .expect("ticks can't be backtested as candles");
+
+        for candle in candles {
+            let candle = candle.into();
+            let candles = self.candles.entry(candle.time + duration).or_default();
+            match candles
+                .iter_mut()
+                .find(|(p, r, _)| *p == pair && *r == resolution)
+            {
+                Some((_, _, existing)) => *existing = candle,
+                None => candles.push((pair.clone(), resolution, candle)),
+            }
+        }
+        self
+    }
+
+    pub fn run<A>(mut self, algo: &mut A) -> Report
+    where
+        A: Algorithm,
+        A::Data: MarketData,
+    {
+        let mut data = A::Data::default();
+        let mut equity = Vec::with_capacity(self.candles.len());
+
+        for (time, candles) in self.candles {
+            for (pair, resolution, candle) in &candles {
+                self.exchange.process(pair, candle);
+                data.update(pair, *resolution, candle);
+            }
+
+            self.exchange.set_time(time);
+            match &mut self.risk {
+                Some(risk) => {
+                    for (pair, _, candle) in &candles {
+                        risk.set_reference(pair.clone(), candle.close);
+                    }
+                    risk.update_equity(time, self.exchange.equity());
+
+                    let mut broker = Checked {
+                        broker: &mut self.exchange,
+                        risk,
+                    };
+                    algo.on(&data, &mut broker);
+                }
+                None => algo.on(&data, &mut self.exchange),
+            }
+            equity.push((time, self.exchange.equity()));
+        }
+
+        Report::new(self.initial_cash, equity, self.exchange.into_fills())
+    }
+}
+
+/// Reads candles stored in the JSON format of Gemini's candles endpoint.
+pub fn read_candles(path: impl AsRef<Path>) -> io::Result<Vec<gemini::common::Candle>> {
+    let reader = BufReader::new(File::open(path)?);
+    let candles = serde_json::from_reader::<_, gemini::rest::candles::Candles>(reader)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(candles.candles)
+}
+
+#[cfg(test)]
+mod test {
+    use super::exchange::PercentFee;
+    use super::*;
+    use crate::{
+        algo::AlgorithmData,
+        order::{Broker, Order, OrderError, Side},
+        risk::{Limits, Violation},
+        tick::Tick,
+    };
+    use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    fn btcusd() -> Pair {
+        Pair::new("BTC", "USD")
+    }
+
+    fn ethusd() -> Pair {
+        Pair::new("ETH", "USD")
+    }
+
+    #[derive(Default)]
+    struct Closes {
+        btcusd: Option<Decimal>,
+        ethusd: Option<Decimal>,
+    }
+
+    impl MarketData for Closes {
+        fn subscriptions() -> Vec<(Pair, Resolution)> {
+            vec![
+                (btcusd(), Resolution::Minute),
+                (ethusd(), Resolution::Minute),
+            ]
+        }
+
+        fn update(&mut self, pair: &Pair, resolution: Resolution, candle: &Candle) -> bool {
+            match resolution {
+                Resolution::Minute if *pair == btcusd() => self.btcusd = Some(candle.close),
+                Resolution::Minute if *pair == ethusd() => self.ethusd = Some(candle.close),
+                _ => return false,
+            }
+            true
+        }
+
+        fn update_tick(&mut self, _: &Pair, _: &Tick) -> bool {
+            false
+        }
+    }
+
+    /// Buys bitcoin while it is above ether and sells it otherwise.
+    struct Switch;
+
+    impl AlgorithmData for Switch {
+        type Data = Closes;
+    }
+
+    impl Algorithm for Switch {
+        fn on(&mut self, data: &Closes, broker: &mut dyn Broker) {
+            if let (Some(btc), Some(eth)) = (data.btcusd, data.ethusd) {
+                let position = broker.position(&btcusd());
+                if btc > eth && position.is_zero() {
+                    broker
+                        .submit(Order::market(btcusd(), Side::Buy, dec!(1)))
+                        .unwrap();
+                } else if btc <= eth && !position.is_zero() {
+                    broker
+                        .submit(Order::market(btcusd(), Side::Sell, position))
+                        .unwrap();
+                }
+            }
+        }
+    }
+
+    fn candle(minute: i64, price: Decimal) -> gemini::common::Candle {
+        gemini::common::Candle {
+            time: Utc.timestamp_opt(minute * 60, 0).unwrap(),
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: dec!(1),
+        }
+    }
+
+    #[test]
+    fn test_backtest() {
+        let btc = vec![
+            candle(3, dec!(12)),
+            candle(2, dec!(11)),
+            candle(1, dec!(12)),
+            candle(0, dec!(8)),
+            candle(4, dec!(9)),
+            candle(5, dec!(10)),
+        ];
+        let eth = (0..6).map(|minute| candle(minute, dec!(10)));
+
+        let report = Backtest::new(dec!(100))
+            .with_fees(PercentFee {
+                maker: dec!(0),
+                taker: dec!(0.1),
+            })
+            .with_candles(btcusd(), Resolution::Minute, btc)
+            .with_candles(ethusd(), Resolution::Minute, eth)
+            .run(&mut Switch);
+
+        // bought at the open of minute 2 and sold at the open of minute 5
+        assert_eq!(2, report.fills.len());
+        assert_eq!(dec!(11), report.fills[0].price);
+        assert_eq!(dec!(10), report.fills[1].price);
+
+        assert_eq!(1, report.trades.len());
+        assert_eq!(dec!(-3.1), report.trades[0].pnl);
+
+        let equity = report.equity.iter().map(|(_, e)| *e).collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                dec!(100),
+                dec!(100),
+                dec!(98.9),
+                dec!(99.9),
+                dec!(96.9),
+                dec!(96.9)
+            ],
+            equity
+        );
+        assert_eq!(Some(dec!(-0.031)), report.summary.total_return);
+        assert_eq!(Some(dec!(0)), report.summary.win_rate);
+    }
+
+    /// Buys bitcoin on every candle.
+    #[derive(Default)]
+    struct Accumulate {
+        rejections: Vec<OrderError>,
+    }
+
+    impl AlgorithmData for Accumulate {
+        type Data = Closes;
+    }
+
+    impl Algorithm for Accumulate {
+        fn on(&mut self, _: &Closes, broker: &mut dyn Broker) {
+            if let Err(e) = broker.submit(Order::market(btcusd(), Side::Buy, dec!(1))) {
+                self.rejections.push(e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_backtest_risk() {
+        let btc = (0..4).map(|minute| candle(minute, dec!(10)));
+        let mut limits = Limits::default();
+        limits.max_position.insert(btcusd(), dec!(2));
+
+        let mut algo = Accumulate::default();
+        let report = Backtest::new(dec!(100))
+            .with_risk(RiskManager::new(limits))
+            .with_candles(btcusd(), Resolution::Minute, btc)
+            .run(&mut algo);
+
+        // the orders of the last two candles would exceed the position limit
+        assert_eq!(2, report.fills.len());
+        assert_eq!(2, algo.rejections.len());
+        assert!(matches!(
+            algo.rejections[0],
+            OrderError::Risk(Violation::Position { position, .. }) if position == dec!(3)
+        ));
+    }
+
+    #[derive(Default)]
+    struct MinuteAndHour {
+        minute: Option<DateTime>,
+        hour: Option<Decimal>,
+    }
+
+    impl MarketData for MinuteAndHour {
+        fn subscriptions() -> Vec<(Pair, Resolution)> {
+            vec![(btcusd(), Resolution::Minute), (btcusd(), Resolution::Hour)]
+        }
+
+        fn update(&mut self, _: &Pair, resolution: Resolution, candle: &Candle) -> bool {
+            match resolution {
+                Resolution::Minute => self.minute = Some(candle.time),
+                Resolution::Hour => self.hour = Some(candle.close),
+                _ => return false,
+            }
+            true
+        }
+
+        fn update_tick(&mut self, _: &Pair, _: &Tick) -> bool {
+            false
+        }
+    }
+
+    /// Records the hour close seen with each minute candle.
+    #[derive(Default)]
+    struct Observer {
+        seen: Vec<(Option<DateTime>, Option<Decimal>)>,
+    }
+
+    impl AlgorithmData for Observer {
+        type Data = MinuteAndHour;
+    }
+
+    impl Algorithm for Observer {
+        fn on(&mut self, data: &MinuteAndHour, _: &mut dyn Broker) {
+            self.seen.push((data.minute, data.hour));
+        }
+    }
+
+    #[test]
+    fn test_backtest_mixed_resolutions() {
+        let minutes = (0..61).map(|minute| candle(minute, dec!(10)));
+        let hour = gemini::common::Candle {
+            close: dec!(999),
+            ..candle(0, dec!(10))
+        };
+
+        let mut algo = Observer::default();
+        Backtest::new(dec!(100))
+            .with_candles(btcusd(), Resolution::Minute, minutes)
+            .with_candles(btcusd(), Resolution::Hour, vec![hour])
+            .run(&mut algo);
+
+        // the hour candle completes together with its last minute candle, not before
+        let minute = |minute: i64| Some(Utc.timestamp_opt(minute * 60, 0).unwrap());
+        let hour_at = |time| {
+            algo.seen
+                .iter()
+                .filter(|(minute, _)| *minute == time)
+                .map(|(_, hour)| *hour)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![None], hour_at(minute(0)));
+        assert_eq!(vec![None], hour_at(minute(58)));
+        assert_eq!(Some(dec!(999)), *hour_at(minute(59)).last().unwrap());
+    }
+}
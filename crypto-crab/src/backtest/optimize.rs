@@ -0,0 +1,558 @@
+use super::{metrics::Metrics, Backtest};
+use crate::{
+    algo::{Algorithm, MarketData, Pair},
+    candle::Candle,
+    decimal::Decimal,
+    period::Resolution,
+    time::{DateTime, Duration},
+};
+use num_traits::ToPrimitive;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashSet},
+    ops::Range,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+/// The values of the named parameters of one backtest.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize)]
+pub struct Params(BTreeMap<String, Decimal>);
+
+impl Params {
+    pub fn get(&self, name: &str) -> Option<Decimal> {
+        self.0.get(name).copied()
+    }
+
+    pub fn with(mut self, name: impl Into<String>, value: Decimal) -> Self {
+        self.0.insert(name.into(), value);
+        self
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Decimal)> {
+        self.0.iter().map(|(name, value)| (name.as_str(), *value))
+    }
+}
+
+/// The values to try for each parameter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParamSpace {
+    dimensions: Vec<(String, Vec<Decimal>)>,
+}
+
+impl ParamSpace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a parameter, replacing the values of an earlier one of the same name.
+    pub fn with<I>(mut self, name: impl Into<String>, values: I) -> Self
+    where
+        I: IntoIterator<Item = Decimal>,
+    {
+        let name = name.into();
+        let values = values.into_iter().collect();
+        match self.dimensions.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, existing)) => *existing = values,
+            None => self.dimensions.push((name, values)),
+        }
+        self
+    }
+
+    /// Adds a parameter taking the values from `start` up to and including `end` in
+    /// increments of `step`.
+    ///
+    /// # Panics
+    ///
+    /// If `step` isn't positive.
+    pub fn with_range(
+        self,
+        name: impl Into<String>,
+        start: Decimal,
+        end: Decimal,
+        step: Decimal,
+    ) -> Self {
+        assert!(step > Decimal::ZERO, "the step of a range must be positive");
+        let count = ((end - start) / step)
+            .floor()
+            .to_usize()
+            .map_or(0, |count| count + 1);
+        self.with(name, (0..count).map(|i| start + step * Decimal::from(i)))
+    }
+
+    /// The number of combinations of all values.
+    pub fn len(&self) -> usize {
+        if self.dimensions.is_empty() {
+            return 0;
+        }
+        self.dimensions
+            .iter()
+            .map(|(_, values)| values.len())
+            .product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Every combination of the values.
+    pub fn grid(&self) -> Vec<Params> {
+        (0..self.len()).map(|i| self.combination(i)).collect()
+    }
+
+    /// Up to `count` distinct combinations, chosen at random but reproducibly for
+    /// the same `seed`.
+    pub fn sample(&self, count: usize, seed: u64) -> Vec<Params> {
+        let len = self.len();
+        let count = count.min(len);
+        let mut rng = SplitMix64(seed);
+        let mut chosen = HashSet::with_capacity(count);
+        let mut params = Vec::with_capacity(count);
+
+        while params.len() < count {
+            let i = (rng.next() % len as u64) as usize;
+            if chosen.insert(i) {
+                params.push(self.combination(i));
+            }
+        }
+        params
+    }
+
+    // The `i`th combination, counting with the last parameter varying fastest.
+    fn combination(&self, mut i: usize) -> Params {
+        let mut params = Params::default();
+        for (name, values) in self.dimensions.iter().rev() {
+            params.0.insert(name.clone(), values[i % values.len()]);
+            i /= values.len();
+        }
+        params
+    }
+}
+
+// A small, seedable generator, good enough to pick parameter combinations.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// The metric runs are ranked by. Higher scores are better.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    TotalReturn,
+    Cagr,
+    Sharpe,
+    Sortino,
+    Calmar,
+    ProfitFactor,
+    /// Scored as the negated drawdown, so the smallest drawdown ranks first.
+    MaxDrawdown,
+}
+
+impl Objective {
+    pub fn score(self, metrics: &Metrics) -> Option<Decimal> {
+        match self {
+            Self::TotalReturn => metrics.total_return,
+            Self::Cagr => metrics.cagr,
+            Self::Sharpe => metrics.sharpe,
+            Self::Sortino => metrics.sortino,
+            Self::Calmar => metrics.calmar,
+            Self::ProfitFactor => metrics.trades.profit_factor,
+            Self::MaxDrawdown => Some(-metrics.max_drawdown),
+        }
+    }
+}
+
+/// The result of the backtest of one set of parameters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Run {
+    pub params: Params,
+    pub metrics: Metrics,
+    /// `None` if the objective is undefined for the run, e.g. a Sharpe ratio without trades.
+    pub score: Option<Decimal>,
+}
+
+/// One step of a walk-forward optimization.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Fold {
+    pub in_sample: Range<DateTime>,
+    pub out_of_sample: Range<DateTime>,
+    /// The best run on the in-sample candles.
+    pub best: Run,
+    /// The parameters of `best` run on the out-of-sample candles.
+    pub validation: Run,
+}
+
+type Setup = dyn Fn(Backtest) -> Backtest + Send + Sync;
+
+/// Runs backtests of an algorithm over many sets of parameters in parallel and ranks
+/// them by an `Objective`.
+///
+/// Each backtest starts from the same candles and initial cash. Fees, slippage and risk
+/// limits are applied to every backtest with `with_setup`.
+pub struct Optimizer {
+    initial_cash: Decimal,
+    candles: Vec<(Pair, Resolution, Vec<Candle>)>,
+    objective: Objective,
+    threads: usize,
+    setup: Option<Box<Setup>>,
+}
+
+impl Optimizer {
+    pub fn new(initial_cash: Decimal, objective: Objective) -> Self {
+        Self {
+            initial_cash,
+            candles: Vec::new(),
+            objective,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            setup: None,
+        }
+    }
+
+    /// Sets the number of backtests run at the same time, by default the available
+    /// parallelism.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Configures each backtest, e.g. with fees.
+    pub fn with_setup(
+        mut self,
+        setup: impl Fn(Backtest) -> Backtest + Send + Sync + 'static,
+    ) -> Self {
+        self.setup = Some(Box::new(setup));
+        self
+    }
+
+    /// Adds the candles of `pair` at `resolution`, see `Backtest::with_candles`.
+    pub fn with_candles<C, I>(mut self, pair: Pair, resolution: Resolution, candles: I) -> Self
+    where
+        C: Into<Candle>,
+        I: IntoIterator<Item = C>,
+    {
+        let candles = candles.into_iter().map(Into::into).collect();
+        self.candles.push((pair, resolution, candles));
+        self
+    }
+
+    /// Backtests the algorithms `algo` creates for each of `params` and returns the runs
+    /// from the best to the worst score, with undefined scores last.
+    pub fn run<A, F>(&self, params: &[Params], algo: F) -> Vec<Run>
+    where
+        A: Algorithm,
+        A::Data: MarketData,
+        F: Fn(&Params) -> A + Sync,
+    {
+        let span = self.span();
+        self.run_in(span, params, &algo)
+    }
+
+    /// Splits the candles into consecutive folds of `in_sample` followed by
+    /// `out_of_sample`, optimizes `params` on the in-sample candles of each fold and
+    /// validates the best of them on the out-of-sample candles. The folds advance by
+    /// `out_of_sample`, so the out-of-sample periods don't overlap.
+    ///
+    /// Algorithms start without data in each period, so indicators need to warm up
+    /// again at the start of the out-of-sample candles.
+    ///
+    /// # Panics
+    ///
+    /// If either duration isn't positive.
+    pub fn walk_forward<A, F>(
+        &self,
+        in_sample: Duration,
+        out_of_sample: Duration,
+        params: &[Params],
+        algo: F,
+    ) -> Vec<Fold>
+    where
+        A: Algorithm,
+        A::Data: MarketData,
+        F: Fn(&Params) -> A + Sync,
+    {
+        assert!(
+            in_sample > Duration::zero() && out_of_sample > Duration::zero(),
+            "walk-forward periods must be positive"
+        );
+        let span = match self.span() {
+            Some(span) => span,
+            None => return Vec::new(),
+        };
+
+        let mut folds = Vec::new();
+        let mut start = span.start;
+        while start + in_sample < span.end {
+            let in_range = start..start + in_sample;
+            let out_range = in_range.end..(in_range.end + out_of_sample).min(span.end);
+
+            let best = self
+                .run_in(Some(in_range.clone()), params, &algo)
+                .into_iter()
+                .next();
+            if let Some(best) = best {
+                let validation = self.backtest(out_range.clone(), &best.params, &algo);
+                folds.push(Fold {
+                    in_sample: in_range,
+                    out_of_sample: out_range,
+                    best,
+                    validation,
+                });
+            }
+            start += out_of_sample;
+        }
+        folds
+    }
+
+    // The times from the start of the first candle to the start of the last.
+    fn span(&self) -> Option<Range<DateTime>> {
+        let times = self
+            .candles
+            .iter()
+            .flat_map(|(_, _, candles)| candles.iter().map(|candle| candle.time));
+        let start = times.clone().min()?;
+        let end = times.max()?;
+        Some(start..end + Duration::nanoseconds(1))
+    }
+
+    fn run_in<A, F>(&self, span: Option<Range<DateTime>>, params: &[Params], algo: &F) -> Vec<Run>
+    where
+        A: Algorithm,
+        A::Data: MarketData,
+        F: Fn(&Params) -> A + Sync,
+    {
+        let span = match span {
+            Some(span) => span,
+            None => return Vec::new(),
+        };
+
+        let next = AtomicUsize::new(0);
+        let runs = Mutex::new(Vec::with_capacity(params.len()));
+        thread::scope(|scope| {
+            for _ in 0..self.threads.min(params.len()) {
+                scope.spawn(|| {
+                    while let Some(params) = params.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let run = self.backtest(span.clone(), params, algo);
+                        runs.lock().unwrap().push(run);
+                    }
+                });
+            }
+        });
+
+        let mut runs = runs.into_inner().unwrap();
+        // by score, then by the parameters to be independent of the thread timing
+        runs.sort_by(|a, b| {
+            match (a.score, b.score) {
+                (Some(a_score), Some(b_score)) => b_score.cmp(&a_score),
+                (a_score, b_score) => b_score.is_some().cmp(&a_score.is_some()),
+            }
+            .then_with(|| a.params.0.cmp(&b.params.0))
+        });
+        runs
+    }
+
+    fn backtest<A, F>(&self, span: Range<DateTime>, params: &Params, algo: &F) -> Run
+    where
+        A: Algorithm,
+        A::Data: MarketData,
+        F: Fn(&Params) -> A,
+    {
+        let mut backtest = Backtest::new(self.initial_cash);
+        if let Some(setup) = &self.setup {
+            backtest = setup(backtest);
+        }
+        for (pair, resolution, candles) in &self.candles {
+            let candles = candles
+                .iter()
+                .filter(|candle| span.contains(&candle.time))
+                .cloned();
+            backtest = backtest.with_candles(pair.clone(), *resolution, candles);
+        }
+
+        let metrics = backtest.run(&mut algo(params)).metrics();
+        Run {
+            params: params.clone(),
+            score: self.objective.score(&metrics),
+            metrics,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        algo::AlgorithmData,
+        order::{Broker, Order, Side},
+        tick::Tick,
+    };
+    use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    fn btc() -> Pair {
+        Pair::new("BTC", "USD")
+    }
+
+    #[derive(Default)]
+    struct Close(Option<Decimal>);
+
+    impl MarketData for Close {
+        fn subscriptions() -> Vec<(Pair, Resolution)> {
+            vec![(btc(), Resolution::Minute)]
+        }
+
+        fn update(&mut self, _: &Pair, _: Resolution, candle: &Candle) -> bool {
+            self.0 = Some(candle.close);
+            true
+        }
+
+        fn update_tick(&mut self, _: &Pair, _: &Tick) -> bool {
+            false
+        }
+    }
+
+    /// Buys once the close is below `entry` and sells once it is above `exit`.
+    struct Threshold {
+        entry: Decimal,
+        exit: Decimal,
+    }
+
+    impl AlgorithmData for Threshold {
+        type Data = Close;
+    }
+
+    impl Algorithm for Threshold {
+        fn on(&mut self, data: &Close, broker: &mut dyn Broker) {
+            let close = match data.0 {
+                Some(close) => close,
+                None => return,
+            };
+            let position = broker.position(&btc());
+            if position.is_zero() && close < self.entry {
+                broker
+                    .submit(Order::market(btc(), Side::Buy, dec!(1)))
+                    .unwrap();
+            } else if !position.is_zero() && close > self.exit {
+                broker
+                    .submit(Order::market(btc(), Side::Sell, position))
+                    .unwrap();
+            }
+        }
+    }
+
+    fn threshold(params: &Params) -> Threshold {
+        Threshold {
+            entry: params.get("entry").unwrap(),
+            exit: params.get("exit").unwrap(),
+        }
+    }
+
+    // Oscillates between 10 and 20 with a period of ten minutes.
+    fn candles(minutes: i64) -> Vec<Candle> {
+        (0..minutes)
+            .map(|minute| {
+                let phase = minute % 10;
+                let price = Decimal::from(10 + 2 * phase.min(10 - phase));
+                Candle {
+                    time: Utc.timestamp_opt(minute * 60, 0).unwrap(),
+                    open: price,
+                    high: price,
+                    low: price,
+                    close: price,
+                    volume: dec!(1),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_param_space() {
+        let space = ParamSpace::new()
+            .with("a", vec![dec!(1), dec!(2)])
+            .with_range("b", dec!(0.5), dec!(1.5), dec!(0.5));
+        assert_eq!(6, space.len());
+
+        let grid = space.grid();
+        assert_eq!(
+            Params::default().with("a", dec!(1)).with("b", dec!(0.5)),
+            grid[0]
+        );
+        assert_eq!(
+            Params::default().with("a", dec!(2)).with("b", dec!(1.5)),
+            grid[5]
+        );
+
+        let sample = space.sample(4, 7);
+        assert_eq!(4, sample.len());
+        assert_eq!(4, sample.iter().collect::<HashSet<_>>().len());
+        assert!(sample.iter().all(|params| grid.contains(params)));
+        assert_eq!(sample, space.sample(4, 7));
+        assert_eq!(6, space.sample(10, 7).len());
+        assert!(ParamSpace::new().grid().is_empty());
+    }
+
+    #[test]
+    fn test_optimize() {
+        let space = ParamSpace::new()
+            .with("entry", vec![dec!(11), dec!(15), dec!(30)])
+            .with("exit", vec![dec!(13), dec!(19)]);
+        let optimizer = Optimizer::new(dec!(1000), Objective::TotalReturn)
+            .with_threads(3)
+            .with_candles(btc(), Resolution::Minute, candles(60));
+
+        let runs = optimizer.run(&space.grid(), threshold);
+        assert_eq!(6, runs.len());
+        // buying the lows and selling the highs is the most profitable
+        assert_eq!(
+            Params::default()
+                .with("entry", dec!(11))
+                .with("exit", dec!(19)),
+            runs[0].params
+        );
+        assert!(runs.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        assert_eq!(
+            runs,
+            optimizer.with_threads(1).run(&space.grid(), threshold)
+        );
+    }
+
+    #[test]
+    fn test_walk_forward() {
+        let space = ParamSpace::new()
+            .with("entry", vec![dec!(11), dec!(15)])
+            .with("exit", vec![dec!(19)]);
+        let optimizer = Optimizer::new(dec!(1000), Objective::TotalReturn).with_candles(
+            btc(),
+            Resolution::Minute,
+            candles(60),
+        );
+
+        let folds = optimizer.walk_forward(
+            Duration::minutes(30),
+            Duration::minutes(10),
+            &space.grid(),
+            threshold,
+        );
+        assert_eq!(3, folds.len());
+        for (i, fold) in folds.iter().enumerate() {
+            let start = Utc.timestamp_opt(600 * i as i64, 0).unwrap();
+            assert_eq!(start, fold.in_sample.start);
+            assert_eq!(fold.in_sample.end, fold.out_of_sample.start);
+            assert_eq!(fold.best.params, fold.validation.params);
+            assert_eq!(Some(dec!(11)), fold.best.params.get("entry"));
+        }
+        assert_eq!(
+            Utc.timestamp_opt(3540, 0).unwrap() + Duration::nanoseconds(1),
+            folds[2].out_of_sample.end
+        );
+    }
+}
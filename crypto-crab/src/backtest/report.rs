@@ -0,0 +1,289 @@
+use super::metrics::Metrics;
+use crate::{
+    algo::Pair,
+    decimal::{Decimal, MathematicalOps},
+    order::{Fill, Side},
+    time::{DateTime, Duration},
+};
+use market::stats::Stats;
+use serde::{Serialize, Serializer};
+use std::collections::HashMap;
+
+/// A position which was (partially) opened and closed again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Trade {
+    #[serde(serialize_with = "serialize_pair")]
+    pub pair: Pair,
+    // `Buy` for long and `Sell` for short positions.
+    pub side: Side,
+    pub quantity: Decimal,
+    pub entry_time: DateTime,
+    // The average price the position was opened at.
+    pub entry_price: Decimal,
+    pub exit_time: DateTime,
+    pub exit_price: Decimal,
+    // The realized profit net of the entry and exit fees.
+    pub pnl: Decimal,
+}
+
+// As in "BTC/USD".
+fn serialize_pair<S: Serializer>(pair: &Pair, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(pair)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    /// The change of the equity relative to the initial cash.
+    /// `None` if there was no initial cash.
+    pub total_return: Option<Decimal>,
+    /// The annualized Sharpe ratio of the returns between equity samples, assuming
+    /// a risk free rate of zero. `None` if there are less than three samples or
+    /// the returns have no spread.
+    pub sharpe: Option<Decimal>,
+    /// The largest decline from a peak of the equity as a fraction of the peak.
+    pub max_drawdown: Decimal,
+    /// The fraction of trades with a positive profit. `None` if there are no trades.
+    pub win_rate: Option<Decimal>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub initial_cash: Decimal,
+    /// The equity after each point in time of the backtest.
+    pub equity: Vec<(DateTime, Decimal)>,
+    pub fills: Vec<Fill>,
+    pub trades: Vec<Trade>,
+    pub summary: Summary,
+}
+
+impl Report {
+    pub fn new(initial_cash: Decimal, equity: Vec<(DateTime, Decimal)>, fills: Vec<Fill>) -> Self {
+        let trades = trades(&fills);
+        let summary = Summary {
+            total_return: total_return(initial_cash, &equity),
+            sharpe: sharpe(&equity),
+            max_drawdown: max_drawdown(initial_cash, &equity),
+            win_rate: win_rate(&trades),
+        };
+
+        Self {
+            initial_cash,
+            equity,
+            fills,
+            trades,
+            summary,
+        }
+    }
+
+    pub fn final_equity(&self) -> Decimal {
+        self.equity
+            .last()
+            .map_or(self.initial_cash, |(_, equity)| *equity)
+    }
+
+    /// The full set of performance metrics, see `tearsheet` for reports of them.
+    pub fn metrics(&self) -> Metrics {
+        Metrics::new(self.initial_cash, &self.equity, &self.fills, &self.trades)
+    }
+}
+
+struct Position {
+    // Signed, negative when short.
+    quantity: Decimal,
+    price: Decimal,
+    time: DateTime,
+    // The entry fees not yet attributed to a trade.
+    fees: Decimal,
+}
+
+/// Matches the fills of each pair into trades, valuing positions at their average cost.
+pub fn trades(fills: &[Fill]) -> Vec<Trade> {
+    let mut positions = HashMap::<Pair, Position>::new();
+    let mut trades = Vec::new();
+
+    for fill in fills {
+        let position = positions.entry(fill.pair.clone()).or_insert(Position {
+            quantity: Decimal::ZERO,
+            price: Decimal::ZERO,
+            time: fill.time,
+            fees: Decimal::ZERO,
+        });
+        let sign = fill.side.sign();
+
+        if position.quantity.is_zero()
+            || position.quantity.is_sign_positive() == (fill.side == Side::Buy)
+        {
+            // opening or adding to a position
+            if position.quantity.is_zero() {
+                position.time = fill.time;
+            }
+            let held = position.quantity.abs();
+            position.price =
+                (position.price * held + fill.price * fill.quantity) / (held + fill.quantity);
+            position.quantity += sign * fill.quantity;
+            position.fees += fill.fee;
+            continue;
+        }
+
+        let held = position.quantity.abs();
+        let closed = fill.quantity.min(held);
+        let entry_fee = position.fees * closed / held;
+        let exit_fee = fill.fee * closed / fill.quantity;
+
+        trades.push(Trade {
+            pair: fill.pair.clone(),
+            side: if sign.is_sign_negative() {
+                Side::Buy
+            } else {
+                Side::Sell
+            },
+            quantity: closed,
+            entry_time: position.time,
+            entry_price: position.price,
+            exit_time: fill.time,
+            exit_price: fill.price,
+            pnl: -sign * (fill.price - position.price) * closed - entry_fee - exit_fee,
+        });
+
+        position.quantity += sign * closed;
+        position.fees -= entry_fee;
+
+        // the remainder reverses the position
+        let remaining = fill.quantity - closed;
+        if !remaining.is_zero() {
+            *position = Position {
+                quantity: sign * remaining,
+                price: fill.price,
+                time: fill.time,
+                fees: fill.fee - exit_fee,
+            };
+        }
+    }
+
+    trades
+}
+
+fn total_return(initial_cash: Decimal, equity: &[(DateTime, Decimal)]) -> Option<Decimal> {
+    let (_, last) = equity.last()?;
+    (!initial_cash.is_zero()).then(|| (*last - initial_cash) / initial_cash)
+}
+
+fn sharpe(equity: &[(DateTime, Decimal)]) -> Option<Decimal> {
+    if equity.len() < 3 {
+        return None;
+    }
+
+    let stats = equity
+        .windows(2)
+        .filter_map(|pair| {
+            let ((_, prev), (_, equity)) = (pair[0], pair[1]);
+            (!prev.is_zero()).then(|| (equity - prev) / prev)
+        })
+        .collect::<Stats<Decimal>>();
+
+    let stddev = stats.sample_stddev().filter(|s| !s.is_zero())?;
+
+    // annualized by the average interval between the samples
+    let (first, last) = (equity.first()?.0, equity.last()?.0);
+    let interval = (last - first).num_milliseconds() / (equity.len() as i64 - 1);
+    if interval <= 0 {
+        return None;
+    }
+    let periods = Decimal::from(Duration::days(365).num_milliseconds()) / Decimal::from(interval);
+
+    Some(stats.mean()? / stddev * periods.sqrt()?)
+}
+
+fn max_drawdown(initial_cash: Decimal, equity: &[(DateTime, Decimal)]) -> Decimal {
+    let mut peak = initial_cash;
+    let mut drawdown = Decimal::ZERO;
+
+    for (_, equity) in equity {
+        peak = peak.max(*equity);
+        if peak > Decimal::ZERO {
+            drawdown = drawdown.max((peak - equity) / peak);
+        }
+    }
+
+    drawdown
+}
+
+fn win_rate(trades: &[Trade]) -> Option<Decimal> {
+    let wins = trades.iter().filter(|t| t.pnl > Decimal::ZERO).count();
+    (!trades.is_empty()).then(|| Decimal::from(wins) / Decimal::from(trades.len()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::order::Liquidity;
+    use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    fn time(day: i64) -> DateTime {
+        Utc.timestamp_opt(day * 86400, 0).unwrap()
+    }
+
+    fn fill(day: i64, side: Side, quantity: Decimal, price: Decimal, fee: Decimal) -> Fill {
+        Fill {
+            order_id: day as u64,
+            time: time(day),
+            pair: Pair::new("BTC", "USD"),
+            side,
+            quantity,
+            price,
+            fee,
+            liquidity: Liquidity::Taker,
+        }
+    }
+
+    #[test]
+    fn test_trades() {
+        let fills = [
+            fill(0, Side::Buy, dec!(1), dec!(100), dec!(1)),
+            fill(1, Side::Buy, dec!(1), dec!(110), dec!(1)),
+            fill(2, Side::Sell, dec!(1), dec!(120), dec!(1)),
+            // closes the remaining long and opens a short
+            fill(3, Side::Sell, dec!(2), dec!(100), dec!(2)),
+            fill(4, Side::Buy, dec!(1), dec!(90), dec!(0)),
+        ];
+
+        let trades = trades(&fills);
+        assert_eq!(3, trades.len());
+
+        assert_eq!(Side::Buy, trades[0].side);
+        assert_eq!(dec!(105), trades[0].entry_price);
+        assert_eq!(time(0), trades[0].entry_time);
+        assert_eq!(dec!(13), trades[0].pnl);
+
+        assert_eq!(dec!(1), trades[1].quantity);
+        assert_eq!(dec!(-7), trades[1].pnl);
+
+        assert_eq!(Side::Sell, trades[2].side);
+        assert_eq!(time(3), trades[2].entry_time);
+        assert_eq!(dec!(9), trades[2].pnl);
+
+        assert_eq!(Some(dec!(2) / dec!(3)), win_rate(&trades));
+    }
+
+    #[test]
+    fn test_summary() {
+        let equity = vec![
+            (time(1), dec!(110)),
+            (time(2), dec!(99)),
+            (time(3), dec!(121)),
+            (time(4), dec!(110)),
+        ];
+        let report = Report::new(dec!(100), equity, Vec::new());
+
+        assert_eq!(Some(dec!(0.1)), report.summary.total_return);
+        assert_eq!(dec!(0.1), report.summary.max_drawdown);
+        assert_eq!(None, report.summary.win_rate);
+        assert!(report.summary.sharpe.is_some());
+        assert_eq!(dec!(110), report.final_equity());
+
+        let report = Report::new(dec!(100), Vec::new(), Vec::new());
+        assert_eq!(None, report.summary.total_return);
+        assert_eq!(None, report.summary.sharpe);
+    }
+}
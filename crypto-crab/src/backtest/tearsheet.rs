@@ -0,0 +1,249 @@
+use super::{
+    metrics::Metrics,
+    report::{Report, Trade},
+};
+use crate::{decimal::Decimal, time::DateTime};
+use num_traits::ToPrimitive;
+use plotters::prelude::*;
+use serde::Serialize;
+use std::fmt::{self, Write};
+
+#[derive(Debug)]
+pub enum Error {
+    Json(serde_json::Error),
+    /// The charts couldn't be drawn.
+    Plot(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "failed to serialize the report: {}", e),
+            Self::Plot(e) => write!(f, "failed to plot the report: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl<E: std::error::Error + Send + Sync> From<DrawingAreaErrorKind<E>> for Error {
+    fn from(e: DrawingAreaErrorKind<E>) -> Self {
+        Self::Plot(e.to_string())
+    }
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    metrics: &'a Metrics,
+    equity: &'a [(DateTime, Decimal)],
+    trades: &'a [Trade],
+}
+
+/// The metrics, equity curve and trades of `report` as JSON, with decimals as strings.
+pub fn to_json(report: &Report) -> Result<String, Error> {
+    let metrics = report.metrics();
+    Ok(serde_json::to_string_pretty(&JsonReport {
+        metrics: &metrics,
+        equity: &report.equity,
+        trades: &report.trades,
+    })?)
+}
+
+/// A self-contained HTML page with a table of the metrics of `report` and SVG charts
+/// of its equity curve and drawdown.
+pub fn to_html(report: &Report) -> Result<String, Error> {
+    let metrics = report.metrics();
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Tearsheet</title>\n\
+         <style>body{font-family:sans-serif}td{padding:2px 12px}td+td{text-align:right}</style>\n\
+         </head>\n<body>\n<h1>Tearsheet</h1>\n<table>\n",
+    );
+    for (name, value) in rows(&metrics) {
+        // infallible for strings
+        let _ = writeln!(html, "<tr><td>{}</td><td>{}</td></tr>", name, value);
+    }
+    html.push_str("</table>\n");
+
+    if report.equity.len() > 1 {
+        html.push_str(&equity_chart(report)?);
+        html.push('\n');
+    }
+    html.push_str("</body>\n</html>\n");
+    Ok(html)
+}
+
+fn rows(metrics: &Metrics) -> Vec<(&'static str, String)> {
+    let percent = |value: Option<Decimal>| {
+        value.map_or_else(
+            || "-".to_string(),
+            |v| format!("{}%", (v * Decimal::ONE_HUNDRED).round_dp(2)),
+        )
+    };
+    let ratio = |value: Option<Decimal>| {
+        value.map_or_else(|| "-".to_string(), |v| v.round_dp(2).to_string())
+    };
+    let duration = |secs: Option<i64>| {
+        secs.map_or_else(
+            || "-".to_string(),
+            |secs| format!("{}d {}h", secs / 86400, secs % 86400 / 3600),
+        )
+    };
+    let trades = &metrics.trades;
+
+    vec![
+        (
+            "Start",
+            metrics.start.map_or("-".to_string(), |t| t.to_string()),
+        ),
+        (
+            "End",
+            metrics.end.map_or("-".to_string(), |t| t.to_string()),
+        ),
+        (
+            "Initial equity",
+            metrics.initial_equity.round_dp(2).to_string(),
+        ),
+        ("Final equity", metrics.final_equity.round_dp(2).to_string()),
+        ("Total return", percent(metrics.total_return)),
+        ("CAGR", percent(metrics.cagr)),
+        ("Volatility", percent(metrics.volatility)),
+        ("Sharpe", ratio(metrics.sharpe)),
+        ("Sortino", ratio(metrics.sortino)),
+        ("Calmar", ratio(metrics.calmar)),
+        ("Skewness", ratio(metrics.skewness)),
+        ("Excess kurtosis", ratio(metrics.kurtosis)),
+        ("Max drawdown", percent(Some(metrics.max_drawdown))),
+        (
+            "Max drawdown duration",
+            duration(Some(metrics.max_drawdown_duration_secs)),
+        ),
+        ("Exposure", percent(metrics.exposure)),
+        ("Turnover", ratio(metrics.turnover)),
+        ("Trades", trades.count.to_string()),
+        ("Win rate", percent(trades.win_rate)),
+        ("Profit factor", ratio(trades.profit_factor)),
+        ("Average trade", ratio(trades.average_pnl)),
+        ("Average win", ratio(trades.average_win)),
+        ("Average loss", ratio(trades.average_loss)),
+        ("Largest win", ratio(trades.largest_win)),
+        ("Largest loss", ratio(trades.largest_loss)),
+        ("Average duration", duration(trades.average_duration_secs)),
+    ]
+}
+
+fn equity_chart(report: &Report) -> Result<String, Error> {
+    let equity = report
+        .equity
+        .iter()
+        .map(|(time, equity)| (*time, equity.to_f64().unwrap_or_default()))
+        .collect::<Vec<_>>();
+    let mut peak = report.initial_cash.to_f64().unwrap_or_default();
+    let drawdown = equity
+        .iter()
+        .map(|(time, equity)| {
+            peak = peak.max(*equity);
+            let drawdown = if peak > 0.0 { equity / peak - 1.0 } else { 0.0 };
+            (*time, drawdown * 100.0)
+        })
+        .collect::<Vec<_>>();
+
+    let times = equity[0].0..equity[equity.len() - 1].0;
+    let (low, high) = equity
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(low, high), (_, e)| {
+            (low.min(*e), high.max(*e))
+        });
+    let margin = ((high - low) * 0.05).max(f64::EPSILON);
+    let deepest = drawdown.iter().map(|(_, d)| *d).fold(0.0, f64::min);
+
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, (960, 600)).into_drawing_area();
+        root.fill(&WHITE)?;
+        let (top, bottom) = root.split_vertically(400);
+
+        let mut chart = ChartBuilder::on(&top)
+            .caption("Equity", ("sans-serif", 20))
+            .margin(10)
+            .x_label_area_size(30)
+            .y_label_area_size(80)
+            .build_cartesian_2d(times.clone(), (low - margin)..(high + margin))?;
+        chart.configure_mesh().light_line_style(WHITE).draw()?;
+        chart.draw_series(LineSeries::new(equity, BLUE))?;
+
+        let mut chart = ChartBuilder::on(&bottom)
+            .caption("Drawdown (%)", ("sans-serif", 20))
+            .margin(10)
+            .x_label_area_size(30)
+            .y_label_area_size(80)
+            .build_cartesian_2d(times, (deepest * 1.05 - f64::EPSILON)..0.0)?;
+        chart.configure_mesh().light_line_style(WHITE).draw()?;
+        chart.draw_series(AreaSeries::new(drawdown, 0.0, RED.mix(0.3)).border_style(RED))?;
+
+        root.present()?;
+    }
+    Ok(svg)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algo::Pair;
+    use crate::order::{Fill, Liquidity, Side};
+    use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    fn report() -> Report {
+        let time = |day: i64| Utc.timestamp_opt(day * 86400, 0).unwrap();
+        let fill = |day: i64, side: Side, price: Decimal| Fill {
+            order_id: day as u64,
+            time: time(day),
+            pair: Pair::new("BTC", "USD"),
+            side,
+            quantity: dec!(1),
+            price,
+            fee: dec!(0),
+            liquidity: Liquidity::Taker,
+        };
+        let equity = [dec!(100), dec!(110), dec!(95), dec!(120)]
+            .iter()
+            .enumerate()
+            .map(|(day, equity)| (time(day as i64), *equity))
+            .collect();
+        Report::new(
+            dec!(100),
+            equity,
+            vec![fill(0, Side::Buy, dec!(50)), fill(3, Side::Sell, dec!(70))],
+        )
+    }
+
+    #[test]
+    fn test_json() {
+        let json = to_json(&report()).unwrap();
+        let value = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+
+        assert_eq!("0.20", value["metrics"]["total_return"]);
+        assert_eq!("20", value["metrics"]["trades"]["average_pnl"]);
+        assert_eq!(4, value["equity"].as_array().unwrap().len());
+        assert_eq!("BTC/USD", value["trades"][0]["pair"]);
+        assert_eq!("Buy", value["trades"][0]["side"]);
+    }
+
+    #[test]
+    fn test_html() {
+        let html = to_html(&report()).unwrap();
+        assert!(html.contains("<tr><td>Total return</td><td>20.00%</td></tr>"));
+        assert!(html.contains("<svg"));
+        assert!(html.contains("Drawdown (%)"));
+
+        // nothing to chart without equity samples
+        let empty = to_html(&Report::new(dec!(100), Vec::new(), Vec::new())).unwrap();
+        assert!(!empty.contains("<svg"));
+    }
+}
@@ -1,14 +1,14 @@
-use crypto_crab::{algo::Algorithm, candle::Candle};
+use crypto_crab::{algo::Algorithm, order::Broker};
 use crypto_crab_macros::*;
 
 fn main() {}
 
 #[derive(Algorithm)]
-#[algo(symbol = "ETHUSD", resolution = "minute")]
+#[algo(pair = "ETH/USD", resolution = "minute")]
 pub struct MyAlgo;
 
 impl Algorithm for MyAlgo {
-    fn on(&mut self, data: &Self::Data) {
+    fn on(&mut self, data: &Self::Data, _broker: &mut dyn Broker) {
         data.get_ethusd();
     }
 }
@@ -1,10 +1,38 @@
-use crate::decimal::Decimal;
+use crate::{decimal::Decimal, time::DateTime};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Candle {
+    // The start of the candle's interval.
+    pub time: DateTime,
     pub open: Decimal,
     pub high: Decimal,
     pub low: Decimal,
     pub close: Decimal,
     pub volume: Decimal,
 }
+
+impl From<gemini::common::Candle> for Candle {
+    fn from(candle: gemini::common::Candle) -> Self {
+        Self {
+            time: candle.time,
+            open: candle.open,
+            high: candle.high,
+            low: candle.low,
+            close: candle.close,
+            volume: candle.volume,
+        }
+    }
+}
+
+impl From<exchange_api::Candle> for Candle {
+    fn from(candle: exchange_api::Candle) -> Self {
+        Self {
+            time: candle.time,
+            open: candle.open,
+            high: candle.high,
+            low: candle.low,
+            close: candle.close,
+            volume: candle.volume,
+        }
+    }
+}
@@ -0,0 +1,77 @@
+pub mod moving_average;
+pub mod oscillator;
+pub mod volatility;
+pub mod volume;
+
+use crate::candle::Candle;
+
+/// A technical indicator which is updated incrementally, one candle at a time.
+pub trait Indicator {
+    type Output;
+
+    /// Updates the indicator with the next candle and returns the new value,
+    /// or `None` while the indicator is still warming up.
+    fn update(&mut self, candle: &Candle) -> Option<Self::Output>;
+
+    /// Returns the current value, or `None` while the indicator is still warming up.
+    fn value(&self) -> Option<Self::Output>;
+
+    /// Returns `true` once enough candles have been seen for the indicator to have a value.
+    fn is_ready(&self) -> bool {
+        self.value().is_some()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use crate::{candle::Candle, decimal::Decimal};
+    use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    /// A deterministic candle series shared by the indicator tests.
+    /// The reference values in the tests were computed independently for this series.
+    pub fn candles() -> Vec<Candle> {
+        let closes = [
+            dec!(44.34),
+            dec!(44.09),
+            dec!(44.15),
+            dec!(43.61),
+            dec!(44.33),
+            dec!(44.83),
+            dec!(45.10),
+            dec!(45.42),
+            dec!(45.84),
+            dec!(46.08),
+            dec!(45.89),
+            dec!(46.03),
+            dec!(45.61),
+            dec!(46.28),
+            dec!(46.28),
+            dec!(46.00),
+            dec!(46.03),
+            dec!(46.41),
+            dec!(46.22),
+            dec!(45.64),
+        ];
+
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| {
+                let i = i as i64;
+                Candle {
+                    time: Utc.timestamp_opt(i * 60, 0).unwrap(),
+                    open: *close,
+                    high: close + dec!(0.1) * Decimal::from(i % 3) + dec!(0.1),
+                    low: close - dec!(0.1) * Decimal::from(i % 4) - dec!(0.1),
+                    close: *close,
+                    volume: Decimal::from(100 + 10 * i),
+                }
+            })
+            .collect()
+    }
+
+    pub fn round(value: Option<Decimal>) -> Option<Decimal> {
+        value.map(|v| v.round_dp(10))
+    }
+}
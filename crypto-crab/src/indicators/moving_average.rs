@@ -0,0 +1,196 @@
+use super::Indicator;
+use crate::{
+    candle::Candle,
+    decimal::Decimal,
+    window::{DynamicWindow, Window},
+};
+use std::num::NonZeroUsize;
+
+/// Simple moving average of the close price.
+pub struct Sma {
+    window: DynamicWindow<Decimal>,
+    sum: Decimal,
+    count: usize,
+}
+
+impl Sma {
+    pub fn new(period: NonZeroUsize) -> Self {
+        Self {
+            window: DynamicWindow::new(|_| Decimal::ZERO, period),
+            sum: Decimal::ZERO,
+            count: 0,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.window.period()
+    }
+
+    /// Updates the average with an arbitrary value instead of a candle's close.
+    pub fn push(&mut self, value: Decimal) -> Option<Decimal> {
+        let (_, old) = self.window.push(value);
+        self.sum += value - old;
+        self.count = (self.count + 1).min(self.period());
+        self.current()
+    }
+
+    fn current(&self) -> Option<Decimal> {
+        (self.count == self.period()).then(|| self.sum / Decimal::from(self.period()))
+    }
+}
+
+impl Indicator for Sma {
+    type Output = Decimal;
+
+    fn update(&mut self, candle: &Candle) -> Option<Decimal> {
+        self.push(candle.close)
+    }
+
+    fn value(&self) -> Option<Decimal> {
+        self.current()
+    }
+}
+
+/// Exponential moving average of the close price, seeded with the simple
+/// moving average of the first `period` values.
+pub struct Ema {
+    alpha: Decimal,
+    seed: Sma,
+    ema: Option<Decimal>,
+}
+
+impl Ema {
+    pub fn new(period: NonZeroUsize) -> Self {
+        Self {
+            alpha: Decimal::TWO / Decimal::from(period.get() + 1),
+            seed: Sma::new(period),
+            ema: None,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.seed.period()
+    }
+
+    /// Updates the average with an arbitrary value instead of a candle's close.
+    pub fn push(&mut self, value: Decimal) -> Option<Decimal> {
+        self.ema = match self.ema {
+            Some(ema) => Some(ema + self.alpha * (value - ema)),
+            None => self.seed.push(value),
+        };
+        self.ema
+    }
+}
+
+impl Indicator for Ema {
+    type Output = Decimal;
+
+    fn update(&mut self, candle: &Candle) -> Option<Decimal> {
+        self.push(candle.close)
+    }
+
+    fn value(&self) -> Option<Decimal> {
+        self.ema
+    }
+}
+
+/// Linearly weighted moving average of the close price, where the newest value
+/// has a weight of `period` and the oldest a weight of one.
+pub struct Wma {
+    window: DynamicWindow<Decimal>,
+    sum: Decimal,
+    numerator: Decimal,
+    count: usize,
+}
+
+impl Wma {
+    pub fn new(period: NonZeroUsize) -> Self {
+        Self {
+            window: DynamicWindow::new(|_| Decimal::ZERO, period),
+            sum: Decimal::ZERO,
+            numerator: Decimal::ZERO,
+            count: 0,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.window.period()
+    }
+
+    /// Updates the average with an arbitrary value instead of a candle's close.
+    pub fn push(&mut self, value: Decimal) -> Option<Decimal> {
+        let period = Decimal::from(self.period());
+        let (_, old) = self.window.push(value);
+
+        // shifting the window lowers the weight of every previous value by one
+        self.numerator += period * value - self.sum;
+        self.sum += value - old;
+        self.count = (self.count + 1).min(self.period());
+        self.current()
+    }
+
+    fn current(&self) -> Option<Decimal> {
+        let period = self.period();
+        (self.count == period).then(|| self.numerator / Decimal::from(period * (period + 1) / 2))
+    }
+}
+
+impl Indicator for Wma {
+    type Output = Decimal;
+
+    fn update(&mut self, candle: &Candle) -> Option<Decimal> {
+        self.push(candle.close)
+    }
+
+    fn value(&self) -> Option<Decimal> {
+        self.current()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::indicators::test::{candles, round};
+    use rust_decimal_macros::dec;
+
+    fn period(n: usize) -> NonZeroUsize {
+        NonZeroUsize::new(n).unwrap()
+    }
+
+    fn test_indicator(
+        mut indicator: impl Indicator<Output = Decimal>,
+        first: Decimal,
+        last: Decimal,
+    ) {
+        let candles = candles();
+        for candle in &candles[..4] {
+            assert!(indicator.update(candle).is_none());
+            assert!(!indicator.is_ready());
+        }
+
+        assert_eq!(Some(first), round(indicator.update(&candles[4])));
+        for candle in &candles[5..] {
+            indicator.update(candle);
+        }
+        assert_eq!(Some(last), round(indicator.value()));
+    }
+
+    #[test]
+    fn test_sma() {
+        test_indicator(Sma::new(period(5)), dec!(44.104), dec!(46.06));
+    }
+
+    #[test]
+    fn test_ema() {
+        test_indicator(Ema::new(period(5)), dec!(44.104), dec!(45.9960536194));
+    }
+
+    #[test]
+    fn test_wma() {
+        test_indicator(
+            Wma::new(period(5)),
+            dec!(44.0706666667),
+            dec!(46.0246666667),
+        );
+    }
+}
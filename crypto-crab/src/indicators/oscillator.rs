@@ -0,0 +1,319 @@
+use super::{
+    moving_average::{Ema, Sma},
+    Indicator,
+};
+use crate::{candle::Candle, decimal::Decimal};
+use std::{collections::VecDeque, num::NonZeroUsize};
+
+/// Wilder's relative strength index of the close price.
+pub struct Rsi {
+    period: usize,
+    prev_close: Option<Decimal>,
+    changes: usize,
+    avg_gain: Decimal,
+    avg_loss: Decimal,
+}
+
+impl Rsi {
+    pub fn new(period: NonZeroUsize) -> Self {
+        Self {
+            period: period.get(),
+            prev_close: None,
+            changes: 0,
+            avg_gain: Decimal::ZERO,
+            avg_loss: Decimal::ZERO,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Updates the index with an arbitrary value instead of a candle's close.
+    pub fn push(&mut self, value: Decimal) -> Option<Decimal> {
+        if let Some(prev) = self.prev_close.replace(value) {
+            let change = value - prev;
+            let gain = change.max(Decimal::ZERO);
+            let loss = (-change).max(Decimal::ZERO);
+            let period = Decimal::from(self.period);
+
+            self.changes += 1;
+            if self.changes <= self.period {
+                // the first averages are the simple mean of the first `period` changes
+                self.avg_gain += gain;
+                self.avg_loss += loss;
+                if self.changes == self.period {
+                    self.avg_gain /= period;
+                    self.avg_loss /= period;
+                }
+            } else {
+                self.avg_gain = (self.avg_gain * (period - Decimal::ONE) + gain) / period;
+                self.avg_loss = (self.avg_loss * (period - Decimal::ONE) + loss) / period;
+            }
+        }
+        self.current()
+    }
+
+    fn current(&self) -> Option<Decimal> {
+        if self.changes < self.period {
+            return None;
+        }
+
+        let total = self.avg_gain + self.avg_loss;
+        if total.is_zero() {
+            Some(Decimal::ONE_HUNDRED / Decimal::TWO)
+        } else {
+            Some(Decimal::ONE_HUNDRED * self.avg_gain / total)
+        }
+    }
+}
+
+impl Indicator for Rsi {
+    type Output = Decimal;
+
+    fn update(&mut self, candle: &Candle) -> Option<Decimal> {
+        self.push(candle.close)
+    }
+
+    fn value(&self) -> Option<Decimal> {
+        self.current()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MacdValue {
+    pub macd: Decimal,
+    pub signal: Decimal,
+    pub histogram: Decimal,
+}
+
+/// Moving average convergence/divergence of the close price.
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+    value: Option<MacdValue>,
+}
+
+impl Macd {
+    pub fn new(fast: NonZeroUsize, slow: NonZeroUsize, signal: NonZeroUsize) -> Self {
+        Self {
+            fast: Ema::new(fast),
+            slow: Ema::new(slow),
+            signal: Ema::new(signal),
+            value: None,
+        }
+    }
+
+    /// Updates the indicator with an arbitrary value instead of a candle's close.
+    pub fn push(&mut self, value: Decimal) -> Option<MacdValue> {
+        let (fast, slow) = (self.fast.push(value), self.slow.push(value));
+        if let (Some(fast), Some(slow)) = (fast, slow) {
+            let macd = fast - slow;
+            if let Some(signal) = self.signal.push(macd) {
+                self.value = Some(MacdValue {
+                    macd,
+                    signal,
+                    histogram: macd - signal,
+                });
+            }
+        }
+        self.value
+    }
+}
+
+impl Default for Macd {
+    /// The conventional 12/26/9 period MACD.
+    fn default() -> Self {
+        let period = |n| NonZeroUsize::new(n).unwrap();
+        Self::new(period(12), period(26), period(9))
+    }
+}
+
+impl Indicator for Macd {
+    type Output = MacdValue;
+
+    fn update(&mut self, candle: &Candle) -> Option<MacdValue> {
+        self.push(candle.close)
+    }
+
+    fn value(&self) -> Option<MacdValue> {
+        self.value
+    }
+}
+
+/// The extreme value of the most recent `period` values, maintained with
+/// a monotonic queue so each update is amortized constant time.
+struct Extremum {
+    period: u64,
+    values: VecDeque<(u64, Decimal)>,
+    // Whether this tracks the maximum (or the minimum) value.
+    max: bool,
+}
+
+impl Extremum {
+    fn new(period: usize, max: bool) -> Self {
+        Self {
+            period: period as u64,
+            values: VecDeque::with_capacity(period),
+            max,
+        }
+    }
+
+    fn push(&mut self, index: u64, value: Decimal) -> Decimal {
+        let max = self.max;
+        while matches!(self.values.back(), Some((_, v)) if (max && *v <= value) || (!max && *v >= value))
+        {
+            self.values.pop_back();
+        }
+        self.values.push_back((index, value));
+
+        while matches!(self.values.front(), Some((i, _)) if i + self.period <= index) {
+            self.values.pop_front();
+        }
+
+        // the queue always contains at least the value just pushed
+        self.values.front().map(|(_, v)| *v).unwrap_or(value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StochasticValue {
+    pub k: Decimal,
+    pub d: Decimal,
+}
+
+/// The stochastic oscillator. `%K` locates the close within the high-low range of the
+/// last `k_period` candles and `%D` is the simple moving average of `%K`.
+///
+/// `%K` is 50 when the range is empty.
+pub struct Stochastic {
+    k_period: usize,
+    highs: Extremum,
+    lows: Extremum,
+    count: u64,
+    d: Sma,
+    value: Option<StochasticValue>,
+}
+
+impl Stochastic {
+    pub fn new(k_period: NonZeroUsize, d_period: NonZeroUsize) -> Self {
+        Self {
+            k_period: k_period.get(),
+            highs: Extremum::new(k_period.get(), true),
+            lows: Extremum::new(k_period.get(), false),
+            count: 0,
+            d: Sma::new(d_period),
+            value: None,
+        }
+    }
+}
+
+impl Indicator for Stochastic {
+    type Output = StochasticValue;
+
+    fn update(&mut self, candle: &Candle) -> Option<StochasticValue> {
+        let highest = self.highs.push(self.count, candle.high);
+        let lowest = self.lows.push(self.count, candle.low);
+        self.count += 1;
+
+        if self.count >= self.k_period as u64 {
+            let range = highest - lowest;
+            let k = if range.is_zero() {
+                Decimal::ONE_HUNDRED / Decimal::TWO
+            } else {
+                Decimal::ONE_HUNDRED * (candle.close - lowest) / range
+            };
+
+            if let Some(d) = self.d.push(k) {
+                self.value = Some(StochasticValue { k, d });
+            }
+        }
+        self.value
+    }
+
+    fn value(&self) -> Option<StochasticValue> {
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::indicators::test::{candles, round};
+    use rust_decimal_macros::dec;
+
+    fn period(n: usize) -> NonZeroUsize {
+        NonZeroUsize::new(n).unwrap()
+    }
+
+    #[test]
+    fn test_rsi() {
+        let candles = candles();
+        let mut rsi = Rsi::new(period(14));
+
+        for candle in &candles[..14] {
+            assert!(rsi.update(candle).is_none());
+        }
+        assert_eq!(Some(dec!(70.4641350211)), round(rsi.update(&candles[14])));
+
+        for candle in &candles[15..] {
+            rsi.update(candle);
+        }
+        assert_eq!(Some(dec!(57.9150206701)), round(rsi.value()));
+    }
+
+    #[test]
+    fn test_rsi_flat() {
+        let mut rsi = Rsi::new(period(2));
+        for _ in 0..3 {
+            rsi.push(dec!(1));
+        }
+        assert_eq!(Some(dec!(50)), rsi.value());
+    }
+
+    #[test]
+    fn test_macd() {
+        let candles = candles();
+        let mut macd = Macd::new(period(3), period(6), period(4));
+
+        for candle in &candles[..8] {
+            assert!(macd.update(candle).is_none());
+        }
+
+        let value = macd.update(&candles[8]).unwrap();
+        assert_eq!(dec!(0.4137574405), value.macd.round_dp(10));
+        assert_eq!(dec!(0.3328404018), value.signal.round_dp(10));
+
+        for candle in &candles[9..] {
+            macd.update(candle);
+        }
+
+        let value = macd.value().unwrap();
+        assert_eq!(dec!(-0.0635485212), value.macd.round_dp(10));
+        assert_eq!(dec!(0.0417042780), value.signal.round_dp(10));
+        assert_eq!(dec!(-0.1052527992), value.histogram.round_dp(10));
+    }
+
+    #[test]
+    fn test_stochastic() {
+        let candles = candles();
+        let mut stochastic = Stochastic::new(period(5), period(3));
+
+        for candle in &candles[..6] {
+            assert!(stochastic.update(candle).is_none());
+        }
+
+        let value = stochastic.update(&candles[6]).unwrap();
+        assert_eq!(dec!(94.9748743719), value.k.round_dp(10));
+        assert_eq!(dec!(88.0661197401), value.d.round_dp(10));
+
+        for candle in &candles[7..] {
+            stochastic.update(candle);
+        }
+
+        let value = stochastic.value().unwrap();
+        assert_eq!(dec!(27.2108843537), value.k.round_dp(10));
+        assert_eq!(dec!(52.0132377275), value.d.round_dp(10));
+    }
+}
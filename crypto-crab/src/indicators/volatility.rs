@@ -0,0 +1,189 @@
+use super::Indicator;
+use crate::{
+    candle::Candle,
+    decimal::{Decimal, MathematicalOps},
+    window::{DynamicWindow, Window},
+};
+use std::num::NonZeroUsize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BollingerValue {
+    pub upper: Decimal,
+    pub middle: Decimal,
+    pub lower: Decimal,
+}
+
+/// Bollinger bands of the close price: the simple moving average plus and minus
+/// `width` population standard deviations.
+pub struct Bollinger {
+    window: DynamicWindow<Decimal>,
+    width: Decimal,
+    sum: Decimal,
+    sum_squares: Decimal,
+    count: usize,
+}
+
+impl Bollinger {
+    pub fn new(period: NonZeroUsize, width: Decimal) -> Self {
+        Self {
+            window: DynamicWindow::new(|_| Decimal::ZERO, period),
+            width,
+            sum: Decimal::ZERO,
+            sum_squares: Decimal::ZERO,
+            count: 0,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.window.period()
+    }
+
+    /// Updates the bands with an arbitrary value instead of a candle's close.
+    pub fn push(&mut self, value: Decimal) -> Option<BollingerValue> {
+        let (_, old) = self.window.push(value);
+        self.sum += value - old;
+        self.sum_squares += value * value - old * old;
+        self.count = (self.count + 1).min(self.period());
+        self.current()
+    }
+
+    fn current(&self) -> Option<BollingerValue> {
+        if self.count < self.period() {
+            return None;
+        }
+
+        let period = Decimal::from(self.period());
+        let mean = self.sum / period;
+        let variance = (self.sum_squares / period - mean * mean).max(Decimal::ZERO);
+        let offset = self.width * variance.sqrt()?;
+
+        Some(BollingerValue {
+            upper: mean + offset,
+            middle: mean,
+            lower: mean - offset,
+        })
+    }
+}
+
+impl Indicator for Bollinger {
+    type Output = BollingerValue;
+
+    fn update(&mut self, candle: &Candle) -> Option<BollingerValue> {
+        self.push(candle.close)
+    }
+
+    fn value(&self) -> Option<BollingerValue> {
+        self.current()
+    }
+}
+
+/// Wilder's average true range.
+pub struct Atr {
+    period: usize,
+    prev_close: Option<Decimal>,
+    count: usize,
+    atr: Decimal,
+}
+
+impl Atr {
+    pub fn new(period: NonZeroUsize) -> Self {
+        Self {
+            period: period.get(),
+            prev_close: None,
+            count: 0,
+            atr: Decimal::ZERO,
+        }
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+}
+
+/// The greatest of the candle's range and the distances from the previous close
+/// to its high and low.
+pub fn true_range(candle: &Candle, prev_close: Option<Decimal>) -> Decimal {
+    let range = candle.high - candle.low;
+    match prev_close {
+        Some(prev) => range
+            .max((candle.high - prev).abs())
+            .max((candle.low - prev).abs()),
+        None => range,
+    }
+}
+
+impl Indicator for Atr {
+    type Output = Decimal;
+
+    fn update(&mut self, candle: &Candle) -> Option<Decimal> {
+        let tr = true_range(candle, self.prev_close.replace(candle.close));
+        let period = Decimal::from(self.period);
+
+        self.count += 1;
+        if self.count <= self.period {
+            // the first average is the simple mean of the first `period` true ranges
+            self.atr += tr;
+            if self.count == self.period {
+                self.atr /= period;
+            }
+        } else {
+            self.atr = (self.atr * (period - Decimal::ONE) + tr) / period;
+        }
+        self.value()
+    }
+
+    fn value(&self) -> Option<Decimal> {
+        (self.count >= self.period).then_some(self.atr)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::indicators::test::{candles, round};
+    use rust_decimal_macros::dec;
+
+    fn period(n: usize) -> NonZeroUsize {
+        NonZeroUsize::new(n).unwrap()
+    }
+
+    #[test]
+    fn test_bollinger() {
+        let candles = candles();
+        let mut bollinger = Bollinger::new(period(5), dec!(2));
+
+        for candle in &candles[..4] {
+            assert!(bollinger.update(candle).is_none());
+        }
+
+        let value = bollinger.update(&candles[4]).unwrap();
+        assert_eq!(dec!(44.6355035277), value.upper.round_dp(10));
+        assert_eq!(dec!(44.104), value.middle.round_dp(10));
+        assert_eq!(dec!(43.5724964723), value.lower.round_dp(10));
+
+        for candle in &candles[5..] {
+            bollinger.update(candle);
+        }
+
+        let value = bollinger.value().unwrap();
+        assert_eq!(dec!(46.5730302135), value.upper.round_dp(10));
+        assert_eq!(dec!(46.06), value.middle.round_dp(10));
+        assert_eq!(dec!(45.5469697865), value.lower.round_dp(10));
+    }
+
+    #[test]
+    fn test_atr() {
+        let candles = candles();
+        let mut atr = Atr::new(period(5));
+
+        for candle in &candles[..4] {
+            assert!(atr.update(candle).is_none());
+        }
+        assert_eq!(Some(dec!(0.622)), round(atr.update(&candles[4])));
+
+        for candle in &candles[5..] {
+            atr.update(candle);
+        }
+        assert_eq!(Some(dec!(0.6527573998)), round(atr.value()));
+    }
+}
@@ -0,0 +1,126 @@
+use super::Indicator;
+use crate::{candle::Candle, decimal::Decimal};
+
+/// On-balance volume: the running total of volume, added on up closes and
+/// subtracted on down closes.
+#[derive(Default)]
+pub struct Obv {
+    prev_close: Option<Decimal>,
+    obv: Decimal,
+}
+
+impl Obv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Indicator for Obv {
+    type Output = Decimal;
+
+    fn update(&mut self, candle: &Candle) -> Option<Decimal> {
+        if let Some(prev) = self.prev_close.replace(candle.close) {
+            match candle.close.cmp(&prev) {
+                std::cmp::Ordering::Greater => self.obv += candle.volume,
+                std::cmp::Ordering::Less => self.obv -= candle.volume,
+                std::cmp::Ordering::Equal => {}
+            }
+        }
+        self.value()
+    }
+
+    fn value(&self) -> Option<Decimal> {
+        self.prev_close.map(|_| self.obv)
+    }
+}
+
+/// Volume weighted average of the typical price `(high + low + close) / 3`
+/// since the last call to `reset`, e.g. the start of the session.
+#[derive(Default)]
+pub struct Vwap {
+    price_volume: Decimal,
+    volume: Decimal,
+}
+
+impl Vwap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Replaces a candle passed to `update` before with its revision, e.g. after a
+    /// broken trade was retracted from it.
+    pub fn revise(&mut self, previous: &Candle, revised: &Candle) -> Option<Decimal> {
+        let typical =
+            |candle: &Candle| (candle.high + candle.low + candle.close) / Decimal::from(3);
+        self.price_volume +=
+            typical(revised) * revised.volume - typical(previous) * previous.volume;
+        self.volume += revised.volume - previous.volume;
+        self.value()
+    }
+}
+
+impl Indicator for Vwap {
+    type Output = Decimal;
+
+    fn update(&mut self, candle: &Candle) -> Option<Decimal> {
+        let typical = (candle.high + candle.low + candle.close) / Decimal::from(3);
+        self.price_volume += typical * candle.volume;
+        self.volume += candle.volume;
+        self.value()
+    }
+
+    fn value(&self) -> Option<Decimal> {
+        (!self.volume.is_zero()).then(|| self.price_volume / self.volume)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::indicators::test::{candles, round};
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_obv() {
+        let mut obv = Obv::new();
+        assert!(!obv.is_ready());
+
+        for candle in &candles() {
+            obv.update(candle);
+        }
+        assert_eq!(Some(dec!(600)), obv.value());
+    }
+
+    #[test]
+    fn test_vwap() {
+        let mut vwap = Vwap::new();
+        assert!(!vwap.is_ready());
+
+        for candle in &candles() {
+            vwap.update(candle);
+        }
+        assert_eq!(Some(dec!(45.6032905983)), round(vwap.value()));
+
+        // a candle whose only trade was broken
+        let broken = Candle {
+            volume: dec!(1000),
+            ..candles()[0].clone()
+        };
+        vwap.update(&broken);
+        let revised = Candle {
+            volume: Decimal::ZERO,
+            ..broken.clone()
+        };
+        assert_eq!(
+            Some(dec!(45.6032905983)),
+            round(vwap.revise(&broken, &revised))
+        );
+
+        vwap.reset();
+        assert!(vwap.value().is_none());
+    }
+}
@@ -1,7 +1,17 @@
+pub mod aggregate;
 pub mod algo;
+pub mod backtest;
 pub mod candle;
 pub mod decimal;
+pub mod indicators;
+pub mod live;
+pub mod order;
 pub mod period;
+pub mod portfolio;
+pub mod resample;
+pub mod risk;
+pub mod store;
+pub mod tick;
 pub mod time;
 pub mod util;
 pub mod window;
@@ -0,0 +1,579 @@
+use crate::decimal::Decimal;
+use chrono::{DateTime, Duration, Utc};
+use gemini::{
+    common::{AuctionEvent, AuctionOutcome, AuctionPricing, AuctionSchedule},
+    rest::{
+        auction::{Auction, AuctionHttpRequest},
+        auction_history::{AuctionHistory, AuctionHistoryHttpRequest},
+        client::{Client, Error},
+        order::{CancelOrderRequest, NewOrderRequest, OrderOption, OrderStatus, Side},
+    },
+    symbol::Symbol,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// An auction-only limit order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuctionOrder {
+    pub side: Side,
+    pub amount: Decimal,
+    pub price: Decimal,
+}
+
+#[derive(Debug)]
+pub enum AuctionUpdate {
+    /// The auction is open for orders.
+    Opened {
+        auction_time: DateTime<Utc>,
+    },
+    /// A new indicative price or quantity was published.
+    Indicative(AuctionPricing),
+    /// The indicative price is further than 5% from the collar price, which the
+    /// auction can't run at.
+    OutsideCollar {
+        pricing: AuctionPricing,
+        deviation: Decimal,
+    },
+    /// The last time to place the orders passed before they could be placed, e.g. as
+    /// the auction was followed too late.
+    Missed {
+        auction_time: DateTime<Utc>,
+    },
+    Submitted(OrderStatus),
+    Cancelled(OrderStatus),
+    /// A request failed. Failed polls of the auction are retried.
+    Failed(Error),
+    /// The auction ran, with the result if it could be fetched.
+    Ran(Option<AuctionPricing>),
+}
+
+#[derive(Debug, Clone)]
+enum Action {
+    Submit(NewOrderRequest),
+    Cancel(u64),
+    // of the auction at the time
+    FetchResult(DateTime<Utc>),
+}
+
+type CancelCondition = Box<dyn Fn(&AuctionPricing) -> bool + Send>;
+
+// Gemini stops accepting cancellations of auction-only orders 10 seconds before the
+// auction runs.
+const CANCEL_LEAD_SECS: i64 = 10;
+
+// The longest time between the auction time and the time of its result.
+const RESULT_WINDOW_SECS: i64 = 60;
+
+// The number of results the result of the followed auction is looked up among.
+const RECENT_RESULTS: u32 = 3;
+
+// The longest time to wait before retrying a failed poll of the auction.
+const RETRY_SECS: u64 = 1;
+
+/// Takes part in the next auction of a symbol, following it through Gemini's REST API.
+///
+/// The auction is polled whenever its next event is due, i.e. at `next_update_ms`,
+/// `closed_until_ms` and `next_auction_ms`. The orders are placed as auction-only
+/// orders once the auction opens and, if a cancel condition is given, cancelled on the
+/// first indicative meeting it before the last cancel time.
+pub struct AuctionScheduler {
+    symbol: Symbol,
+    orders: Vec<AuctionOrder>,
+    cancel_when: Option<CancelCondition>,
+    schedule: Option<AuctionSchedule>,
+    margin: Duration,
+    poll_interval: std::time::Duration,
+    // The auction being followed, the next one when the first snapshot arrives.
+    auction_time: Option<DateTime<Utc>>,
+    opened: bool,
+    submitted: bool,
+    cancelled: bool,
+    placed: Vec<u64>,
+    indicative: Option<AuctionPricing>,
+    done: bool,
+    updates: Vec<AuctionUpdate>,
+}
+
+impl AuctionScheduler {
+    pub fn new(symbol: Symbol) -> Self {
+        Self {
+            symbol,
+            orders: Vec::new(),
+            cancel_when: None,
+            schedule: None,
+            margin: Duration::seconds(5),
+            poll_interval: std::time::Duration::from_secs(60),
+            auction_time: None,
+            opened: false,
+            submitted: false,
+            cancelled: false,
+            placed: Vec::new(),
+            indicative: None,
+            done: false,
+            updates: Vec::new(),
+        }
+    }
+
+    pub fn with_order(mut self, order: AuctionOrder) -> Self {
+        self.orders.push(order);
+        self
+    }
+
+    /// Cancels the orders on the first indicative `cancel_when` returns true for, e.g.
+    /// `|pricing| !pricing.within_collar()`.
+    pub fn with_cancel_when<F>(mut self, cancel_when: F) -> Self
+    where
+        F: Fn(&AuctionPricing) -> bool + Send + 'static,
+    {
+        self.cancel_when = Some(Box::new(cancel_when));
+        self
+    }
+
+    /// The schedule of the auction from its opening event on the market data feed,
+    /// the last cancel time is assumed to be 10 seconds before the auction otherwise.
+    pub fn with_schedule(mut self, schedule: AuctionSchedule) -> Self {
+        self.schedule = Some(schedule);
+        self.auction_time = Some(schedule.auction_time);
+        self
+    }
+
+    /// How long before the last cancel time orders are placed or cancelled at the
+    /// latest, leaving time for the requests. Defaults to 5 seconds.
+    pub fn with_margin(mut self, margin: Duration) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// The longest time between two polls of the auction. Defaults to a minute.
+    pub fn with_poll_interval(mut self, poll_interval: std::time::Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// The time of the auction being followed, once known.
+    pub fn auction_time(&self) -> Option<DateTime<Utc>> {
+        self.auction_time
+    }
+
+    /// The ids of the orders placed in the auction.
+    pub fn placed(&self) -> &[u64] {
+        &self.placed
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn last_cancel_time(&self, auction_time: DateTime<Utc>) -> DateTime<Utc> {
+        match self.schedule {
+            Some(schedule) if schedule.auction_time == auction_time => schedule.last_cancel_time,
+            _ => auction_time - Duration::seconds(CANCEL_LEAD_SECS),
+        }
+    }
+
+    fn indicative(auction: &Auction, now: DateTime<Utc>) -> Option<AuctionPricing> {
+        let price = auction.most_recent_indicative_price?;
+        let quantity = auction.most_recent_indicative_quantity.unwrap_or_default();
+        Some(AuctionPricing {
+            eid: auction.last_auction_eid,
+            time: now,
+            highest_bid_price: auction.most_recent_highest_bid_price,
+            lowest_ask_price: auction.most_recent_lowest_ask_price,
+            collar_price: auction.most_recent_collar_price.unwrap_or_default(),
+            outcome: if price.is_zero() {
+                AuctionOutcome::Failure
+            } else {
+                AuctionOutcome::Success { price, quantity }
+            },
+        })
+    }
+
+    // Applies a snapshot of the auction taken at `now`, returning the requests to make.
+    fn step(&mut self, auction: &Auction, now: DateTime<Utc>) -> Vec<Action> {
+        if self.done {
+            return Vec::new();
+        }
+        let auction_time = match self.auction_time {
+            Some(auction_time) if auction.next_auction_ms > auction_time => {
+                self.done = true;
+                return vec![Action::FetchResult(auction_time)];
+            }
+            Some(auction_time) => auction_time,
+            None => *self.auction_time.insert(auction.next_auction_ms),
+        };
+        let deadline = self.last_cancel_time(auction_time) - self.margin;
+
+        let mut actions = Vec::new();
+        let open = !matches!(auction.closed_until_ms, Some(until) if until > now);
+        if open && !self.opened {
+            self.opened = true;
+            self.updates.push(AuctionUpdate::Opened { auction_time });
+        }
+        if !self.submitted && !self.orders.is_empty() && now >= deadline {
+            self.submitted = true;
+            self.updates.push(AuctionUpdate::Missed { auction_time });
+        }
+        if open && !self.submitted && now < deadline {
+            self.submitted = true;
+            actions.extend(self.orders.iter().map(|order| {
+                Action::Submit(
+                    NewOrderRequest::builder()
+                        .symbol(self.symbol)
+                        .side(order.side)
+                        .amount(order.amount)
+                        .price(order.price)
+                        .options(vec![OrderOption::AuctionOnly])
+                        .build(),
+                )
+            }));
+        }
+
+        let pricing = match (Self::indicative(auction, now), self.indicative) {
+            (None, _) => return actions,
+            (Some(pricing), Some(last))
+                if pricing.eid == last.eid && pricing.outcome == last.outcome =>
+            {
+                return actions
+            }
+            (Some(pricing), _) => pricing,
+        };
+        self.indicative = Some(pricing);
+        self.updates.push(AuctionUpdate::Indicative(pricing));
+        if let Some(deviation) = pricing.collar_deviation() {
+            if deviation.abs() > AuctionPricing::collar() {
+                self.updates
+                    .push(AuctionUpdate::OutsideCollar { pricing, deviation });
+            }
+        }
+
+        let cancel = matches!(&self.cancel_when, Some(f) if f(&pricing));
+        if cancel && !self.cancelled && now < deadline {
+            self.cancelled = true;
+            actions.extend(self.placed.iter().map(|id| Action::Cancel(*id)));
+        }
+        actions
+    }
+
+    fn on_submitted(&mut self, result: Result<OrderStatus, Error>) -> Option<Action> {
+        match result {
+            Ok(status) => {
+                let id = status.order_id;
+                self.placed.push(id);
+                self.updates.push(AuctionUpdate::Submitted(status));
+                // cancelled while the order was being placed
+                self.cancelled.then_some(Action::Cancel(id))
+            }
+            Err(e) => {
+                self.updates.push(AuctionUpdate::Failed(e));
+                None
+            }
+        }
+    }
+
+    // Reports the result of the auction at `auction_time` among the events of `history`.
+    fn on_history(&mut self, auction_time: DateTime<Utc>, history: Result<AuctionHistory, Error>) {
+        let update = match history {
+            Ok(history) => AuctionUpdate::Ran(history.auctions.into_iter().find_map(|auction| {
+                match AuctionEvent::from(auction) {
+                    AuctionEvent::Result(pricing)
+                        if (pricing.time - auction_time).num_seconds().abs()
+                            < RESULT_WINDOW_SECS =>
+                    {
+                        Some(pricing)
+                    }
+                    _ => None,
+                }
+            })),
+            Err(e) => AuctionUpdate::Failed(e),
+        };
+        self.updates.push(update);
+    }
+
+    fn on_cancelled(&mut self, result: Result<OrderStatus, Error>) {
+        self.updates.push(match result {
+            Ok(status) => AuctionUpdate::Cancelled(status),
+            Err(e) => AuctionUpdate::Failed(e),
+        });
+    }
+
+    // How long to wait for the next poll after the snapshot taken at `now`.
+    fn next_poll(&self, auction: &Auction, now: DateTime<Utc>) -> std::time::Duration {
+        let deadline = self
+            .auction_time
+            .map(|auction_time| self.last_cancel_time(auction_time) - self.margin);
+        [
+            auction.closed_until_ms,
+            auction.next_update_ms,
+            deadline,
+            // the auction has run once the next one is scheduled
+            self.auction_time
+                .map(|auction_time| auction_time + Duration::seconds(1)),
+        ]
+        .iter()
+        .flatten()
+        .filter(|time| **time > now)
+        .min()
+        .and_then(|time| (*time - now).to_std().ok())
+        .map_or(self.poll_interval, |wait| wait.min(self.poll_interval))
+    }
+
+    /// Follows the auction until it has run, sending its updates to `updates`.
+    /// `client` must have credentials if there are orders to place.
+    ///
+    /// Failed polls of the auction are reported and retried, as placed orders can only
+    /// be cancelled while the auction is followed.
+    pub async fn run(&mut self, client: &Client, updates: &UnboundedSender<AuctionUpdate>) {
+        loop {
+            let req = AuctionHttpRequest::builder().symbol(self.symbol).build();
+            let auction = match client.request(req).await {
+                Ok(auction) => auction,
+                Err(e) => {
+                    let _ = updates.send(AuctionUpdate::Failed(e));
+                    let retry = std::time::Duration::from_secs(RETRY_SECS);
+                    tokio::time::sleep(retry.min(self.poll_interval)).await;
+                    continue;
+                }
+            };
+            let now = Utc::now();
+
+            let mut actions = self.step(&auction, now);
+            while let Some(action) = actions.pop() {
+                match action {
+                    Action::Submit(req) => {
+                        let result = client.private_request(req).await;
+                        actions.extend(self.on_submitted(result));
+                    }
+                    Action::Cancel(order_id) => {
+                        let req = CancelOrderRequest::builder().order_id(order_id).build();
+                        let result = client.private_request(req).await;
+                        self.on_cancelled(result);
+                    }
+                    Action::FetchResult(auction_time) => {
+                        // the auction just ran, so it's among the latest results
+                        let req = AuctionHistoryHttpRequest::builder()
+                            .symbol(self.symbol)
+                            .limit_auction_results(RECENT_RESULTS)
+                            .include_indicative(false)
+                            .build();
+                        let history = client.request(req).await;
+                        self.on_history(auction_time, history);
+                    }
+                }
+            }
+            for update in self.updates.drain(..) {
+                let _ = updates.send(update);
+            }
+
+            if self.done {
+                return;
+            }
+            tokio::time::sleep(self.next_poll(&auction, now)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    const AUCTION_MS: i64 = 1_000_000_000;
+
+    fn at(secs_before_auction: i64) -> DateTime<Utc> {
+        Utc.timestamp_millis_opt(AUCTION_MS - secs_before_auction * 1000)
+            .unwrap()
+    }
+
+    fn snapshot(fields: &str, next_auction_ms: i64) -> Auction {
+        let json = format!(r#"{{{}"next_auction_ms":{}}}"#, fields, next_auction_ms);
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn indicative(eid: u64, price: &str) -> String {
+        format!(
+            r#""last_auction_eid":{},
+            "most_recent_indicative_price":"{}",
+            "most_recent_indicative_quantity":"2",
+            "most_recent_collar_price":"100","#,
+            eid, price
+        )
+    }
+
+    fn status(order_id: u64) -> OrderStatus {
+        let json = format!(
+            r#"{{
+                "order_id": "{}",
+                "symbol": "btcusd",
+                "side": "buy",
+                "type": "exchange limit",
+                "timestampms": 0,
+                "is_live": true,
+                "is_cancelled": false,
+                "price": "99",
+                "executed_amount": "0",
+                "remaining_amount": "1",
+                "original_amount": "1",
+                "avg_execution_price": "0"
+            }}"#,
+            order_id
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn buyer() -> AuctionScheduler {
+        AuctionScheduler::new(Symbol::BTCUSD)
+            .with_order(AuctionOrder {
+                side: Side::Buy,
+                amount: dec!(1),
+                price: dec!(99),
+            })
+            .with_cancel_when(|pricing| !pricing.within_collar())
+    }
+
+    #[test]
+    fn test_orders() {
+        let mut scheduler = buyer();
+
+        // closed until 10 minutes before the auction
+        let closed = snapshot(
+            &format!(r#""closed_until_ms":{},"#, at(600).timestamp_millis()),
+            AUCTION_MS,
+        );
+        assert!(scheduler.step(&closed, at(3600)).is_empty());
+        assert_eq!(Some(at(0)), scheduler.auction_time());
+        assert_eq!(
+            std::time::Duration::from_secs(60),
+            scheduler.next_poll(&closed, at(3600))
+        );
+        assert_eq!(
+            std::time::Duration::from_secs(1),
+            scheduler.next_poll(&closed, at(601))
+        );
+
+        let open = snapshot(
+            &format!(r#""next_update_ms":{},"#, at(300).timestamp_millis()),
+            AUCTION_MS,
+        );
+        let actions = scheduler.step(&open, at(600));
+        assert!(matches!(
+            scheduler.updates.as_slice(),
+            [AuctionUpdate::Opened { auction_time }] if *auction_time == at(0)
+        ));
+        assert_eq!(1, actions.len());
+        let req = serde_json::to_value(match &actions[0] {
+            Action::Submit(req) => req,
+            action => panic!("unexpected {:?}", action),
+        })
+        .unwrap();
+        assert_eq!(r#"["auction-only"]"#, req["options"].to_string());
+        assert!(scheduler.on_submitted(Ok(status(7))).is_none());
+        assert_eq!(&[7], scheduler.placed());
+        scheduler.updates.clear();
+
+        // the orders are only placed once
+        assert!(scheduler.step(&open, at(590)).is_empty());
+
+        let within = snapshot(&indicative(1, "101"), AUCTION_MS);
+        assert!(scheduler.step(&within, at(300)).is_empty());
+        // repeated snapshots of the same indicative
+        assert!(scheduler.step(&within, at(290)).is_empty());
+        assert!(matches!(
+            scheduler.updates.as_slice(),
+            [AuctionUpdate::Indicative(pricing)] if pricing.eid == Some(1)
+        ));
+        scheduler.updates.clear();
+
+        let outside = snapshot(&indicative(2, "94"), AUCTION_MS);
+        assert!(matches!(
+            scheduler.step(&outside, at(60)).as_slice(),
+            [Action::Cancel(7)]
+        ));
+        assert!(matches!(
+            scheduler.updates.as_slice(),
+            [
+                AuctionUpdate::Indicative(_),
+                AuctionUpdate::OutsideCollar { deviation, .. }
+            ] if *deviation == dec!(-0.06)
+        ));
+
+        // the next auction is scheduled once this one ran
+        let next = snapshot("", AUCTION_MS + 86_400_000);
+        assert!(matches!(
+            scheduler.step(&next, at(-1)).as_slice(),
+            [Action::FetchResult(time)] if *time == at(0)
+        ));
+        assert!(scheduler.is_done());
+    }
+
+    #[test]
+    fn test_result() {
+        let event = |eid: u64, time: DateTime<Utc>| {
+            format!(
+                r#"{{"auction_id":{},"auction_price":"100","auction_quantity":"2","eid":{},
+                "collar_price":"100","auction_result":"success","timestamp":0,
+                "timestampms":{},"event_type":"auction"}}"#,
+                eid,
+                eid,
+                time.timestamp_millis()
+            )
+        };
+        // newest first, the followed auction isn't the latest one
+        let history = serde_json::from_str::<AuctionHistory>(&format!(
+            "[{},{}]",
+            event(2, at(-86_400)),
+            event(1, at(-1))
+        ))
+        .unwrap();
+
+        let mut scheduler = buyer();
+        scheduler.on_history(at(0), Ok(history.clone()));
+        assert!(matches!(
+            scheduler.updates.as_slice(),
+            [AuctionUpdate::Ran(Some(pricing))] if pricing.eid == Some(1)
+        ));
+
+        scheduler.updates.clear();
+        scheduler.on_history(at(3600), Ok(history));
+        assert!(matches!(
+            scheduler.updates.as_slice(),
+            [AuctionUpdate::Ran(None)]
+        ));
+    }
+
+    #[test]
+    fn test_last_cancel_time() {
+        let open = snapshot(&indicative(1, "90"), AUCTION_MS);
+
+        // too late to place or cancel orders
+        let mut scheduler = buyer();
+        assert!(scheduler.step(&open, at(12)).is_empty());
+        assert!(matches!(
+            scheduler.updates.as_slice(),
+            [
+                AuctionUpdate::Opened { .. },
+                AuctionUpdate::Missed { auction_time },
+                AuctionUpdate::Indicative(_),
+                AuctionUpdate::OutsideCollar { .. },
+            ] if *auction_time == at(0)
+        ));
+        // reported once
+        scheduler.updates.clear();
+        assert!(scheduler.step(&open, at(11)).is_empty());
+        assert!(scheduler.updates.is_empty());
+
+        // the last cancel time of the auction opening is used instead of the default
+        let schedule = AuctionSchedule {
+            opened: at(900),
+            auction_time: at(0),
+            first_indicative: at(300),
+            last_cancel_time: at(30),
+        };
+        let mut scheduler = buyer().with_schedule(schedule);
+        assert!(scheduler.step(&open, at(34)).is_empty());
+        assert_eq!(
+            std::time::Duration::from_secs(35),
+            scheduler.next_poll(&open, at(34))
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{
+    algo::{Event, Pair},
+    backtest::exchange::SimulatedExchange,
+    candle::Candle,
+    decimal::Decimal,
+    order::{Broker, Order, OrderError, OrderId},
+    time::DateTime,
+};
+use async_trait::async_trait;
+use gemini::ws::marketv2::{l2::Change, trade::Trade};
+
+/// A backend which executes the orders of an algorithm run by the `Runtime`.
+#[async_trait]
+pub trait Execution: Broker + Send {
+    /// Called with every market data event before the algorithm sees it,
+    /// e.g. to fill simulated orders or to price market orders.
+    fn on_event(&mut self, _event: &Event) {}
+
+    /// The pairs whose order book and trades the backend needs, regardless of
+    /// the subscriptions of the algorithm.
+    fn book_pairs(&self) -> Vec<Pair> {
+        Vec::new()
+    }
+
+    /// Called with the changes to the order book of one of the `book_pairs`,
+    /// including the initial snapshot, along with the exchange timestamp of the latest
+    /// trade, as the updates carry none. Updates before the first trade are stamped
+    /// with the time they were received.
+    fn on_book(&mut self, _pair: &Pair, _time: DateTime, _changes: &[Change]) {}
+
+    /// Called with every trade of the `book_pairs` after the initial snapshot.
+    fn on_trade(&mut self, _trade: &Trade) {}
+
+    /// Processes the responses of the exchange. Called periodically and
+    /// before the algorithm is called.
+    fn poll(&mut self) {}
+
+    /// Cancels all open orders, e.g. once the kill switch is engaged.
+    fn cancel_all(&mut self);
+
+    /// Cancels all open orders and waits for outstanding requests to complete.
+    async fn shutdown(&mut self);
+}
+
+/// Paper trading against the live data the algorithm receives. Orders are filled
+/// against candles and trades which start after they were submitted.
+#[async_trait]
+impl Execution for SimulatedExchange {
+    fn on_event(&mut self, event: &Event) {
+        match event {
+            Event::Candle(pair, resolution, candle) => {
+                self.process(pair, candle);
+                if let Some(duration) = resolution.to_duration() {
+                    self.set_time(candle.time + duration);
+                }
+            }
+            Event::Tick(pair, tick) => {
+                let candle = Candle {
+                    time: tick.time,
+                    open: tick.price,
+                    high: tick.price,
+                    low: tick.price,
+                    close: tick.price,
+                    volume: tick.quantity,
+                };
+                self.process(pair, &candle);
+                self.set_time(tick.time);
+            }
+        }
+    }
+
+    fn cancel_all(&mut self) {
+        SimulatedExchange::cancel_all(self);
+    }
+
+    async fn shutdown(&mut self) {
+        SimulatedExchange::cancel_all(self);
+    }
+}
+
+/// Lets an algorithm inspect its holdings while rejecting all of its orders.
+pub(crate) struct Halted<'a, B: ?Sized>(pub &'a B);
+
+impl<'a, B: Broker + ?Sized> Broker for Halted<'a, B> {
+    fn submit(&mut self, _: Order) -> Result<OrderId, OrderError> {
+        Err(OrderError::Halted)
+    }
+
+    fn cancel(&mut self, _: OrderId) -> bool {
+        false
+    }
+
+    fn position(&self, pair: &Pair) -> Decimal {
+        self.0.position(pair)
+    }
+
+    fn cash(&self) -> Decimal {
+        self.0.cash()
+    }
+
+    fn open_order_count(&self) -> usize {
+        self.0.open_order_count()
+    }
+
+    fn open_quantity(&self, pair: &Pair) -> Decimal {
+        self.0.open_quantity(pair)
+    }
+}
@@ -0,0 +1,970 @@
+pub mod auction;
+pub mod execution;
+pub mod paper;
+pub mod price_feed;
+pub mod rest;
+
+use crate::{
+    aggregate::Aggregator,
+    algo::{Algorithm, Event, MarketData},
+    candle::Candle,
+    decimal::Decimal,
+    period::Resolution,
+    risk::{Checked, RiskManager},
+    tick::Tick,
+    time::DateTime,
+};
+use async_tungstenite::tungstenite::{self, Message};
+use chrono::Utc;
+use exchange_api::{MarketDataSource, Pair};
+use execution::{Execution, Halted};
+use futures::{Future, Sink, SinkExt, Stream, StreamExt};
+use gemini::{
+    symbol::Symbol,
+    ws::marketv2::{
+        candle::Candles,
+        l2::{Auction, Change},
+        trade::Trade,
+        Subscribe, Subscription, SubscriptionType,
+    },
+};
+use market::order_book::OrderBook;
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    convert::TryFrom,
+    time::Duration,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+pub use crate::risk::KillSwitch;
+
+#[derive(Debug)]
+pub enum Error {
+    WebSocket(Box<tungstenite::Error>),
+    Json(serde_json::Error),
+    /// A subscribed pair isn't traded on the exchange.
+    UnknownPair(Pair),
+}
+
+impl From<tungstenite::Error> for Error {
+    fn from(e: tungstenite::Error) -> Self {
+        Self::WebSocket(Box::new(e))
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+// Gemini streams every change to the current candle, so a candle is only complete
+// once a newer one arrives.
+#[derive(Debug, Default)]
+struct CandleTracker {
+    latest: HashMap<(Pair, Resolution), Candle>,
+}
+
+impl CandleTracker {
+    // Returns the completed candles in chronological order and whether they are
+    // part of the initial snapshot.
+    fn update(
+        &mut self,
+        pair: &Pair,
+        resolution: Resolution,
+        mut candles: Vec<Candle>,
+    ) -> (Vec<Candle>, bool) {
+        candles.sort_by_key(|candle| candle.time);
+        let key = (pair.clone(), resolution);
+        let snapshot = !self.latest.contains_key(&key);
+
+        let mut complete = Vec::new();
+        for candle in candles {
+            match self.latest.get_mut(&key) {
+                Some(latest) if candle.time > latest.time => {
+                    complete.push(std::mem::replace(latest, candle));
+                }
+                Some(latest) if candle.time == latest.time => *latest = candle,
+                Some(_) => {}
+                None => {
+                    self.latest.insert(key.clone(), candle);
+                }
+            }
+        }
+        (complete, snapshot)
+    }
+}
+
+/// Runs an algorithm against Gemini's live market data feed.
+///
+/// Candle subscriptions are served by Gemini's candle feeds, ticks and the resolutions
+/// without a feed, e.g. seconds and weeks, are built from the trades of the L2 feed.
+/// Data from the initial snapshots warms up the algorithm, but any orders it places in
+/// the meantime are rejected with `OrderError::Halted`, as are all orders once the
+/// kill switch is engaged.
+///
+/// Given a `RiskManager`, orders are checked against its limits. The reference price of
+/// a pair is the collar price of its upcoming auction, if any, or the mid of its
+/// order book. Pairs without an L2 subscription are referenced at the price of their
+/// latest event. Messages of symbols without a pair are ignored.
+pub struct Runtime<A: Algorithm, E> {
+    algo: A,
+    execution: E,
+    data: A::Data,
+    aggregator: Aggregator,
+    candles: CandleTracker,
+    kill_switch: KillSwitch,
+    // Whether the open orders were cancelled since the kill switch was engaged.
+    killed: bool,
+    // The exchange timestamp of the latest trade, as Gemini's book updates carry none.
+    exchange_time: Option<DateTime>,
+    // The order books of the L2 feed and the collar prices of upcoming auctions.
+    books: HashMap<Pair, OrderBook>,
+    collars: HashMap<Pair, Decimal>,
+    risk: Option<RiskManager>,
+}
+
+impl<A, E> Runtime<A, E>
+where
+    A: Algorithm,
+    A::Data: MarketData,
+    E: Execution,
+{
+    pub fn new(algo: A, execution: E) -> Self {
+        let ticks = A::Data::subscriptions()
+            .into_iter()
+            .filter(|(_, resolution)| resolution.candle_subscription().is_none())
+            .collect::<Vec<_>>();
+
+        Self {
+            algo,
+            execution,
+            data: A::Data::default(),
+            aggregator: Aggregator::new(&ticks),
+            candles: CandleTracker::default(),
+            kill_switch: KillSwitch::default(),
+            killed: false,
+            exchange_time: None,
+            books: HashMap::new(),
+            collars: HashMap::new(),
+            risk: None,
+        }
+    }
+
+    /// Checks the orders of the algorithm against `risk`, which shares the kill switch
+    /// of the runtime.
+    pub fn with_risk(mut self, risk: RiskManager) -> Self {
+        self.risk = Some(risk.with_kill_switch(self.kill_switch.clone()));
+        self
+    }
+
+    pub fn risk(&self) -> Option<&RiskManager> {
+        self.risk.as_ref()
+    }
+
+    pub fn kill_switch(&self) -> KillSwitch {
+        self.kill_switch.clone()
+    }
+
+    pub fn algo(&self) -> &A {
+        &self.algo
+    }
+
+    pub fn execution(&self) -> &E {
+        &self.execution
+    }
+
+    pub fn into_parts(self) -> (A, E) {
+        (self.algo, self.execution)
+    }
+
+    /// The market data subscription covering every subscription of the algorithm.
+    /// Fails if one of the pairs isn't traded on Gemini.
+    pub fn subscribe(&self) -> Result<Subscribe, Error> {
+        let to_symbol = |pair: Pair| Symbol::try_from(&pair).map_err(|_| Error::UnknownPair(pair));
+
+        let mut subscriptions = Vec::<(SubscriptionType, Vec<Symbol>)>::new();
+        for (pair, resolution) in A::Data::subscriptions() {
+            let ty = resolution
+                .candle_subscription()
+                .unwrap_or(SubscriptionType::L2);
+            let symbol = to_symbol(pair)?;
+            match subscriptions.iter_mut().find(|(other, _)| *other == ty) {
+                Some((_, symbols)) if symbols.contains(&symbol) => {}
+                Some((_, symbols)) => symbols.push(symbol),
+                None => subscriptions.push((ty, vec![symbol])),
+            }
+        }
+        for pair in self.execution.book_pairs() {
+            let symbol = to_symbol(pair)?;
+            match subscriptions
+                .iter_mut()
+                .find(|(ty, _)| *ty == SubscriptionType::L2)
+            {
+                Some((_, symbols)) if symbols.contains(&symbol) => {}
+                Some((_, symbols)) => symbols.push(symbol),
+                None => subscriptions.push((SubscriptionType::L2, vec![symbol])),
+            }
+        }
+
+        Ok(Subscribe::builder()
+            .subscriptions(
+                subscriptions
+                    .into_iter()
+                    .map(|(name, symbols)| {
+                        Subscription::builder().name(name).symbols(symbols).build()
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .build())
+    }
+
+    /// Passes a single market data message to the algorithm.
+    /// Messages the algorithm isn't subscribed to are ignored.
+    pub fn handle_message(&mut self, text: &str) -> Result<(), Error> {
+        let value = serde_json::from_str::<serde_json::Value>(text)?;
+        let ty = value
+            .get("type")
+            .and_then(|ty| ty.as_str())
+            .unwrap_or_default();
+
+        match ty {
+            // the trades of the initial snapshot precede the subscription
+            "l2_updates" => {
+                let pair = match exchange_api::gemini::pair(Symbol::deserialize(&value["symbol"])?)
+                {
+                    Some(pair) => pair,
+                    None => return Ok(()),
+                };
+                let changes = Vec::<Change>::deserialize(&value["changes"])?;
+                let trades = match value.get("trades") {
+                    Some(trades) => Vec::<Trade>::deserialize(trades)?,
+                    None => Vec::new(),
+                };
+                for trade in &trades {
+                    self.observe(trade.timestamp);
+                }
+                // stamped with the latest trade until the exchange has sent one
+                let time = self.exchange_time.unwrap_or_else(Utc::now);
+                self.execution.on_book(&pair, time, &changes);
+                let book = self.books.entry(pair).or_default();
+                for change in &changes {
+                    book.update(paper::book_order(change));
+                }
+                if let Some(auctions) = value.get("auction_events") {
+                    for auction in Vec::<Auction>::deserialize(auctions)? {
+                        self.on_auction(auction);
+                    }
+                }
+
+                for trade in trades {
+                    for event in self.aggregator.push_trade(&trade) {
+                        self.handle(event, true);
+                    }
+                }
+            }
+            "trade" => {
+                let trade = Trade::deserialize(&value)?;
+                self.observe(trade.timestamp);
+                self.execution.on_trade(&trade);
+                for event in self.aggregator.push_trade(&trade) {
+                    self.handle(event, false);
+                }
+            }
+            "auction_indicative" | "auction_result" => {
+                self.on_auction(Auction::deserialize(&value)?);
+            }
+            ty if ty.starts_with("candles_") => {
+                let update = Candles::deserialize(&value)?;
+                let resolution = Resolution::from(update.candle_type);
+                let candles = update.candles.into_iter().map(Candle::from).collect();
+                if let Some(pair) = exchange_api::gemini::pair(update.symbol) {
+                    self.handle_candles(&pair, resolution, candles);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn on_auction(&mut self, auction: Auction) {
+        match auction {
+            Auction::Indicative(indicative) => {
+                let data = indicative.data;
+                if let Some(pair) = exchange_api::gemini::pair(data.symbol) {
+                    self.collars.insert(pair, data.collar_price);
+                }
+            }
+            Auction::Result(result) => {
+                if let Some(pair) = exchange_api::gemini::pair(result.data.symbol) {
+                    self.collars.remove(&pair);
+                }
+            }
+        }
+    }
+
+    // The collar price of an upcoming auction, the mid of the book or the latest price.
+    fn reference(&self, event: &Event) -> Decimal {
+        let pair = event.pair();
+        self.collars
+            .get(pair)
+            .copied()
+            .or_else(|| self.books.get(pair)?.mid())
+            .unwrap_or(match event {
+                Event::Candle(_, _, candle) => candle.close,
+                Event::Tick(_, tick) => tick.price,
+            })
+    }
+
+    fn observe(&mut self, time: DateTime) {
+        self.exchange_time = Some(self.exchange_time.map_or(time, |latest| latest.max(time)));
+    }
+
+    /// Passes the latest candles of `pair` to the algorithm, e.g. from a market data
+    /// source other than Gemini's feed. As with the feed, a candle is complete once a
+    /// newer one arrives and the candles completed by the first update are warmup.
+    pub fn handle_candles(&mut self, pair: &Pair, resolution: Resolution, candles: Vec<Candle>) {
+        let (complete, snapshot) = self.candles.update(pair, resolution, candles);
+        for candle in complete {
+            self.handle(Event::Candle(pair.clone(), resolution, candle), snapshot);
+        }
+    }
+
+    /// Passes a trade of `pair` to the ticks and candles built from trades.
+    pub fn handle_tick(&mut self, pair: &Pair, tick: &Tick) {
+        self.push_tick(pair, tick, false);
+    }
+
+    fn push_tick(&mut self, pair: &Pair, tick: &Tick, warmup: bool) {
+        for event in self.aggregator.push(pair, tick) {
+            self.handle(event, warmup);
+        }
+    }
+
+    // Cancels the open orders once the kill switch is engaged, by hand or by the risk checks.
+    fn check_kill_switch(&mut self) {
+        if self.kill_switch.is_engaged() && !self.killed {
+            self.killed = true;
+            self.execution.cancel_all();
+        }
+    }
+
+    fn handle(&mut self, event: Event, warmup: bool) {
+        self.check_kill_switch();
+        self.execution.on_event(&event);
+        if !event.apply(&mut self.data) {
+            return;
+        }
+
+        self.execution.poll();
+        let reference = self.reference(&event);
+        if let Some(risk) = &mut self.risk {
+            risk.set_reference(event.pair().clone(), reference);
+            risk.update_broker(Utc::now(), &self.execution);
+        }
+        self.check_kill_switch();
+
+        match &self.risk {
+            _ if warmup || self.kill_switch.is_engaged() => {
+                self.algo.on(&self.data, &mut Halted(&self.execution))
+            }
+            Some(risk) => self.algo.on(
+                &self.data,
+                &mut Checked {
+                    broker: &mut self.execution,
+                    risk,
+                },
+            ),
+            None => self.algo.on(&self.data, &mut self.execution),
+        }
+    }
+
+    /// Subscribes to `stream` and runs the algorithm until the stream ends, fails,
+    /// or `shutdown` completes. Open orders are cancelled before returning.
+    pub async fn run<S>(
+        &mut self,
+        mut stream: S,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), Error>
+    where
+        S: Stream<Item = Result<Message, tungstenite::Error>>
+            + Sink<Message, Error = tungstenite::Error>
+            + Unpin,
+    {
+        let result = self.run_until(&mut stream, shutdown).await;
+        self.execution.shutdown().await;
+        result
+    }
+
+    async fn run_until<S>(
+        &mut self,
+        stream: &mut S,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), Error>
+    where
+        S: Stream<Item = Result<Message, tungstenite::Error>>
+            + Sink<Message, Error = tungstenite::Error>
+            + Unpin,
+    {
+        let subscribe = serde_json::to_string(&self.subscribe()?)?;
+        stream.send(Message::Text(subscribe)).await?;
+
+        futures::pin_mut!(shutdown);
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => return Ok(()),
+                _ = interval.tick() => {
+                    // bars without trades are only closed once time passes them
+                    for event in self.aggregator.flush(Utc::now()) {
+                        self.handle(event, false);
+                    }
+                    self.execution.poll();
+                    self.check_kill_switch();
+                }
+                msg = stream.next() => match msg {
+                    Some(Ok(Message::Text(text))) => self.handle_message(&text)?,
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return Err(e.into()),
+                    None => return Ok(()),
+                },
+            }
+        }
+    }
+
+    /// Runs the algorithm on data polled from `source` every `interval` until `shutdown`
+    /// completes, so algorithms run unchanged against any exchange with an adapter.
+    /// Fails if one of the subscribed pairs isn't traded on the source.
+    ///
+    /// Subscriptions served by candles poll the candles of their resolution, the others
+    /// are built from the polled trades. Failed requests are sent to `failures` and
+    /// retried on the next poll. Paper and execution backends get no order book or
+    /// trade updates in this mode, only the events of the subscriptions.
+    /// Open orders are cancelled before returning.
+    pub async fn run_source<S: MarketDataSource>(
+        &mut self,
+        source: &S,
+        interval: Duration,
+        failures: &UnboundedSender<S::Error>,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), Error> {
+        let pairs = source.pairs();
+        let mut candles = Vec::new();
+        let mut trades = HashMap::new();
+        for (pair, resolution) in A::Data::subscriptions() {
+            if !pairs.contains(&pair) {
+                return Err(Error::UnknownPair(pair));
+            }
+            match resolution.to_duration() {
+                Some(duration) if resolution.candle_subscription().is_some() => {
+                    candles.push((pair, resolution, duration))
+                }
+                _ => {
+                    trades.entry(pair).or_insert(None);
+                }
+            }
+        }
+
+        self.poll_until(source, candles, trades, interval, failures, shutdown)
+            .await;
+        self.execution.shutdown().await;
+        Ok(())
+    }
+
+    async fn poll_until<S: MarketDataSource>(
+        &mut self,
+        source: &S,
+        candles: Vec<(Pair, Resolution, chrono::Duration)>,
+        mut trades: HashMap<Pair, Option<TradeCursor>>,
+        interval: Duration,
+        failures: &UnboundedSender<S::Error>,
+        shutdown: impl Future<Output = ()>,
+    ) {
+        futures::pin_mut!(shutdown);
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => return,
+                _ = interval.tick() => {
+                    for (pair, resolution, duration) in &candles {
+                        match source.candles(pair, *duration).await {
+                            Ok(update) => {
+                                let update = update.into_iter().map(Candle::from).collect();
+                                self.handle_candles(pair, *resolution, update);
+                            }
+                            Err(e) => {
+                                let _ = failures.send(e);
+                            }
+                        }
+                    }
+                    for (pair, cursor) in trades.iter_mut() {
+                        let update = match source.trades(pair).await {
+                            Ok(update) => update,
+                            Err(e) => {
+                                let _ = failures.send(e);
+                                continue;
+                            }
+                        };
+                        // the trades of the first poll precede the run
+                        let warmup = cursor.is_none();
+                        for trade in TradeCursor::advance(cursor, update) {
+                            self.push_tick(pair, &Tick::from(&trade), warmup);
+                        }
+                    }
+                    for event in self.aggregator.flush(Utc::now()) {
+                        self.handle(event, false);
+                    }
+                    self.execution.poll();
+                }
+            }
+        }
+    }
+}
+
+// The number of trade ids a `TradeCursor` remembers, well beyond the trades a poll
+// returns.
+const SEEN_TRADES: usize = 1000;
+
+// The ids of the latest trades seen of a pair, polled trades overlap with earlier
+// polls and trades may be published late.
+#[derive(Debug, Default)]
+struct TradeCursor {
+    ids: HashSet<String>,
+    // The ids in the order they were seen, to forget the oldest.
+    order: VecDeque<String>,
+}
+
+impl TradeCursor {
+    // Returns the unbroken trades not seen before, in the order they were polled.
+    fn advance(
+        cursor: &mut Option<Self>,
+        trades: Vec<exchange_api::Trade>,
+    ) -> Vec<exchange_api::Trade> {
+        let cursor = cursor.get_or_insert_with(Self::default);
+        let mut new = Vec::new();
+        for trade in trades {
+            if trade.broken || !cursor.ids.insert(trade.id.clone()) {
+                continue;
+            }
+            cursor.order.push_back(trade.id.clone());
+            if cursor.order.len() > SEEN_TRADES {
+                let oldest = cursor.order.pop_front().unwrap();
+                cursor.ids.remove(&oldest);
+            }
+            new.push(trade);
+        }
+        new
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        algo::AlgorithmData,
+        backtest::exchange::SimulatedExchange,
+        order::{Broker, Order, OrderError, Side},
+        risk::{Limits, Violation},
+    };
+    use chrono::TimeZone;
+    use gemini::ws::record::{Frame, Replayer, Speed};
+    use market::paper::PaperExchange;
+    use paper::PaperExecution;
+    use rust_decimal_macros::dec;
+    use std::io::Cursor;
+
+    #[derive(Default)]
+    struct Data {
+        candle: Option<Candle>,
+    }
+
+    fn btc() -> Pair {
+        Pair::new("BTC", "USD")
+    }
+
+    impl MarketData for Data {
+        fn subscriptions() -> Vec<(Pair, Resolution)> {
+            vec![(btc(), Resolution::Minute)]
+        }
+
+        fn update(&mut self, pair: &Pair, resolution: Resolution, candle: &Candle) -> bool {
+            let tracked = *pair == btc() && resolution == Resolution::Minute;
+            if tracked {
+                self.candle = Some(candle.clone());
+            }
+            tracked
+        }
+
+        fn update_tick(&mut self, _: &Pair, _: &Tick) -> bool {
+            false
+        }
+    }
+
+    // Buys on every candle.
+    #[derive(Default)]
+    struct Buyer {
+        results: Vec<Result<(), OrderError>>,
+    }
+
+    impl AlgorithmData for Buyer {
+        type Data = Data;
+    }
+
+    impl Algorithm for Buyer {
+        fn on(&mut self, _: &Data, broker: &mut dyn Broker) {
+            let order = Order::market(btc(), Side::Buy, dec!(1));
+            self.results.push(broker.submit(order).map(|_| ()));
+        }
+    }
+
+    fn candles(times: &[i64]) -> String {
+        let candles = times
+            .iter()
+            .map(|t| format!(r#"[{},"100","110","90","{}","1"]"#, t * 60_000, 100 + t))
+            .collect::<Vec<_>>();
+        format!(
+            r#"{{"type":"candles_1m_updates","symbol":"BTCUSD","changes":[{}]}}"#,
+            candles.join(",")
+        )
+    }
+
+    // Serves a batch of candles of the given minutes per poll, failing the second poll
+    // and every poll after the last batch.
+    struct Source(Vec<Pair>, std::sync::Mutex<Vec<Vec<i64>>>);
+
+    #[async_trait::async_trait]
+    impl MarketDataSource for Source {
+        type Error = ();
+
+        fn pairs(&self) -> Vec<exchange_api::Pair> {
+            self.0.clone()
+        }
+
+        async fn candles(
+            &self,
+            pair: &exchange_api::Pair,
+            interval: chrono::Duration,
+        ) -> Result<Vec<exchange_api::Candle>, ()> {
+            assert_eq!(self.pairs()[0], *pair);
+            assert_eq!(chrono::Duration::minutes(1), interval);
+            let mut batches = self.1.lock().unwrap();
+            if batches.is_empty() || batches[0].is_empty() {
+                batches.retain(|batch| !batch.is_empty());
+                return Err(());
+            }
+            Ok(batches
+                .remove(0)
+                .into_iter()
+                .map(|t| exchange_api::Candle {
+                    time: Utc.timestamp_opt(t * 60, 0).unwrap(),
+                    open: dec!(100),
+                    high: dec!(110),
+                    low: dec!(90),
+                    close: dec!(100),
+                    volume: dec!(1),
+                })
+                .collect())
+        }
+
+        async fn trades(&self, _: &exchange_api::Pair) -> Result<Vec<exchange_api::Trade>, ()> {
+            Err(())
+        }
+
+        async fn order_book(&self, _: &exchange_api::Pair) -> Result<exchange_api::OrderBook, ()> {
+            Err(())
+        }
+    }
+
+    fn recording(messages: &[String]) -> Cursor<Vec<u8>> {
+        let mut recording = Vec::new();
+        for text in messages {
+            let frame = Frame {
+                timestamp: Utc.timestamp_opt(0, 0).unwrap(),
+                text: text.clone(),
+            };
+            serde_json::to_writer(&mut recording, &frame).unwrap();
+            recording.push(b'\n');
+        }
+        Cursor::new(recording)
+    }
+
+    #[test]
+    fn test_candle_tracker() {
+        let mut tracker = CandleTracker::default();
+        let candle = |minute: i64, close| Candle {
+            time: Utc.timestamp_opt(minute * 60, 0).unwrap(),
+            open: dec!(1),
+            high: dec!(1),
+            low: dec!(1),
+            close,
+            volume: dec!(1),
+        };
+        let (pair, resolution) = (&btc(), Resolution::Minute);
+
+        let (complete, snapshot) = tracker.update(
+            pair,
+            resolution,
+            vec![candle(2, dec!(3)), candle(1, dec!(2))],
+        );
+        assert!(snapshot);
+        assert_eq!(vec![candle(1, dec!(2))], complete);
+
+        let (complete, snapshot) = tracker.update(pair, resolution, vec![candle(2, dec!(4))]);
+        assert!(!snapshot);
+        assert!(complete.is_empty());
+
+        let (complete, _) = tracker.update(pair, resolution, vec![candle(3, dec!(5))]);
+        assert_eq!(vec![candle(2, dec!(4))], complete);
+    }
+
+    #[test]
+    fn test_trade_cursor() {
+        let trade = |id: &str, second, broken| exchange_api::Trade {
+            id: id.to_owned(),
+            time: Utc.timestamp_opt(second, 0).unwrap(),
+            price: dec!(100),
+            quantity: dec!(1),
+            taker: None,
+            kind: exchange_api::TradeKind::Continuous,
+            broken,
+        };
+        let ids = |trades: Vec<exchange_api::Trade>| {
+            trades.into_iter().map(|trade| trade.id).collect::<Vec<_>>()
+        };
+
+        let mut cursor = None;
+        let first = vec![
+            trade("1", 1, false),
+            trade("2", 2, false),
+            trade("3", 2, true),
+        ];
+        assert_eq!(
+            vec!["1", "2"],
+            ids(TradeCursor::advance(&mut cursor, first))
+        );
+
+        // polls overlap, and trades may share a time
+        let second = vec![
+            trade("2", 2, false),
+            trade("4", 2, false),
+            trade("5", 3, false),
+        ];
+        assert_eq!(
+            vec!["4", "5"],
+            ids(TradeCursor::advance(&mut cursor, second))
+        );
+        // late trades aren't skipped
+        assert_eq!(
+            vec!["0"],
+            ids(TradeCursor::advance(
+                &mut cursor,
+                vec![trade("0", 1, false)]
+            ))
+        );
+
+        // only the latest ids are remembered
+        let many = (10..10 + SEEN_TRADES as i64)
+            .map(|id| trade(&id.to_string(), id, false))
+            .collect();
+        assert_eq!(SEEN_TRADES, TradeCursor::advance(&mut cursor, many).len());
+        assert_eq!(SEEN_TRADES, cursor.as_ref().unwrap().ids.len());
+        assert_eq!(
+            vec!["1"],
+            ids(TradeCursor::advance(
+                &mut cursor,
+                vec![trade("1", 1, false)]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_subscribe() {
+        let runtime = Runtime::new(Buyer::default(), SimulatedExchange::new(dec!(0)));
+        assert_eq!(
+            r#"{"type":"subscribe","subscriptions":[{"name":"candles_1m","symbols":["BTCUSD"]}]}"#,
+            serde_json::to_string(&runtime.subscribe().unwrap()).unwrap()
+        );
+
+        // pairs Gemini doesn't trade can't be subscribed to
+        let runtime = Runtime::new(
+            Buyer::default(),
+            PaperExecution::new(PaperExchange::new(dec!(0)), &[Pair::new("BTC", "JPY")]),
+        );
+        assert!(matches!(
+            runtime.subscribe(),
+            Err(Error::UnknownPair(pair)) if pair == Pair::new("BTC", "JPY")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_run_replay() {
+        let messages = vec![
+            candles(&[2, 1, 0]),
+            r#"{"type":"heartbeat","timestampms":0}"#.to_owned(),
+            candles(&[3]),
+            candles(&[3]),
+            candles(&[4]),
+        ];
+        let replayer = Replayer::new(recording(&messages), Speed::Max);
+
+        let mut runtime = Runtime::new(Buyer::default(), SimulatedExchange::new(dec!(1000)));
+        runtime
+            .run(replayer, futures::future::pending())
+            .await
+            .unwrap();
+
+        let (algo, exchange) = runtime.into_parts();
+        // the two snapshot candles are warmup, the completed candles 2 and 3 are live
+        assert_eq!(
+            vec![
+                Err(OrderError::Halted),
+                Err(OrderError::Halted),
+                Ok(()),
+                Ok(())
+            ],
+            algo.results
+        );
+        // the order placed on candle 2 fills at the open of candle 3,
+        // the order placed on candle 3 is cancelled on shutdown
+        assert_eq!(1, exchange.fills().len());
+        assert_eq!(dec!(100), exchange.fills()[0].price);
+        assert_eq!(0, exchange.open_orders().count());
+    }
+
+    #[tokio::test]
+    async fn test_kill_switch() {
+        let messages = vec![candles(&[1, 0]), candles(&[2]), candles(&[3])];
+        let replayer = Replayer::new(recording(&messages), Speed::Max);
+
+        let mut runtime = Runtime::new(Buyer::default(), SimulatedExchange::new(dec!(1000)));
+        runtime.kill_switch().engage();
+        runtime
+            .run(replayer, futures::future::pending())
+            .await
+            .unwrap();
+
+        let (algo, exchange) = runtime.into_parts();
+        assert_eq!(3, algo.results.len());
+        assert!(algo.results.iter().all(|r| *r == Err(OrderError::Halted)));
+        assert!(exchange.fills().is_empty());
+    }
+
+    #[test]
+    fn test_kill_switch_cancels() {
+        let mut runtime = Runtime::new(Buyer::default(), SimulatedExchange::new(dec!(1000)));
+        runtime.handle_message(&candles(&[1, 0])).unwrap();
+        runtime.handle_message(&candles(&[2])).unwrap();
+        assert_eq!(1, runtime.execution().open_orders().count());
+
+        // the order placed on candle 1 is cancelled before candle 2 could fill it
+        runtime.kill_switch().engage();
+        runtime.handle_message(&candles(&[3])).unwrap();
+        assert_eq!(0, runtime.execution().open_orders().count());
+        assert!(runtime.execution().fills().is_empty());
+        assert_eq!(
+            Some(&Err(OrderError::Halted)),
+            runtime.algo().results.last()
+        );
+    }
+
+    #[test]
+    fn test_reference_price() {
+        let mut runtime = Runtime::new(Buyer::default(), SimulatedExchange::new(dec!(1000)))
+            .with_risk(RiskManager::new(Limits::default()));
+        let reference =
+            |runtime: &Runtime<Buyer, SimulatedExchange>| runtime.risk().unwrap().reference(&btc());
+        let auction = |ty: &str| {
+            format!(
+                r#"{{"type":"{}","symbol":"BTCUSD","result":"failure","time_ms":0,"collar_price":"97.5","auction_price":"0","auction_quantity":"0"}}"#,
+                ty
+            )
+        };
+
+        // the close without a book
+        runtime.handle_message(&candles(&[1, 0])).unwrap();
+        assert_eq!(Some(dec!(100)), reference(&runtime));
+
+        runtime
+            .handle_message(r#"{"type":"l2_updates","symbol":"BTCUSD","changes":[["buy","96","1"],["sell","100","1"]]}"#)
+            .unwrap();
+        runtime.handle_message(&candles(&[2])).unwrap();
+        assert_eq!(Some(dec!(98)), reference(&runtime));
+
+        runtime
+            .handle_message(&auction("auction_indicative"))
+            .unwrap();
+        runtime.handle_message(&candles(&[3])).unwrap();
+        assert_eq!(Some(dec!(97.5)), reference(&runtime));
+
+        runtime.handle_message(&auction("auction_result")).unwrap();
+        runtime.handle_message(&candles(&[4])).unwrap();
+        assert_eq!(Some(dec!(98)), reference(&runtime));
+    }
+
+    #[tokio::test]
+    async fn test_risk() {
+        let messages = vec![candles(&[1, 0]), candles(&[2]), candles(&[3])];
+        let replayer = Replayer::new(recording(&messages), Speed::Max);
+
+        let mut limits = Limits::default();
+        limits.max_position.insert(btc(), dec!(1));
+        let mut runtime = Runtime::new(Buyer::default(), SimulatedExchange::new(dec!(1000)))
+            .with_risk(RiskManager::new(limits));
+        runtime
+            .run(replayer, futures::future::pending())
+            .await
+            .unwrap();
+
+        // the first order is filled before the second one is checked
+        let (algo, _) = runtime.into_parts();
+        assert_eq!(
+            vec![
+                Err(OrderError::Halted),
+                Ok(()),
+                Err(OrderError::Risk(Violation::Position {
+                    pair: btc(),
+                    position: dec!(2),
+                    limit: dec!(1)
+                }))
+            ],
+            algo.results
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_source() {
+        let (failures, mut failed) = tokio::sync::mpsc::unbounded_channel();
+
+        // a pair the source doesn't trade isn't silently skipped
+        let mut runtime = Runtime::new(Buyer::default(), SimulatedExchange::new(dec!(1000)));
+        let source = Source(vec![Pair::new("ETH", "USD")], Default::default());
+        let result = runtime
+            .run_source(
+                &source,
+                Duration::from_millis(1),
+                &failures,
+                futures::future::pending(),
+            )
+            .await;
+        assert!(matches!(result, Err(Error::UnknownPair(pair)) if pair == btc()));
+
+        // the failed poll is retried, runs until the source is exhausted
+        let shutdown = async {
+            failed.recv().await;
+            failed.recv().await;
+        };
+        let batches = vec![vec![1, 0], vec![], vec![2], vec![3]];
+        let source = Source(vec![btc()], std::sync::Mutex::new(batches));
+        runtime
+            .run_source(&source, Duration::from_millis(1), &failures, shutdown)
+            .await
+            .unwrap();
+
+        // candle 0 is warmup, the completed candles 1 and 2 are live
+        let (algo, exchange) = runtime.into_parts();
+        assert_eq!(vec![Err(OrderError::Halted), Ok(()), Ok(())], algo.results);
+        assert_eq!(1, exchange.fills().len());
+        assert_eq!(0, exchange.open_orders().count());
+    }
+}
@@ -0,0 +1,234 @@
+use super::execution::Execution;
+use crate::{
+    algo::Pair,
+    decimal::Decimal,
+    order::{validate, Broker, Order, OrderError, OrderId, OrderKind, Side},
+    time::DateTime,
+};
+use async_trait::async_trait;
+use gemini::{
+    symbol::Symbol,
+    ws::marketv2::{self, l2::Change, trade::Trade},
+};
+use market::{
+    order_book::{Order as BookOrder, OrderData},
+    paper::{self, NewOrder, PaperExchange, Reject},
+};
+use std::convert::TryFrom;
+
+impl From<Reject> for OrderError {
+    fn from(reject: Reject) -> Self {
+        match reject {
+            Reject::InvalidQuantity => Self::InvalidQuantity,
+            Reject::InvalidPrice => Self::InvalidPrice,
+            Reject::InsufficientFunds => Self::InsufficientFunds,
+            Reject::WouldTake => Self::WouldTake,
+        }
+    }
+}
+
+/// Converts a change of Gemini's L2 feed to a change of a price level of an `OrderBook`.
+pub(crate) fn book_order(change: &Change) -> BookOrder<OrderData> {
+    match change {
+        Change::Buy(data) => BookOrder::Bid(OrderData::new(data.price_level, data.quantity)),
+        Change::Sell(data) => BookOrder::Ask(OrderData::new(data.price_level, data.quantity)),
+    }
+}
+
+fn side(side: Side) -> paper::Side {
+    match side {
+        Side::Buy => paper::Side::Buy,
+        Side::Sell => paper::Side::Sell,
+    }
+}
+
+/// Paper trading against the order books of `pairs`. Limit orders are good till
+/// cancelled, use the `PaperExchange` directly for other times in force.
+///
+/// The `PaperExchange` simulates Gemini, so orders of pairs Gemini doesn't trade are
+/// rejected with `OrderError::UnknownPair`.
+pub struct PaperExecution {
+    exchange: PaperExchange,
+    pairs: Vec<Pair>,
+}
+
+impl PaperExecution {
+    pub fn new(exchange: PaperExchange, pairs: &[Pair]) -> Self {
+        Self {
+            exchange,
+            pairs: pairs.to_vec(),
+        }
+    }
+
+    pub fn exchange(&self) -> &PaperExchange {
+        &self.exchange
+    }
+
+    pub fn exchange_mut(&mut self) -> &mut PaperExchange {
+        &mut self.exchange
+    }
+
+    pub fn into_inner(self) -> PaperExchange {
+        self.exchange
+    }
+}
+
+impl Broker for PaperExecution {
+    fn submit(&mut self, order: Order) -> Result<OrderId, OrderError> {
+        validate(&order)?;
+        let symbol = Symbol::try_from(&order.pair).map_err(|_| OrderError::UnknownPair)?;
+        let new = match order.kind {
+            OrderKind::Market => NewOrder::market(symbol, side(order.side), order.quantity),
+            OrderKind::Limit(price) => {
+                NewOrder::limit(symbol, side(order.side), order.quantity, price)
+            }
+        };
+        Ok(self.exchange.submit(new)?)
+    }
+
+    fn cancel(&mut self, id: OrderId) -> bool {
+        self.exchange.cancel(id)
+    }
+
+    fn position(&self, pair: &Pair) -> Decimal {
+        Symbol::try_from(pair).map_or(Decimal::ZERO, |symbol| self.exchange.position(symbol))
+    }
+
+    fn cash(&self) -> Decimal {
+        self.exchange.cash()
+    }
+
+    fn open_order_count(&self) -> usize {
+        self.exchange.open_order_count()
+    }
+
+    fn open_quantity(&self, pair: &Pair) -> Decimal {
+        Symbol::try_from(pair).map_or(Decimal::ZERO, |symbol| self.exchange.open_quantity(symbol))
+    }
+}
+
+#[async_trait]
+impl Execution for PaperExecution {
+    fn book_pairs(&self) -> Vec<Pair> {
+        self.pairs.clone()
+    }
+
+    fn on_book(&mut self, pair: &Pair, time: DateTime, changes: &[Change]) {
+        if let Ok(symbol) = Symbol::try_from(pair) {
+            for change in changes {
+                self.exchange.update_book(symbol, time, book_order(change));
+            }
+        }
+    }
+
+    fn on_trade(&mut self, trade: &Trade) {
+        let side = match trade.side {
+            marketv2::Side::Buy => paper::Side::Buy,
+            marketv2::Side::Sell => paper::Side::Sell,
+        };
+        self.exchange.trade(
+            trade.symbol,
+            trade.timestamp,
+            side,
+            trade.price,
+            trade.quantity,
+        );
+    }
+
+    fn cancel_all(&mut self) {
+        self.exchange.cancel_all();
+    }
+
+    async fn shutdown(&mut self) {
+        self.exchange.cancel_all();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        algo::{Algorithm, AlgorithmData, MarketData},
+        candle::Candle,
+        live::Runtime,
+        period::Resolution,
+        tick::Tick,
+    };
+    use rust_decimal_macros::dec;
+
+    fn btc() -> Pair {
+        Pair::new("BTC", "USD")
+    }
+
+    #[derive(Default)]
+    struct Data;
+
+    impl MarketData for Data {
+        fn subscriptions() -> Vec<(Pair, Resolution)> {
+            vec![(btc(), Resolution::Tick)]
+        }
+
+        fn update(&mut self, _: &Pair, _: Resolution, _: &Candle) -> bool {
+            false
+        }
+
+        fn update_tick(&mut self, _: &Pair, _: &Tick) -> bool {
+            true
+        }
+    }
+
+    // Bids below the market on the first trade.
+    #[derive(Default)]
+    struct Bidder {
+        placed: bool,
+    }
+
+    impl AlgorithmData for Bidder {
+        type Data = Data;
+    }
+
+    impl Algorithm for Bidder {
+        fn on(&mut self, _: &Data, broker: &mut dyn Broker) {
+            if !self.placed {
+                let order = Order::limit(btc(), Side::Buy, dec!(1), dec!(99));
+                self.placed = broker.submit(order).is_ok();
+            }
+        }
+    }
+
+    #[test]
+    fn test_paper_runtime() {
+        let execution = PaperExecution::new(PaperExchange::new(dec!(1000)), &[btc()]);
+        let mut runtime = Runtime::new(Bidder::default(), execution);
+        assert_eq!(
+            r#"{"type":"subscribe","subscriptions":[{"name":"l2","symbols":["BTCUSD"]}]}"#,
+            serde_json::to_string(&runtime.subscribe().unwrap()).unwrap()
+        );
+
+        for message in &[
+            r#"{"type":"l2_updates","symbol":"BTCUSD","changes":[["buy","99","2"],["sell","101","1"]],"trades":[],"auction_events":[]}"#,
+            r#"{"type":"trade","symbol":"BTCUSD","event_id":1,"timestamp":1000,"price":"101","quantity":"1","side":"buy"}"#,
+            r#"{"type":"trade","symbol":"BTCUSD","event_id":2,"timestamp":2000,"price":"99","quantity":"2.5","side":"sell"}"#,
+        ] {
+            runtime.handle_message(message).unwrap();
+        }
+
+        // the bid queued behind the 2 already at 99
+        let (algo, execution) = runtime.into_parts();
+        assert!(algo.placed);
+        let fills = execution.exchange().fills();
+        assert_eq!(1, fills.len());
+        assert_eq!((dec!(0.5), dec!(99)), (fills[0].quantity, fills[0].price));
+        assert_eq!(dec!(0.5), execution.position(&btc()));
+    }
+
+    #[test]
+    fn test_insufficient_funds() {
+        let mut execution = PaperExecution::new(PaperExchange::new(dec!(100)), &[]);
+        let order = Order::limit(btc(), Side::Buy, dec!(2), dec!(99));
+        assert_eq!(Err(OrderError::InsufficientFunds), execution.submit(order));
+
+        let order = Order::limit(Pair::new("BTC", "JPY"), Side::Buy, dec!(1), dec!(1));
+        assert_eq!(Err(OrderError::UnknownPair), execution.submit(order));
+    }
+}
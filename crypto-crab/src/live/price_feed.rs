@@ -0,0 +1,368 @@
+use crate::decimal::Decimal;
+use exchange_api::Pair;
+use futures::Future;
+use gemini::rest::{
+    client::{Client, Error},
+    price_feed::{PriceFeed, PriceFeeds, PriceFeedsHttpRequest},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// A pair whose price differs from the rate implied through another currency, e.g.
+/// ETHBTC from ETHUSD and BTCUSD. Spreads are computed from last prices, so they only
+/// indicate an opportunity, the book may not offer it.
+///
+/// Each triangle of currencies is reported once, on the pair between the two
+/// currencies other than `via`, where `via` is the one most pairs of the triangle are
+/// quoted in, e.g. on ETHBTC through USD.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Arbitrage {
+    pub pair: Pair,
+    pub via: String,
+    pub price: Decimal,
+    pub implied: Decimal,
+    /// The difference of the implied rate to the price as a fraction of the price,
+    /// positive if the pair is cheaper than the route through `via`.
+    pub spread: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PriceEvent {
+    /// The price of a pair changed, `previous` is `None` when it is first seen.
+    Price {
+        pair: Pair,
+        previous: Option<Decimal>,
+        price: Decimal,
+        percent_change_24h: Decimal,
+    },
+    /// A spread beyond the threshold opened or changed.
+    Arbitrage(Arbitrage),
+    /// The spread of a pair through `via` fell back within the threshold.
+    ArbitrageClosed { pair: Pair, via: String },
+}
+
+/// The latest price of every pair, from which the rates between currencies are implied.
+#[derive(Debug, Clone, Default)]
+pub struct PriceTable {
+    feeds: HashMap<Pair, PriceFeed>,
+}
+
+impl PriceTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, pair: &Pair) -> Option<&PriceFeed> {
+        self.feeds.get(pair)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Pair, &PriceFeed)> {
+        self.feeds.iter()
+    }
+
+    /// Stores a price, returning the one it replaced. Pairs which can't be split into
+    /// their currencies are ignored.
+    pub fn insert(&mut self, feed: PriceFeed) -> Option<PriceFeed> {
+        let (base, quote) = feed.currencies()?;
+        self.feeds.insert(Pair::new(base, quote), feed)
+    }
+
+    fn currencies(&self) -> HashSet<&str> {
+        self.feeds
+            .keys()
+            .flat_map(|pair| [pair.base.as_str(), pair.quote.as_str()])
+            .collect()
+    }
+
+    /// The price of `base` in `quote` from the pair of the two currencies, whichever is
+    /// the base of the pair.
+    pub fn direct_rate(&self, base: &str, quote: &str) -> Option<Decimal> {
+        if base == quote {
+            return Some(Decimal::ONE);
+        }
+        if let Some(feed) = self.feeds.get(&Pair::new(base, quote)) {
+            return Some(feed.price);
+        }
+        let feed = self.feeds.get(&Pair::new(quote, base))?;
+        (!feed.price.is_zero()).then(|| Decimal::ONE / feed.price)
+    }
+
+    /// The price of `base` in `quote` implied through `via`, e.g. of ETH in BTC through
+    /// USD from ETHUSD and BTCUSD.
+    pub fn implied_rate(&self, base: &str, quote: &str, via: &str) -> Option<Decimal> {
+        let (base, quote) = (self.direct_rate(base, via)?, self.direct_rate(quote, via)?);
+        (!quote.is_zero()).then(|| base / quote)
+    }
+
+    /// The price of `base` in `quote`, directly or implied through another currency.
+    pub fn rate(&self, base: &str, quote: &str) -> Option<Decimal> {
+        self.direct_rate(base, quote).or_else(|| {
+            self.currencies()
+                .into_iter()
+                .filter(|via| *via != base && *via != quote)
+                .find_map(|via| self.implied_rate(base, quote, via))
+        })
+    }
+
+    // The currency of `triangle` most of its pairs are quoted in, the first by name on
+    // a tie.
+    fn via<'a>(&self, triangle: [&'a str; 3]) -> &'a str {
+        let quoted = |currency: &str| {
+            self.feeds
+                .keys()
+                .filter(|pair| pair.quote == currency && triangle.contains(&pair.base.as_str()))
+                .count()
+        };
+        let mut triangle = triangle;
+        triangle.sort_unstable();
+        triangle
+            .iter()
+            .copied()
+            .rev()
+            .max_by_key(|currency| quoted(currency))
+            .unwrap()
+    }
+
+    /// The triangles whose price differs by more than `threshold`, as a fraction of the
+    /// price, from the rate implied through the third currency, ordered by pair and
+    /// currency.
+    pub fn arbitrage(&self, threshold: Decimal) -> Vec<Arbitrage> {
+        let currencies = self.currencies();
+        let mut arbitrage = self
+            .feeds
+            .iter()
+            .filter(|(_, feed)| !feed.price.is_zero())
+            .flat_map(|(pair, feed)| {
+                let (base, quote) = (pair.base.as_str(), pair.quote.as_str());
+                currencies.iter().filter_map(move |via| {
+                    if *via == base || *via == quote || self.via([base, quote, via]) != *via {
+                        return None;
+                    }
+                    let implied = self.implied_rate(base, quote, via)?;
+                    let spread = (implied - feed.price) / feed.price;
+                    (spread.abs() > threshold).then(|| Arbitrage {
+                        pair: pair.clone(),
+                        via: via.to_string(),
+                        price: feed.price,
+                        implied,
+                        spread,
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+        arbitrage.sort_by(|a, b| (&a.pair, &a.via).cmp(&(&b.pair, &b.via)));
+        arbitrage
+    }
+}
+
+/// Polls the prices of every pair and sends the changes, and the triangular arbitrage
+/// spreads beyond a threshold, to its subscribers.
+pub struct PriceWatcher {
+    table: PriceTable,
+    interval: Duration,
+    threshold: Decimal,
+    // The spreads beyond the threshold of the last poll.
+    arbitrage: HashMap<(Pair, String), Decimal>,
+    subscribers: Vec<UnboundedSender<PriceEvent>>,
+}
+
+impl Default for PriceWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PriceWatcher {
+    pub fn new() -> Self {
+        Self {
+            table: PriceTable::new(),
+            interval: Duration::from_secs(10),
+            threshold: Decimal::new(5, 3),
+            arbitrage: HashMap::new(),
+            subscribers: Vec::new(),
+        }
+    }
+
+    /// Defaults to 10 seconds.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// The smallest spread reported as arbitrage, as a fraction of the price. Defaults
+    /// to 0.5%.
+    pub fn with_threshold(mut self, threshold: Decimal) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn table(&self) -> &PriceTable {
+        &self.table
+    }
+
+    /// Receives the events of every following poll.
+    pub fn subscribe(&mut self) -> UnboundedReceiver<PriceEvent> {
+        let (sender, receiver) = unbounded_channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Applies the prices of a poll, sending the events to the subscribers and
+    /// returning them.
+    pub fn update(&mut self, feeds: PriceFeeds) -> Vec<PriceEvent> {
+        let mut events = Vec::new();
+        for feed in feeds.feeds {
+            let pair = match feed.currencies() {
+                Some((base, quote)) => Pair::new(base, quote),
+                None => continue,
+            };
+            let (price, percent_change_24h) = (feed.price, feed.percent_change_24h);
+            let previous = self.table.insert(feed).map(|previous| previous.price);
+            if previous != Some(price) {
+                events.push(PriceEvent::Price {
+                    pair,
+                    previous,
+                    price,
+                    percent_change_24h,
+                });
+            }
+        }
+
+        let arbitrage = self.table.arbitrage(self.threshold);
+        let mut closed = self.arbitrage.keys().cloned().collect::<HashSet<_>>();
+        for arbitrage in arbitrage {
+            let key = (arbitrage.pair.clone(), arbitrage.via.clone());
+            closed.remove(&key);
+            if self.arbitrage.insert(key, arbitrage.spread) != Some(arbitrage.spread) {
+                events.push(PriceEvent::Arbitrage(arbitrage));
+            }
+        }
+        let mut closed = closed.into_iter().collect::<Vec<_>>();
+        closed.sort();
+        for (pair, via) in closed {
+            self.arbitrage.remove(&(pair.clone(), via.clone()));
+            events.push(PriceEvent::ArbitrageClosed { pair, via });
+        }
+
+        self.subscribers.retain(|subscriber| {
+            events
+                .iter()
+                .all(|event| subscriber.send(event.clone()).is_ok())
+        });
+        events
+    }
+
+    /// Polls the prices every interval until `shutdown` completes or a request fails.
+    pub async fn run(
+        &mut self,
+        client: &Client,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), Error> {
+        futures::pin_mut!(shutdown);
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => return Ok(()),
+                _ = interval.tick() => {
+                    let feeds = client.request(PriceFeedsHttpRequest).await?;
+                    self.update(feeds);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn feeds(btc: &str, eth: &str, ethbtc: &str) -> PriceFeeds {
+        let json = format!(
+            r#"[
+                {{"pair":"BTCUSD","price":"{}","percentChange24h":"0.0100"}},
+                {{"pair":"ETHUSD","price":"{}","percentChange24h":"-0.0200"}},
+                {{"pair":"ETHBTC","price":"{}","percentChange24h":"0.0000"}},
+                {{"pair":"ZECLTC","price":"1","percentChange24h":"0.0000"}}
+            ]"#,
+            btc, eth, ethbtc
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_rates() {
+        let mut table = PriceTable::new();
+        let feeds = feeds("40000", "2000", "0.05");
+        for feed in feeds.feeds {
+            table.insert(feed);
+        }
+        // pairs without a symbol are kept too
+        assert_eq!(4, table.iter().count());
+        assert_eq!(dec!(1), table.get(&Pair::new("ZEC", "LTC")).unwrap().price);
+
+        assert_eq!(Some(dec!(0.000025)), table.direct_rate("USD", "BTC"));
+        assert_eq!(Some(dec!(0.05)), table.implied_rate("ETH", "BTC", "USD"));
+        assert_eq!(Some(dec!(2000)), table.implied_rate("ETH", "USD", "BTC"));
+        assert!(table.arbitrage(dec!(0)).is_empty());
+
+        // without the pair the rate is implied
+        table.feeds.remove(&Pair::new("ETH", "BTC"));
+        assert_eq!(Some(dec!(0.05)), table.rate("ETH", "BTC"));
+        assert_eq!(None, table.direct_rate("ETH", "BTC"));
+    }
+
+    #[test]
+    fn test_watcher() {
+        let mut watcher = PriceWatcher::new().with_threshold(dec!(0.01));
+        let mut receiver = watcher.subscribe();
+
+        let events = watcher.update(feeds("40000", "2000", "0.05"));
+        assert_eq!(4, events.len());
+        assert_eq!(
+            PriceEvent::Price {
+                pair: Pair::new("BTC", "USD"),
+                previous: None,
+                price: dec!(40000),
+                percent_change_24h: dec!(0.01),
+            },
+            events[0]
+        );
+        assert!(watcher.update(feeds("40000", "2000", "0.05")).is_empty());
+
+        // ETHBTC is 2% cheaper than buying ether with dollars bought with bitcoin, which
+        // is reported once for the triangle, on the pair not quoted in dollars
+        let events = watcher.update(feeds("40000", "2000", "0.049"));
+        assert_eq!(2, events.len());
+        match &events[1] {
+            PriceEvent::Arbitrage(arbitrage) => assert_eq!(
+                (Pair::new("ETH", "BTC"), "USD", dec!(0.0204)),
+                (
+                    arbitrage.pair.clone(),
+                    arbitrage.via.as_str(),
+                    arbitrage.spread.round_dp(4)
+                )
+            ),
+            event => panic!("unexpected {:?}", event),
+        }
+
+        let events = watcher.update(feeds("40000", "2000", "0.0499"));
+        assert_eq!(2, events.len());
+        assert_eq!(
+            PriceEvent::ArbitrageClosed {
+                pair: Pair::new("ETH", "BTC"),
+                via: "USD".to_string(),
+            },
+            events[1]
+        );
+
+        let mut received = 0;
+        while receiver.try_recv().is_ok() {
+            received += 1;
+        }
+        assert_eq!(4 + 2 + 2, received);
+    }
+}
@@ -0,0 +1,625 @@
+use super::execution::Execution;
+use crate::{
+    algo::{Event, Pair},
+    backtest::exchange::{FeeModel, PercentFee},
+    decimal::Decimal,
+    order::{validate, Broker, Fill, Liquidity, Order, OrderError, OrderId, OrderKind, Side},
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use gemini::{
+    error::ErrorReason,
+    rest::{
+        client::{Client, Error},
+        order::{
+            self, CancelOrderRequest, ClientOrderStatusRequest, NewOrderRequest, OrderOption,
+            OrderStatus, OrderStatusRequest,
+        },
+    },
+    symbol::Symbol,
+};
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    convert::TryFrom,
+    hash::{BuildHasher, Hasher},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+};
+
+struct LiveOrder {
+    order: Order,
+    // The id assigned by the exchange once the order was acknowledged.
+    exchange_id: Option<u64>,
+    executed: Decimal,
+    // The executed quantity times the average execution price.
+    executed_notional: Decimal,
+    cancel_requested: bool,
+    status_pending: bool,
+    // The error of placing the order if it failed without an answer from Gemini, so the
+    // order may be live anyway. Cleared once the order is found by its client order id.
+    unconfirmed: Option<Error>,
+}
+
+enum Response {
+    Status(Result<OrderStatus, Error>),
+    // The orders with the client order id of an unconfirmed order.
+    Lookup(Result<Vec<OrderStatus>, Error>),
+}
+
+/// Executes orders through Gemini's private REST API.
+///
+/// Requests are sent in the background, so `submit` returns a local id right away
+/// and fills are reported once `poll` processes the responses. Open orders are
+/// polled for their status every `poll_interval`. Market orders are sent as
+/// immediate-or-cancel limit orders priced `protection` away from the last price.
+///
+/// Orders are only rejected if Gemini answers with an error. If placing an order fails
+/// otherwise, e.g. it times out, the order is looked up by its client order id until
+/// it is found or known to not exist. Client order ids are the order ids prefixed with
+/// a random session id, so they don't collide with the orders of earlier runs or other
+/// executions on the account.
+///
+/// Must be used from within a tokio runtime.
+pub struct RestExecution {
+    client: Arc<Client>,
+    cash: Decimal,
+    positions: HashMap<Pair, Decimal>,
+    prices: HashMap<Pair, Decimal>,
+    protection: Decimal,
+    // The estimated fees, since order statuses don't include them.
+    fees: PercentFee,
+    poll_interval: Duration,
+    last_poll: Option<Instant>,
+    // Prefixes the client order ids.
+    session: String,
+    next_id: OrderId,
+    orders: HashMap<OrderId, LiveOrder>,
+    fills: Vec<Fill>,
+    rejections: Vec<(OrderId, Error)>,
+    sender: UnboundedSender<(OrderId, Response)>,
+    receiver: UnboundedReceiver<(OrderId, Response)>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl RestExecution {
+    /// `client` must have credentials. `cash` is the quote currency balance
+    /// available to the algorithm.
+    pub fn new(client: Client, cash: Decimal) -> Self {
+        let (sender, receiver) = unbounded_channel();
+        Self {
+            client: Arc::new(client),
+            cash,
+            positions: HashMap::new(),
+            prices: HashMap::new(),
+            protection: Decimal::new(1, 2),
+            fees: PercentFee::default(),
+            poll_interval: Duration::from_secs(1),
+            last_poll: None,
+            session: format!("{:016x}", RandomState::new().build_hasher().finish()),
+            next_id: 0,
+            orders: HashMap::new(),
+            fills: Vec::new(),
+            rejections: Vec::new(),
+            sender,
+            receiver,
+            tasks: Vec::new(),
+        }
+    }
+
+    pub fn with_position(mut self, pair: Pair, quantity: Decimal) -> Self {
+        self.positions.insert(pair, quantity);
+        self
+    }
+
+    /// The fraction of the last price market orders may execute away from it. Defaults to 1%.
+    pub fn with_protection(mut self, protection: Decimal) -> Self {
+        self.protection = protection;
+        self
+    }
+
+    pub fn with_fees(mut self, fees: PercentFee) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn fills(&self) -> &[Fill] {
+        &self.fills
+    }
+
+    /// Removes and returns the orders which were rejected by the exchange.
+    pub fn take_rejections(&mut self) -> Vec<(OrderId, Error)> {
+        std::mem::take(&mut self.rejections)
+    }
+
+    pub fn open_orders(&self) -> impl Iterator<Item = (OrderId, &Order)> {
+        self.orders.iter().map(|(id, order)| (*id, &order.order))
+    }
+
+    fn client_order_id(&self, id: OrderId) -> String {
+        format!("{}-{}", self.session, id)
+    }
+
+    fn spawn<R>(&mut self, id: OrderId, req: R)
+    where
+        R: gemini::rest::private::PrivateHttpRequest<Response = OrderStatus> + Send + 'static,
+    {
+        let (client, sender) = (self.client.clone(), self.sender.clone());
+        self.tasks.push(tokio::spawn(async move {
+            let response = Response::Status(client.private_request(req).await);
+            let _ = sender.send((id, response));
+        }));
+    }
+
+    fn lookup(&mut self, id: OrderId) {
+        if let Some(order) = self.orders.get_mut(&id) {
+            order.status_pending = true;
+        }
+        let req = ClientOrderStatusRequest::builder()
+            .client_order_id(self.client_order_id(id))
+            .build();
+        let (client, sender) = (self.client.clone(), self.sender.clone());
+        self.tasks.push(tokio::spawn(async move {
+            let response = Response::Lookup(client.private_request(req).await);
+            let _ = sender.send((id, response));
+        }));
+    }
+
+    fn cancel_on_exchange(&mut self, id: OrderId) {
+        let exchange_id = match self.orders.get(&id) {
+            Some(LiveOrder {
+                exchange_id: Some(exchange_id),
+                ..
+            }) => *exchange_id,
+            _ => return,
+        };
+        self.spawn(
+            id,
+            CancelOrderRequest::builder().order_id(exchange_id).build(),
+        );
+    }
+
+    fn process(&mut self, id: OrderId, status: OrderStatus) {
+        let order = match self.orders.get_mut(&id) {
+            Some(order) => order,
+            None => return,
+        };
+        let acknowledged = order.exchange_id.replace(status.order_id).is_none();
+        order.status_pending = false;
+
+        let quantity = status.executed_amount - order.executed;
+        if quantity > Decimal::ZERO {
+            let notional = status.executed_amount * status.avg_execution_price;
+            let price = (notional - order.executed_notional) / quantity;
+            order.executed = status.executed_amount;
+            order.executed_notional = notional;
+
+            let (pair, side) = (order.order.pair.clone(), order.order.side);
+            let liquidity = match order.order.kind {
+                OrderKind::Market => Liquidity::Taker,
+                OrderKind::Limit(_) if acknowledged => Liquidity::Taker,
+                OrderKind::Limit(_) => Liquidity::Maker,
+            };
+            let fee = self.fees.fee(quantity * price, liquidity);
+
+            self.cash -= side.sign() * quantity * price + fee;
+            *self.positions.entry(pair.clone()).or_default() += side.sign() * quantity;
+            self.fills.push(Fill {
+                order_id: id,
+                time: Utc::now(),
+                pair,
+                side,
+                quantity,
+                price,
+                fee,
+                liquidity,
+            });
+        }
+
+        if !status.is_live {
+            self.orders.remove(&id);
+        } else if acknowledged && self.orders[&id].cancel_requested {
+            self.cancel_on_exchange(id);
+        }
+    }
+
+    fn fail(&mut self, id: OrderId, error: Error) {
+        let order = match self.orders.get_mut(&id) {
+            Some(order) => order,
+            None => return,
+        };
+        order.status_pending = false;
+        // failed requests for acknowledged orders are repeated by the status polls
+        if order.exchange_id.is_some() {
+            return;
+        }
+        match error {
+            Error::Gemini(_) => self.reject(id, error),
+            error => order.unconfirmed = Some(error),
+        }
+    }
+
+    fn found(&mut self, id: OrderId, result: Result<Vec<OrderStatus>, Error>) {
+        let client_order_id = self.client_order_id(id);
+        let order = match self.orders.get_mut(&id) {
+            Some(order) => order,
+            None => return,
+        };
+        order.status_pending = false;
+
+        let status = match result {
+            Ok(statuses) => statuses
+                .into_iter()
+                .find(|status| status.client_order_id.as_ref() == Some(&client_order_id)),
+            Err(Error::Gemini(e)) if matches!(e.reason(), ErrorReason::OrderNotFound) => None,
+            // looked up again by the next status poll
+            Err(_) => return,
+        };
+        match status {
+            Some(status) => {
+                order.unconfirmed = None;
+                self.process(id, status);
+            }
+            // the order never reached Gemini
+            None => {
+                if let Some(error) = order.unconfirmed.take() {
+                    self.reject(id, error);
+                }
+            }
+        }
+    }
+
+    fn reject(&mut self, id: OrderId, error: Error) {
+        self.orders.remove(&id);
+        self.rejections.push((id, error));
+    }
+
+    fn market_price(&self, order: &Order, symbol: Symbol) -> Result<Decimal, OrderError> {
+        let price = self.prices.get(&order.pair).ok_or(OrderError::Unpriced)?;
+        let tick = symbol.tick_size().ok_or(OrderError::Unpriced)?;
+
+        // rounded to the tick size, away from the last price
+        Ok(match order.side {
+            Side::Buy => (price * (Decimal::ONE + self.protection) / tick).ceil() * tick,
+            Side::Sell => (price * (Decimal::ONE - self.protection) / tick).floor() * tick,
+        })
+    }
+}
+
+impl Broker for RestExecution {
+    fn submit(&mut self, order: Order) -> Result<OrderId, OrderError> {
+        validate(&order)?;
+        let symbol = Symbol::try_from(&order.pair).map_err(|_| OrderError::UnknownPair)?;
+        let (price, options) = match order.kind {
+            OrderKind::Limit(price) => (price, vec![]),
+            OrderKind::Market => (
+                self.market_price(&order, symbol)?,
+                vec![OrderOption::ImmediateOrCancel],
+            ),
+        };
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let req = NewOrderRequest::builder()
+            .client_order_id(self.client_order_id(id))
+            .symbol(symbol)
+            .amount(order.quantity)
+            .price(price)
+            .side(match order.side {
+                Side::Buy => order::Side::Buy,
+                Side::Sell => order::Side::Sell,
+            })
+            .options(options)
+            .build();
+        self.spawn(id, req);
+
+        self.orders.insert(
+            id,
+            LiveOrder {
+                order,
+                exchange_id: None,
+                executed: Decimal::ZERO,
+                executed_notional: Decimal::ZERO,
+                cancel_requested: false,
+                status_pending: true,
+                unconfirmed: None,
+            },
+        );
+        Ok(id)
+    }
+
+    fn cancel(&mut self, id: OrderId) -> bool {
+        match self.orders.get_mut(&id) {
+            Some(order) if !order.cancel_requested => {
+                order.cancel_requested = true;
+                self.cancel_on_exchange(id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn position(&self, pair: &Pair) -> Decimal {
+        self.positions.get(pair).copied().unwrap_or_default()
+    }
+
+    fn cash(&self) -> Decimal {
+        self.cash
+    }
+
+    fn open_order_count(&self) -> usize {
+        self.orders.len()
+    }
+
+    fn open_quantity(&self, pair: &Pair) -> Decimal {
+        self.orders
+            .values()
+            .filter(|live| live.order.pair == *pair)
+            .map(|live| live.order.side.sign() * (live.order.quantity - live.executed))
+            .sum()
+    }
+}
+
+#[async_trait]
+impl Execution for RestExecution {
+    fn on_event(&mut self, event: &Event) {
+        let price = match event {
+            Event::Candle(_, _, candle) => candle.close,
+            Event::Tick(_, tick) => tick.price,
+        };
+        self.prices.insert(event.pair().clone(), price);
+    }
+
+    fn poll(&mut self) {
+        while let Ok((id, response)) = self.receiver.try_recv() {
+            match response {
+                Response::Status(Ok(status)) => self.process(id, status),
+                Response::Status(Err(e)) => self.fail(id, e),
+                Response::Lookup(result) => self.found(id, result),
+            }
+        }
+        self.tasks.retain(|task| !task.is_finished());
+
+        let now = Instant::now();
+        if matches!(self.last_poll, Some(last) if now - last < self.poll_interval) {
+            return;
+        }
+        self.last_poll = Some(now);
+
+        let ids = self
+            .orders
+            .iter()
+            .filter(|(_, order)| !order.status_pending)
+            .filter(|(_, order)| order.exchange_id.is_some() || order.unconfirmed.is_some())
+            .map(|(id, order)| (*id, order.exchange_id))
+            .collect::<Vec<_>>();
+        for (id, exchange_id) in ids {
+            match exchange_id {
+                Some(exchange_id) => {
+                    if let Some(order) = self.orders.get_mut(&id) {
+                        order.status_pending = true;
+                    }
+                    self.spawn(
+                        id,
+                        OrderStatusRequest::builder().order_id(exchange_id).build(),
+                    );
+                }
+                None => self.lookup(id),
+            }
+        }
+    }
+
+    fn cancel_all(&mut self) {
+        let ids = self.orders.keys().copied().collect::<Vec<_>>();
+        for id in ids {
+            self.cancel(id);
+        }
+    }
+
+    async fn shutdown(&mut self) {
+        self.cancel_all();
+
+        // acknowledgements which arrive in the meantime trigger further cancellations,
+        // unconfirmed orders are looked up once to cancel them if they were placed
+        let mut looked_up = false;
+        loop {
+            while !self.tasks.is_empty() {
+                futures::future::join_all(self.tasks.drain(..)).await;
+                self.last_poll = Some(Instant::now());
+                self.poll();
+            }
+
+            let unconfirmed = self
+                .orders
+                .iter()
+                .filter(|(_, order)| order.unconfirmed.is_some())
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>();
+            if looked_up || unconfirmed.is_empty() {
+                break;
+            }
+            looked_up = true;
+            for id in unconfirmed {
+                self.lookup(id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{candle::Candle, period::Resolution};
+    use chrono::TimeZone;
+    use gemini::rest::private::Credentials;
+    use rust_decimal_macros::dec;
+
+    fn btc() -> Pair {
+        Pair::new("BTC", "USD")
+    }
+
+    fn execution() -> RestExecution {
+        // nothing listens on the discard port, so every request fails
+        let client = Client::new("http://127.0.0.1:9".parse().unwrap())
+            .with_credentials(Credentials::new("key", "secret"));
+        RestExecution::new(client, dec!(1000))
+    }
+
+    #[tokio::test]
+    async fn test_market_order_price() {
+        let mut execution = execution();
+        let order = Order::market(btc(), Side::Buy, dec!(1));
+        assert_eq!(Err(OrderError::Unpriced), execution.submit(order.clone()));
+        let yen = Order::market(Pair::new("BTC", "JPY"), Side::Buy, dec!(1));
+        assert_eq!(Err(OrderError::UnknownPair), execution.submit(yen));
+
+        let candle = Candle {
+            time: Utc.timestamp_opt(0, 0).unwrap(),
+            open: dec!(100),
+            high: dec!(100),
+            low: dec!(100),
+            close: dec!(100.005),
+            volume: dec!(1),
+        };
+        execution.on_event(&Event::Candle(btc(), Resolution::Minute, candle.clone()));
+
+        let market_price = |order: &Order| {
+            let symbol = Symbol::try_from(&order.pair).unwrap();
+            execution.market_price(order, symbol)
+        };
+        assert_eq!(Ok(dec!(101.01)), market_price(&order));
+        let sell = Order::market(btc(), Side::Sell, dec!(1));
+        assert_eq!(Ok(dec!(99.00)), market_price(&sell));
+
+        let candle = Candle {
+            close: dec!(0.0612345),
+            ..candle
+        };
+        let eth = Pair::new("ETH", "BTC");
+        execution.on_event(&Event::Candle(eth.clone(), Resolution::Minute, candle));
+        let order = Order::market(eth, Side::Buy, dec!(1));
+        assert_eq!(
+            Ok(dec!(0.06185)),
+            execution.market_price(&order, Symbol::ETHBTC)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unconfirmed_order() {
+        let mut execution = execution();
+        let id = execution
+            .submit(Order::limit(btc(), Side::Buy, dec!(1), dec!(100)))
+            .unwrap();
+        assert_eq!(1, execution.open_orders().count());
+
+        // the order may have been placed, so it's kept until it's found
+        execution.shutdown().await;
+        assert_eq!(1, execution.open_orders().count());
+        assert!(execution.take_rejections().is_empty());
+        assert!(matches!(
+            execution.orders[&id].unconfirmed,
+            Some(Error::Reqwest(_))
+        ));
+        assert_eq!(dec!(1000), execution.cash());
+    }
+
+    #[test]
+    fn test_rejected_order() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+
+        let mut execution = execution();
+        let order = Order::limit(btc(), Side::Buy, dec!(1), dec!(100));
+        let rejected = execution.submit(order.clone()).unwrap();
+        let unconfirmed = execution.submit(order).unwrap();
+
+        let error = serde_json::from_str(
+            r#"{"result":"error","reason":"InsufficientFunds","message":"Insufficient funds"}"#,
+        )
+        .unwrap();
+        execution.fail(rejected, Error::Gemini(error));
+        execution.fail(unconfirmed, Error::MissingCredentials);
+        let rejections = execution.take_rejections();
+        assert_eq!(1, rejections.len());
+        assert_eq!(rejected, rejections[0].0);
+
+        // looked up, but it never reached Gemini
+        execution.found(unconfirmed, Ok(vec![]));
+        assert_eq!(0, execution.open_orders().count());
+        let rejections = execution.take_rejections();
+        assert_eq!(unconfirmed, rejections[0].0);
+        assert!(matches!(rejections[0].1, Error::MissingCredentials));
+    }
+
+    #[test]
+    fn test_process_fills() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+
+        let mut execution = execution();
+        let id = execution
+            .submit(Order::limit(btc(), Side::Buy, dec!(2), dec!(100)))
+            .unwrap();
+
+        let client_order_id = execution.client_order_id(id);
+        let status = |executed: Decimal, avg: Decimal, is_live: bool| OrderStatus {
+            order_id: 42,
+            client_order_id: Some(client_order_id.clone()),
+            symbol: Symbol::BTCUSD,
+            side: order::Side::Buy,
+            ty: order::OrderType::ExchangeLimit,
+            timestamp: Utc.timestamp_opt(0, 0).unwrap(),
+            is_live,
+            is_cancelled: false,
+            price: Some(dec!(100)),
+            avg_execution_price: avg,
+            original_amount: dec!(2),
+            executed_amount: executed,
+            remaining_amount: dec!(2) - executed,
+            options: vec![],
+        };
+
+        // the response is lost, but the order is found by its client order id, not
+        // mistaken for an order of an earlier run with the same order id
+        let earlier = OrderStatus {
+            order_id: 7,
+            client_order_id: Some(format!("{:016x}-{}", 0, id)),
+            ..status(dec!(2), dec!(50), false)
+        };
+        let unprefixed = OrderStatus {
+            order_id: 8,
+            client_order_id: Some(id.to_string()),
+            ..earlier.clone()
+        };
+        execution.fail(id, Error::MissingCredentials);
+        execution.found(
+            id,
+            Ok(vec![earlier, unprefixed, status(dec!(0.5), dec!(99), true)]),
+        );
+        assert!(execution.take_rejections().is_empty());
+        assert_eq!(Some(42), execution.orders[&id].exchange_id);
+
+        // partially filled right away, then the remainder rests on the book
+        execution.process(id, status(dec!(2), dec!(99.75), false));
+
+        let fills = execution.fills();
+        assert_eq!(2, fills.len());
+        assert_eq!((dec!(0.5), dec!(99)), (fills[0].quantity, fills[0].price));
+        assert_eq!(Liquidity::Taker, fills[0].liquidity);
+        assert_eq!((dec!(1.5), dec!(100)), (fills[1].quantity, fills[1].price));
+        assert_eq!(Liquidity::Maker, fills[1].liquidity);
+
+        assert_eq!(dec!(2), execution.position(&btc()));
+        assert_eq!(dec!(800.5), execution.cash());
+        assert_eq!(0, execution.open_orders().count());
+    }
+}
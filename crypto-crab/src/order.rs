@@ -0,0 +1,137 @@
+use crate::{algo::Pair, decimal::Decimal, risk::Violation, time::DateTime};
+use serde::Serialize;
+
+pub type OrderId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    /// `1` for buys and `-1` for sells, i.e. the sign of the position change.
+    pub fn sign(self) -> Decimal {
+        match self {
+            Self::Buy => Decimal::ONE,
+            Self::Sell => Decimal::NEGATIVE_ONE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderKind {
+    Market,
+    Limit(Decimal),
+}
+
+/// An order an algorithm wants to place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Order {
+    pub pair: Pair,
+    pub side: Side,
+    pub quantity: Decimal,
+    pub kind: OrderKind,
+}
+
+impl Order {
+    pub fn market(pair: Pair, side: Side, quantity: Decimal) -> Self {
+        Self {
+            pair,
+            side,
+            quantity,
+            kind: OrderKind::Market,
+        }
+    }
+
+    pub fn limit(pair: Pair, side: Side, quantity: Decimal, price: Decimal) -> Self {
+        Self {
+            pair,
+            side,
+            quantity,
+            kind: OrderKind::Limit(price),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Liquidity {
+    Maker,
+    Taker,
+}
+
+/// An execution of (part of) an order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fill {
+    pub order_id: OrderId,
+    pub time: DateTime,
+    pub pair: Pair,
+    pub side: Side,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    pub fee: Decimal,
+    pub liquidity: Liquidity,
+}
+
+impl Fill {
+    pub fn notional(&self) -> Decimal {
+        self.quantity * self.price
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    /// The quantity was zero or negative.
+    InvalidQuantity,
+    /// The limit price was zero or negative.
+    InvalidPrice,
+    /// A market order was placed before any price of the pair was known.
+    Unpriced,
+    /// The pair isn't traded on the exchange.
+    UnknownPair,
+    /// The broker doesn't accept orders at the moment, e.g. while an algorithm's
+    /// data is warmed up or after the kill switch was engaged.
+    Halted,
+    /// The balance not already committed to open orders doesn't cover the order.
+    InsufficientFunds,
+    /// A maker-or-cancel order would have taken liquidity on arrival.
+    WouldTake,
+    /// The order failed the pre-trade risk checks.
+    Risk(Violation),
+}
+
+/// Accepts the orders of an algorithm and keeps track of its holdings.
+///
+/// Implemented by the backtester's simulated exchange and by live execution backends,
+/// so an algorithm runs unchanged against either.
+pub trait Broker {
+    fn submit(&mut self, order: Order) -> Result<OrderId, OrderError>;
+
+    /// Cancels an open order. Returns `false` if the order is unknown or no longer open.
+    fn cancel(&mut self, id: OrderId) -> bool;
+
+    /// The signed quantity held of `pair`, negative when short.
+    fn position(&self, pair: &Pair) -> Decimal;
+
+    /// The quote currency balance.
+    fn cash(&self) -> Decimal;
+
+    /// The number of orders which were submitted and are neither filled nor cancelled.
+    fn open_order_count(&self) -> usize;
+
+    /// The signed remaining quantity of the open orders of `pair`, i.e. the change
+    /// of the position if they were all filled.
+    fn open_quantity(&self, pair: &Pair) -> Decimal;
+}
+
+/// Checks the parts of an order that are invalid regardless of the broker.
+pub fn validate(order: &Order) -> Result<(), OrderError> {
+    if order.quantity <= Decimal::ZERO {
+        return Err(OrderError::InvalidQuantity);
+    }
+
+    match order.kind {
+        OrderKind::Limit(price) if price <= Decimal::ZERO => Err(OrderError::InvalidPrice),
+        _ => Ok(()),
+    }
+}
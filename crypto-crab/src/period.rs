@@ -27,3 +27,7 @@ duration_impl!(Second, Duration::seconds(1));
 duration_impl!(Minute, Duration::minutes(1));
 duration_impl!(Hour, Duration::hours(1));
 duration_impl!(Day, Duration::days(1));
+
+/// The granularity of the market data an algorithm subscribes to, shared with the
+/// REST and market data APIs.
+pub use gemini::resolution::Resolution;
@@ -0,0 +1,512 @@
+use crate::{
+    decimal::Decimal,
+    order::{Fill, Side},
+    time::DateTime,
+};
+use gemini::{
+    currency::Currency,
+    rest::{my_trades::MyTrade, ticker},
+    symbol::Symbol,
+};
+use market::{order_book::OrderBook, paper};
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::TryFrom,
+};
+
+/// How a sale is matched against the lots a position was built up from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LotMethod {
+    /// The oldest lots are closed first.
+    Fifo,
+    /// The newest lots are closed first.
+    Lifo,
+    /// All lots are merged into one at their average price.
+    Average,
+}
+
+/// A single execution which changes the holdings of a portfolio.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub time: DateTime,
+    pub symbol: Symbol,
+    pub side: Side,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    pub fee: Decimal,
+    /// The currency the fee was charged in, or `None` for the quote currency.
+    pub fee_currency: Option<Currency>,
+}
+
+impl From<&Fill> for Transaction {
+    /// Fills of pairs Gemini doesn't trade are booked under `Symbol::Unknown`.
+    fn from(fill: &Fill) -> Self {
+        Self {
+            time: fill.time,
+            symbol: Symbol::try_from(&fill.pair).unwrap_or(Symbol::Unknown),
+            side: fill.side,
+            quantity: fill.quantity,
+            price: fill.price,
+            fee: fill.fee,
+            fee_currency: None,
+        }
+    }
+}
+
+impl From<&paper::Fill> for Transaction {
+    fn from(fill: &paper::Fill) -> Self {
+        Self {
+            time: fill.time,
+            symbol: fill.symbol,
+            side: match fill.side {
+                paper::Side::Buy => Side::Buy,
+                paper::Side::Sell => Side::Sell,
+            },
+            quantity: fill.quantity,
+            price: fill.price,
+            fee: fill.fee,
+            fee_currency: None,
+        }
+    }
+}
+
+impl From<&MyTrade> for Transaction {
+    fn from(trade: &MyTrade) -> Self {
+        use gemini::rest::my_trades::Side as TradeSide;
+
+        Self {
+            time: trade.timestamp,
+            symbol: trade.symbol,
+            side: match trade.side {
+                TradeSide::Buy => Side::Buy,
+                TradeSide::Sell => Side::Sell,
+            },
+            quantity: trade.amount,
+            price: trade.price,
+            fee: trade.fee_amount,
+            fee_currency: Some(trade.fee_currency),
+        }
+    }
+}
+
+/// A part of a position acquired at a single price. Short lots have a negative quantity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lot {
+    pub time: DateTime,
+    pub quantity: Decimal,
+    pub price: Decimal,
+}
+
+/// The holdings of a single symbol.
+#[derive(Debug, Clone, Default)]
+pub struct Position {
+    lots: VecDeque<Lot>,
+    realized: Decimal,
+    fees: Decimal,
+}
+
+impl Position {
+    /// The net quantity held, negative for short positions.
+    pub fn quantity(&self) -> Decimal {
+        self.lots.iter().map(|lot| lot.quantity).sum()
+    }
+
+    pub fn is_flat(&self) -> bool {
+        self.lots.is_empty()
+    }
+
+    /// The open lots, oldest first.
+    pub fn lots(&self) -> impl Iterator<Item = &Lot> {
+        self.lots.iter()
+    }
+
+    /// The total price paid for the open lots, negative for short positions.
+    pub fn cost_basis(&self) -> Decimal {
+        self.lots.iter().map(|lot| lot.quantity * lot.price).sum()
+    }
+
+    /// The average price of the open lots, or `None` if the position is flat.
+    pub fn average_cost(&self) -> Option<Decimal> {
+        let quantity = self.quantity();
+        (!quantity.is_zero()).then(|| self.cost_basis() / quantity)
+    }
+
+    /// The profit of the closed lots, before fees.
+    pub fn realized_pnl(&self) -> Decimal {
+        self.realized
+    }
+
+    /// The profit of the open lots if they were closed at `mark`, before fees.
+    pub fn unrealized_pnl(&self, mark: Decimal) -> Decimal {
+        self.lots
+            .iter()
+            .map(|lot| lot.quantity * (mark - lot.price))
+            .sum()
+    }
+
+    /// The fees paid in the quote currency, or converted to it at the price of the
+    /// execution if they were charged in the base currency. Fees charged in another
+    /// currency are converted at its mark when they're paid and left out if it isn't
+    /// marked.
+    pub fn fees(&self) -> Decimal {
+        self.fees
+    }
+
+    fn apply(&mut self, method: LotMethod, time: DateTime, quantity: Decimal, price: Decimal) {
+        let mut remaining = quantity;
+
+        while !remaining.is_zero() {
+            let lot = match method {
+                LotMethod::Fifo | LotMethod::Average => self.lots.front_mut(),
+                LotMethod::Lifo => self.lots.back_mut(),
+            };
+            let lot = match lot {
+                Some(lot) if lot.quantity.is_sign_positive() != remaining.is_sign_positive() => lot,
+                _ => break,
+            };
+
+            // the quantity closed, with the sign of the lot
+            let closed = if lot.quantity.abs() <= remaining.abs() {
+                lot.quantity
+            } else {
+                -remaining
+            };
+            self.realized += closed * (price - lot.price);
+            lot.quantity -= closed;
+            remaining += closed;
+
+            if lot.quantity.is_zero() {
+                match method {
+                    LotMethod::Fifo | LotMethod::Average => self.lots.pop_front(),
+                    LotMethod::Lifo => self.lots.pop_back(),
+                };
+            }
+        }
+
+        if remaining.is_zero() {
+            return;
+        }
+        match (method, self.lots.front_mut()) {
+            (LotMethod::Average, Some(lot)) => {
+                let total = lot.quantity + remaining;
+                lot.price = (lot.quantity * lot.price + remaining * price) / total;
+                lot.quantity = total;
+            }
+            _ => self.lots.push_back(Lot {
+                time,
+                quantity: remaining,
+                price,
+            }),
+        }
+    }
+}
+
+/// Positions, P&L and cash balances built up from executions.
+///
+/// Every transaction moves its quantity between the balances of the base and quote
+/// currencies of its symbol and pays its fee from the balance of the fee currency.
+/// Balances may become negative, e.g. for short positions.
+#[derive(Debug, Clone)]
+pub struct Portfolio {
+    method: LotMethod,
+    balances: HashMap<Currency, Decimal>,
+    positions: HashMap<Symbol, Position>,
+    marks: HashMap<Symbol, Decimal>,
+}
+
+impl Portfolio {
+    pub fn new(method: LotMethod) -> Self {
+        Self {
+            method,
+            balances: HashMap::new(),
+            positions: HashMap::new(),
+            marks: HashMap::new(),
+        }
+    }
+
+    pub fn with_balance(mut self, currency: impl Into<Currency>, amount: Decimal) -> Self {
+        *self.balances.entry(currency.into()).or_default() += amount;
+        self
+    }
+
+    pub fn method(&self) -> LotMethod {
+        self.method
+    }
+
+    /// Books an execution. Transactions of unknown symbols only update the position.
+    pub fn apply(&mut self, transaction: impl Into<Transaction>) {
+        let tx = transaction.into();
+        let quantity = match tx.side {
+            Side::Buy => tx.quantity,
+            Side::Sell => -tx.quantity,
+        };
+        let (base, quote) = (tx.symbol.base(), tx.symbol.quote());
+        let fee_currency = tx.fee_currency.or(quote);
+
+        let fee = match (fee_currency, quote) {
+            (Some(currency), _) if Some(currency) == base => Some(tx.fee * tx.price),
+            (Some(currency), Some(quote)) if currency != quote => {
+                self.rate(currency, quote).map(|rate| tx.fee * rate)
+            }
+            _ => Some(tx.fee),
+        };
+
+        let position = self.positions.entry(tx.symbol).or_default();
+        position.apply(self.method, tx.time, quantity, tx.price);
+        position.fees += fee.unwrap_or_default();
+
+        if let (Some(base), Some(quote)) = (base, quote) {
+            *self.balances.entry(base).or_default() += quantity;
+            *self.balances.entry(quote).or_default() -= quantity * tx.price;
+        }
+        if let Some(currency) = fee_currency {
+            *self.balances.entry(currency).or_default() -= tx.fee;
+        }
+    }
+
+    pub fn balance(&self, currency: impl Into<Currency>) -> Decimal {
+        self.balances
+            .get(&currency.into())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn balances(&self) -> impl Iterator<Item = (Currency, Decimal)> + '_ {
+        self.balances
+            .iter()
+            .map(|(currency, amount)| (*currency, *amount))
+    }
+
+    pub fn position(&self, symbol: Symbol) -> Option<&Position> {
+        self.positions.get(&symbol)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (Symbol, &Position)> {
+        self.positions
+            .iter()
+            .map(|(symbol, position)| (*symbol, position))
+    }
+
+    /// Sets the price open lots of `symbol` are valued at.
+    pub fn mark(&mut self, symbol: Symbol, price: Decimal) {
+        self.marks.insert(symbol, price);
+    }
+
+    /// Marks `symbol` at the mid of its order book. Returns `false` if either side is empty.
+    pub fn mark_book(&mut self, symbol: Symbol, book: &OrderBook) -> bool {
+        book.mid().map(|mid| self.mark(symbol, mid)).is_some()
+    }
+
+    /// Marks the ticker's symbol at the mid between its bid and ask.
+    pub fn mark_ticker(&mut self, ticker: &ticker::v2::Ticker) {
+        self.mark(
+            ticker.symbol(),
+            (ticker.bid + ticker.ask) / Decimal::from(2),
+        );
+    }
+
+    pub fn mark_price(&self, symbol: Symbol) -> Option<Decimal> {
+        self.marks.get(&symbol).copied()
+    }
+
+    /// The realized P&L of the symbols quoted in `currency`, before fees.
+    pub fn realized_pnl(&self, currency: Currency) -> Decimal {
+        self.quoted_in(currency)
+            .map(|(_, position)| position.realized_pnl())
+            .sum()
+    }
+
+    /// The unrealized P&L of the marked symbols quoted in `currency`.
+    pub fn unrealized_pnl(&self, currency: Currency) -> Decimal {
+        self.quoted_in(currency)
+            .filter_map(|(symbol, position)| {
+                Some(position.unrealized_pnl(self.mark_price(symbol)?))
+            })
+            .sum()
+    }
+
+    /// The fees paid on the symbols quoted in `currency`.
+    pub fn fees(&self, currency: Currency) -> Decimal {
+        self.quoted_in(currency)
+            .map(|(_, position)| position.fees())
+            .sum()
+    }
+
+    /// The value of all balances in `currency`, or `None` if a balance can't be
+    /// converted because no symbol between the currencies was marked.
+    pub fn equity(&self, currency: Currency) -> Option<Decimal> {
+        self.balances
+            .iter()
+            .filter(|(_, amount)| !amount.is_zero())
+            .map(|(held, amount)| {
+                if *held == currency {
+                    return Some(*amount);
+                }
+                self.rate(*held, currency).map(|rate| amount * rate)
+            })
+            .sum()
+    }
+
+    // The mark of the symbol which trades `from` against `to`.
+    fn rate(&self, from: Currency, to: Currency) -> Option<Decimal> {
+        self.marks
+            .iter()
+            .find(|(symbol, _)| symbol.base() == Some(from) && symbol.quote() == Some(to))
+            .map(|(_, price)| *price)
+    }
+
+    fn quoted_in(&self, currency: Currency) -> impl Iterator<Item = (Symbol, &Position)> {
+        self.positions()
+            .filter(move |(symbol, _)| symbol.quote() == Some(currency))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use market::order_book::{Order, OrderData};
+    use rust_decimal_macros::dec;
+
+    fn tx(side: Side, quantity: Decimal, price: Decimal) -> Transaction {
+        Transaction {
+            time: Utc.timestamp_opt(0, 0).unwrap(),
+            symbol: Symbol::BTCUSD,
+            side,
+            quantity,
+            price,
+            fee: dec!(0),
+            fee_currency: None,
+        }
+    }
+
+    // buys 1 at 100 and 1 at 120, then sells 1 at 130
+    fn position(method: LotMethod) -> Position {
+        let mut portfolio = Portfolio::new(method);
+        portfolio.apply(tx(Side::Buy, dec!(1), dec!(100)));
+        portfolio.apply(tx(Side::Buy, dec!(1), dec!(120)));
+        portfolio.apply(tx(Side::Sell, dec!(1), dec!(130)));
+        portfolio.position(Symbol::BTCUSD).unwrap().clone()
+    }
+
+    #[test]
+    fn test_lot_methods() {
+        let fifo = position(LotMethod::Fifo);
+        assert_eq!(dec!(30), fifo.realized_pnl());
+        assert_eq!(Some(dec!(120)), fifo.average_cost());
+
+        let lifo = position(LotMethod::Lifo);
+        assert_eq!(dec!(10), lifo.realized_pnl());
+        assert_eq!(Some(dec!(100)), lifo.average_cost());
+
+        let average = position(LotMethod::Average);
+        assert_eq!(dec!(20), average.realized_pnl());
+        assert_eq!(Some(dec!(110)), average.average_cost());
+        assert_eq!(1, average.lots().count());
+
+        for position in &[fifo, lifo, average] {
+            assert_eq!(dec!(1), position.quantity());
+            assert_eq!(
+                dec!(140) - position.average_cost().unwrap(),
+                position.unrealized_pnl(dec!(140))
+            );
+        }
+    }
+
+    #[test]
+    fn test_position_reversal() {
+        let mut position = Position::default();
+        let time = Utc.timestamp_opt(0, 0).unwrap();
+        position.apply(LotMethod::Fifo, time, dec!(1), dec!(100));
+        position.apply(LotMethod::Fifo, time, dec!(-3), dec!(110));
+
+        assert_eq!(dec!(10), position.realized_pnl());
+        assert_eq!(dec!(-2), position.quantity());
+        assert_eq!(Some(dec!(110)), position.average_cost());
+        assert_eq!(dec!(20), position.unrealized_pnl(dec!(100)));
+
+        position.apply(LotMethod::Fifo, time, dec!(2), dec!(105));
+        assert!(position.is_flat());
+        assert_eq!(dec!(20), position.realized_pnl());
+        assert_eq!(None, position.average_cost());
+    }
+
+    #[test]
+    fn test_balances_and_fees() {
+        let mut portfolio = Portfolio::new(LotMethod::Fifo).with_balance(Currency::USD, dec!(1000));
+
+        let mut buy = tx(Side::Buy, dec!(2), dec!(100));
+        buy.fee = dec!(0.01);
+        buy.fee_currency = Some(Currency::BTC);
+        portfolio.apply(buy);
+        let mut sell = tx(Side::Sell, dec!(1), dec!(110));
+        sell.fee = dec!(1);
+        portfolio.apply(sell);
+
+        assert_eq!(
+            dec!(1000) - dec!(200) + dec!(110) - dec!(1),
+            portfolio.balance(Currency::USD)
+        );
+        assert_eq!(dec!(0.99), portfolio.balance(Currency::BTC));
+        assert_eq!(dec!(2), portfolio.fees(Currency::USD));
+        assert_eq!(dec!(10), portfolio.realized_pnl(Currency::USD));
+
+        assert_eq!(None, portfolio.equity(Currency::USD));
+        let mut book = OrderBook::new();
+        book.update(Order::Bid(OrderData::new(dec!(119), dec!(1))));
+        book.update(Order::Ask(OrderData::new(dec!(121), dec!(1))));
+        assert!(portfolio.mark_book(Symbol::BTCUSD, &book));
+
+        assert_eq!(dec!(20), portfolio.unrealized_pnl(Currency::USD));
+        assert_eq!(
+            Some(dec!(909) + dec!(0.99) * dec!(120)),
+            portfolio.equity(Currency::USD)
+        );
+    }
+
+    #[test]
+    fn test_third_currency_fees() {
+        let mut portfolio = Portfolio::new(LotMethod::Fifo);
+
+        let mut buy = tx(Side::Buy, dec!(1), dec!(100));
+        buy.fee = dec!(0.1);
+        buy.fee_currency = Some(Currency::ETH);
+        portfolio.apply(buy.clone());
+        // ether isn't marked, so the fee can't be converted to dollars
+        assert_eq!(dec!(0), portfolio.fees(Currency::USD));
+
+        portfolio.mark(Symbol::ETHUSD, dec!(20));
+        portfolio.apply(buy);
+        assert_eq!(dec!(2), portfolio.fees(Currency::USD));
+        assert_eq!(dec!(-0.2), portfolio.balance(Currency::ETH));
+        assert_eq!(dec!(-200), portfolio.balance(Currency::USD));
+    }
+
+    #[test]
+    fn test_transaction_from_my_trade() {
+        let json = r#"{
+            "price": "3648.09",
+            "amount": "0.5",
+            "timestamp": 1547232911,
+            "timestampms": 1547232911273,
+            "type": "Sell",
+            "aggressor": false,
+            "fee_currency": "USD",
+            "fee_amount": "1.5",
+            "tid": 107317526,
+            "order_id": "107317524",
+            "exchange": "gemini",
+            "symbol": "BTCUSD"
+        }"#;
+        let trade = serde_json::from_str::<MyTrade>(json).unwrap();
+
+        let mut portfolio = Portfolio::new(LotMethod::Average);
+        portfolio.apply(&trade);
+
+        assert_eq!(dec!(-0.5), portfolio.balance(Currency::BTC));
+        assert_eq!(dec!(1824.045) - dec!(1.5), portfolio.balance(Currency::USD));
+        let position = portfolio.position(Symbol::BTCUSD).unwrap();
+        assert_eq!(dec!(-0.5), position.quantity());
+        assert_eq!(dec!(1.5), position.fees());
+    }
+}
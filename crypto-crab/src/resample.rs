@@ -0,0 +1,207 @@
+use crate::{candle::Candle, decimal::Decimal, period::Resolution};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Candles of `to` can't be built from whole candles of `from`, e.g. 4 hours from
+    /// 6 hours, or either is `Resolution::Tick`.
+    Indivisible { from: Resolution, to: Resolution },
+    /// No resolution Gemini serves divides the resolution, e.g. seconds or ticks.
+    Unsupported(Resolution),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Indivisible { from, to } => {
+                write!(f, "can't resample {:?} candles to {:?}", from, to)
+            }
+            Self::Unsupported(resolution) => {
+                write!(
+                    f,
+                    "no candles served to build {:?} candles from",
+                    resolution
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Aggregates candles of the resolution `from` into candles of the coarser resolution
+/// `to`, aligned to its UTC boundaries, e.g. hourly candles into four hour or weekly
+/// ones. The candles may be in any order and the result is ordered by time.
+///
+/// The last candle is incomplete if the candles end before its interval does, and
+/// intervals without any candles are skipped, see `fill_gaps`.
+pub fn resample<C, I>(candles: I, from: Resolution, to: Resolution) -> Result<Vec<Candle>, Error>
+where
+    C: Into<Candle>,
+    I: IntoIterator<Item = C>,
+{
+    if !from.divides(to) {
+        return Err(Error::Indivisible { from, to });
+    }
+
+    let mut candles = candles.into_iter().map(Into::into).collect::<Vec<Candle>>();
+    candles.sort_by_key(|candle| candle.time);
+
+    let mut resampled = Vec::<Candle>::new();
+    for candle in candles {
+        // `divides` guarantees that only ticks can't be aligned
+        let start = to.align(candle.time).unwrap();
+        match resampled.last_mut() {
+            Some(last) if last.time == start => {
+                last.high = last.high.max(candle.high);
+                last.low = last.low.min(candle.low);
+                last.close = candle.close;
+                last.volume += candle.volume;
+            }
+            _ => resampled.push(Candle {
+                time: start,
+                ..candle
+            }),
+        }
+    }
+    Ok(resampled)
+}
+
+/// Inserts flat candles at the previous close with zero volume for the intervals of
+/// `resolution` between `candles` which have none, as Gemini doesn't publish candles
+/// of intervals without trades. The candles must be ordered by time.
+///
+/// # Panics
+///
+/// If `resolution` is `Resolution::Tick`.
+pub fn fill_gaps(candles: &[Candle], resolution: Resolution) -> Vec<Candle> {
+    let interval = resolution
+        .to_duration()
+        .expect("ticks have no gaps to fill");
+    let mut filled = Vec::<Candle>::with_capacity(candles.len());
+
+    for candle in candles {
+        if let Some(prev) = filled.last().cloned() {
+            let mut time = prev.time + interval;
+            while time < candle.time {
+                filled.push(Candle {
+                    time,
+                    open: prev.close,
+                    high: prev.close,
+                    low: prev.close,
+                    close: prev.close,
+                    volume: Decimal::ZERO,
+                });
+                time += interval;
+            }
+        }
+        filled.push(candle.clone());
+    }
+    filled
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::time::DateTime;
+    use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    fn hour(hour: i64) -> DateTime {
+        Utc.timestamp_opt(hour * 3600, 0).unwrap()
+    }
+
+    fn candle(time: DateTime, open: Decimal, close: Decimal) -> Candle {
+        Candle {
+            time,
+            open,
+            high: open.max(close) + dec!(1),
+            low: open.min(close) - dec!(1),
+            close,
+            volume: dec!(2),
+        }
+    }
+
+    #[test]
+    fn test_resample() {
+        // hours 2 to 9 of the first day, with hour 5 missing
+        let candles = [2, 9, 3, 4, 6, 7, 8]
+            .iter()
+            .map(|h| candle(hour(*h), Decimal::from(*h), Decimal::from(*h + 1)))
+            .collect::<Vec<_>>();
+
+        let resampled = resample(candles.clone(), Resolution::Hour, Resolution::Hour4).unwrap();
+        assert_eq!(
+            vec![
+                Candle {
+                    time: hour(0),
+                    open: dec!(2),
+                    high: dec!(5),
+                    low: dec!(1),
+                    close: dec!(4),
+                    volume: dec!(4),
+                },
+                Candle {
+                    time: hour(4),
+                    open: dec!(4),
+                    high: dec!(9),
+                    low: dec!(3),
+                    close: dec!(8),
+                    volume: dec!(6),
+                },
+                Candle {
+                    time: hour(8),
+                    open: dec!(8),
+                    high: dec!(11),
+                    low: dec!(7),
+                    close: dec!(10),
+                    volume: dec!(4),
+                },
+            ],
+            resampled
+        );
+
+        // 1970-01-01 was a Thursday, so the week starts on the Monday before
+        let weekly = resample(candles, Resolution::Hour, Resolution::Week).unwrap();
+        assert_eq!(1, weekly.len());
+        assert_eq!(hour(-3 * 24), weekly[0].time);
+        assert_eq!(dec!(14), weekly[0].volume);
+
+        assert_eq!(
+            Err(Error::Indivisible {
+                from: Resolution::Hour6,
+                to: Resolution::Hour4
+            }),
+            resample(Vec::<Candle>::new(), Resolution::Hour6, Resolution::Hour4)
+        );
+    }
+
+    #[test]
+    fn test_fill_gaps() {
+        let candles = [
+            candle(hour(0), dec!(1), dec!(2)),
+            candle(hour(3), dec!(3), dec!(4)),
+            candle(hour(4), dec!(4), dec!(5)),
+        ];
+        let filled = fill_gaps(&candles, Resolution::Hour);
+
+        assert_eq!(5, filled.len());
+        assert_eq!(
+            (0..5).map(hour).collect::<Vec<_>>(),
+            filled.iter().map(|c| c.time).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Candle {
+                time: hour(2),
+                open: dec!(2),
+                high: dec!(2),
+                low: dec!(2),
+                close: dec!(2),
+                volume: dec!(0),
+            },
+            filled[2]
+        );
+        assert_eq!(candles[2], filled[4]);
+        assert!(fill_gaps(&[], Resolution::Minute).is_empty());
+    }
+}
@@ -0,0 +1,382 @@
+use crate::{
+    algo::Pair,
+    decimal::Decimal,
+    order::{Broker, Order, OrderError, OrderId, OrderKind},
+    time::DateTime,
+};
+use chrono::NaiveDate;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Stops an algorithm from placing further orders. Cloned handles control the same switch.
+///
+/// The live runtime also cancels the open orders once the switch is engaged.
+#[derive(Debug, Clone, Default)]
+pub struct KillSwitch(Arc<AtomicBool>);
+
+impl KillSwitch {
+    pub fn engage(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// The limits orders are checked against. Limits which are `None` aren't checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// The maximum quantity times price of a single order.
+    pub max_order_notional: Option<Decimal>,
+    /// The maximum absolute quantity held of a pair, including the open orders and
+    /// the order.
+    pub max_position: HashMap<Pair, Decimal>,
+    /// The maximum number of orders open at the same time, including the order.
+    pub max_open_orders: Option<usize>,
+    /// The maximum fraction a limit price may deviate from the reference price.
+    pub price_band: Option<Decimal>,
+    /// The maximum loss of equity since the start of the (UTC) day.
+    pub max_daily_loss: Option<Decimal>,
+}
+
+/// The reason an order was rejected by the risk checks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    KillSwitch,
+    DailyLoss {
+        loss: Decimal,
+        limit: Decimal,
+    },
+    /// The order can't be checked because there is no reference price for its pair.
+    Unpriced {
+        pair: Pair,
+    },
+    PriceBand {
+        price: Decimal,
+        reference: Decimal,
+        band: Decimal,
+    },
+    OrderNotional {
+        notional: Decimal,
+        limit: Decimal,
+    },
+    Position {
+        pair: Pair,
+        position: Decimal,
+        limit: Decimal,
+    },
+    OpenOrders {
+        open: usize,
+        limit: usize,
+    },
+}
+
+/// Checks orders against `Limits` before they are placed.
+///
+/// The reference prices, e.g. the order book mid or the auction collar price, and the
+/// equity are provided by whatever drives the algorithm. The backtester and the live
+/// runtime do so once they are given a `RiskManager`.
+#[derive(Debug, Clone, Default)]
+pub struct RiskManager {
+    limits: Limits,
+    kill_switch: KillSwitch,
+    references: HashMap<Pair, Decimal>,
+    // The day and the equity at its start, along with the latest equity.
+    day: Option<(NaiveDate, Decimal)>,
+    equity: Option<Decimal>,
+}
+
+impl RiskManager {
+    pub fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    /// Shares `kill_switch`, e.g. the one of the live runtime, with the risk checks.
+    pub fn with_kill_switch(mut self, kill_switch: KillSwitch) -> Self {
+        self.kill_switch = kill_switch;
+        self
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    pub fn kill_switch(&self) -> KillSwitch {
+        self.kill_switch.clone()
+    }
+
+    /// Sets the price market orders are valued at and limit prices are compared to.
+    pub fn set_reference(&mut self, pair: Pair, price: Decimal) {
+        self.references.insert(pair, price);
+    }
+
+    pub fn reference(&self, pair: &Pair) -> Option<Decimal> {
+        self.references.get(pair).copied()
+    }
+
+    /// Records the equity at `time`. The first equity of each day is the baseline of
+    /// the daily loss limit.
+    pub fn update_equity(&mut self, time: DateTime, equity: Decimal) {
+        let date = time.date_naive();
+        if !matches!(self.day, Some((day, _)) if day == date) {
+            self.day = Some((date, equity));
+        }
+        self.equity = Some(equity);
+    }
+
+    /// Values the holdings of `broker` at the reference prices and records the equity.
+    pub fn update_broker<B: Broker + ?Sized>(&mut self, time: DateTime, broker: &B) {
+        let positions = self
+            .references
+            .iter()
+            .map(|(pair, price)| broker.position(pair) * price)
+            .sum::<Decimal>();
+        self.update_equity(time, broker.cash() + positions);
+    }
+
+    /// The loss of equity since the start of the day, negative for a profit.
+    pub fn daily_loss(&self) -> Option<Decimal> {
+        Some(self.day?.1 - self.equity?)
+    }
+
+    /// Checks `order` against the limits, given the holdings of `broker`.
+    pub fn check<B: Broker + ?Sized>(&self, order: &Order, broker: &B) -> Result<(), Violation> {
+        let limits = &self.limits;
+        if self.kill_switch.is_engaged() {
+            return Err(Violation::KillSwitch);
+        }
+
+        if let (Some(limit), Some(loss)) = (limits.max_daily_loss, self.daily_loss()) {
+            if loss >= limit {
+                return Err(Violation::DailyLoss { loss, limit });
+            }
+        }
+
+        let reference = self.reference(&order.pair);
+        if let (Some(band), OrderKind::Limit(price)) = (limits.price_band, order.kind) {
+            let reference = reference.ok_or_else(|| Violation::Unpriced {
+                pair: order.pair.clone(),
+            })?;
+            if (price - reference).abs() > band * reference {
+                return Err(Violation::PriceBand {
+                    price,
+                    reference,
+                    band,
+                });
+            }
+        }
+
+        if let Some(limit) = limits.max_order_notional {
+            let price = match order.kind {
+                OrderKind::Limit(price) => price,
+                OrderKind::Market => reference.ok_or_else(|| Violation::Unpriced {
+                    pair: order.pair.clone(),
+                })?,
+            };
+            let notional = order.quantity * price;
+            if notional > limit {
+                return Err(Violation::OrderNotional { notional, limit });
+            }
+        }
+
+        if let Some(limit) = limits.max_position.get(&order.pair).copied() {
+            // as if the open orders of the pair were filled
+            let current = broker.position(&order.pair) + broker.open_quantity(&order.pair);
+            let position = current + order.side.sign() * order.quantity;
+            // orders which reduce the position are always allowed
+            if position.abs() > limit && position.abs() > current.abs() {
+                return Err(Violation::Position {
+                    pair: order.pair.clone(),
+                    position,
+                    limit,
+                });
+            }
+        }
+
+        if let Some(limit) = limits.max_open_orders {
+            let open = broker.open_order_count() + 1;
+            if open > limit {
+                return Err(Violation::OpenOrders { open, limit });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A broker which only passes on orders which pass the risk checks.
+pub struct Checked<'a, B: ?Sized> {
+    pub broker: &'a mut B,
+    pub risk: &'a RiskManager,
+}
+
+impl<'a, B: Broker + ?Sized> Broker for Checked<'a, B> {
+    fn submit(&mut self, order: Order) -> Result<OrderId, OrderError> {
+        self.risk
+            .check(&order, &*self.broker)
+            .map_err(OrderError::Risk)?;
+        self.broker.submit(order)
+    }
+
+    fn cancel(&mut self, id: OrderId) -> bool {
+        self.broker.cancel(id)
+    }
+
+    fn position(&self, pair: &Pair) -> Decimal {
+        self.broker.position(pair)
+    }
+
+    fn cash(&self) -> Decimal {
+        self.broker.cash()
+    }
+
+    fn open_order_count(&self) -> usize {
+        self.broker.open_order_count()
+    }
+
+    fn open_quantity(&self, pair: &Pair) -> Decimal {
+        self.broker.open_quantity(pair)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{backtest::exchange::SimulatedExchange, order::Side};
+    use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    fn risk(limits: Limits) -> RiskManager {
+        let mut risk = RiskManager::new(limits);
+        risk.set_reference(btc(), dec!(100));
+        risk
+    }
+
+    fn btc() -> Pair {
+        Pair::new("BTC", "USD")
+    }
+
+    fn buy(quantity: Decimal) -> Order {
+        Order::market(btc(), Side::Buy, quantity)
+    }
+
+    #[test]
+    fn test_order_limits() {
+        let exchange = SimulatedExchange::new(dec!(10000));
+        let risk = risk(Limits {
+            max_order_notional: Some(dec!(500)),
+            price_band: Some(dec!(0.05)),
+            ..Limits::default()
+        });
+
+        assert_eq!(Ok(()), risk.check(&buy(dec!(5)), &exchange));
+        assert_eq!(
+            Err(Violation::OrderNotional {
+                notional: dec!(600),
+                limit: dec!(500)
+            }),
+            risk.check(&buy(dec!(6)), &exchange)
+        );
+
+        let limit = Order::limit(btc(), Side::Sell, dec!(1), dec!(94));
+        assert_eq!(
+            Err(Violation::PriceBand {
+                price: dec!(94),
+                reference: dec!(100),
+                band: dec!(0.05)
+            }),
+            risk.check(&limit, &exchange)
+        );
+        let eth = Order::market(Pair::new("ETH", "USD"), Side::Buy, dec!(1));
+        assert_eq!(
+            Err(Violation::Unpriced {
+                pair: Pair::new("ETH", "USD")
+            }),
+            risk.check(&eth, &exchange)
+        );
+    }
+
+    #[test]
+    fn test_position_and_open_orders() {
+        let mut limits = Limits {
+            max_open_orders: Some(2),
+            ..Limits::default()
+        };
+        limits.max_position.insert(btc(), dec!(3));
+        let risk = risk(limits);
+        let mut exchange = SimulatedExchange::new(dec!(10000));
+
+        let mut broker = Checked {
+            broker: &mut exchange,
+            risk: &risk,
+        };
+        let sell = |quantity| Order::market(btc(), Side::Sell, quantity);
+        broker.submit(buy(dec!(2))).unwrap();
+        assert_eq!(
+            Err(OrderError::Risk(Violation::Position {
+                pair: btc(),
+                position: dec!(-4),
+                limit: dec!(3)
+            })),
+            broker.submit(sell(dec!(6)))
+        );
+        broker.submit(buy(dec!(1))).unwrap();
+        // the open buys count towards the position
+        assert_eq!(
+            Err(OrderError::Risk(Violation::Position {
+                pair: btc(),
+                position: dec!(4),
+                limit: dec!(3)
+            })),
+            broker.submit(buy(dec!(1)))
+        );
+        assert_eq!(
+            Err(OrderError::Risk(Violation::OpenOrders {
+                open: 3,
+                limit: 2
+            })),
+            broker.submit(sell(dec!(1)))
+        );
+    }
+
+    #[test]
+    fn test_daily_loss_and_kill_switch() {
+        let mut risk = risk(Limits {
+            max_daily_loss: Some(dec!(50)),
+            ..Limits::default()
+        });
+        let exchange = SimulatedExchange::new(dec!(1000));
+        let day = |day: i64, hour: i64| Utc.timestamp_opt(day * 86400 + hour * 3600, 0).unwrap();
+
+        risk.update_equity(day(0, 1), dec!(1000));
+        risk.update_equity(day(0, 2), dec!(940));
+        assert_eq!(Some(dec!(60)), risk.daily_loss());
+        assert_eq!(
+            Err(Violation::DailyLoss {
+                loss: dec!(60),
+                limit: dec!(50)
+            }),
+            risk.check(&buy(dec!(1)), &exchange)
+        );
+
+        // the limit resets with the next day
+        risk.update_broker(day(1, 0), &exchange);
+        assert_eq!(Some(dec!(0)), risk.daily_loss());
+        assert_eq!(Ok(()), risk.check(&buy(dec!(1)), &exchange));
+
+        risk.kill_switch().engage();
+        assert_eq!(
+            Err(Violation::KillSwitch),
+            risk.check(&buy(dec!(1)), &exchange)
+        );
+    }
+}
@@ -0,0 +1,570 @@
+use crate::{
+    candle::Candle,
+    decimal::Decimal,
+    period::Resolution,
+    resample::{self, resample},
+    time::{DateTime, Duration},
+};
+use chrono::{TimeZone, Utc};
+use gemini::{
+    rest::{
+        candles::{CandleHttpRequest, TimeRange},
+        client::{self, Client},
+    },
+    symbol::Symbol,
+    ws::marketv2::candle::Candles,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Rest(client::Error),
+    Resample(resample::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "candle store: {}", e),
+            Self::Rest(e) => write!(f, "candle backfill: {}", e),
+            Self::Resample(e) => write!(f, "candle store: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<resample::Error> for Error {
+    fn from(e: resample::Error) -> Self {
+        Self::Resample(e)
+    }
+}
+
+impl From<client::Error> for Error {
+    fn from(e: client::Error) -> Self {
+        Self::Rest(e)
+    }
+}
+
+// The candles of one symbol and range, loaded from their file on first use.
+struct Series {
+    path: PathBuf,
+    candles: BTreeMap<DateTime, Candle>,
+    file: Option<BufWriter<File>>,
+}
+
+impl Series {
+    // A last line without a line break was cut short while it was appended, e.g. by a
+    // crash. It's dropped from the file if it's incomplete and terminated otherwise, so
+    // later appends start on a line of their own.
+    fn load(path: PathBuf) -> io::Result<Self> {
+        let mut candles = BTreeMap::new();
+        let mut reader = match File::open(&path) {
+            Ok(file) => BufReader::new(file),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self {
+                    path,
+                    candles,
+                    file: None,
+                })
+            }
+            Err(e) => return Err(e),
+        };
+
+        let mut line = String::new();
+        // the length of the complete lines read so far
+        let mut length = 0;
+        for number in 1.. {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            let terminated = line.ends_with('\n');
+            let text = line.trim_end();
+            if text.is_empty() {
+                length += line.len() as u64;
+                continue;
+            }
+            match (parse(text), terminated) {
+                (Some(candle), _) => {
+                    // later lines are updates of earlier ones
+                    candles.insert(candle.time, candle);
+                }
+                (None, false) => {
+                    OpenOptions::new()
+                        .write(true)
+                        .open(&path)?
+                        .set_len(length)?;
+                    break;
+                }
+                (None, true) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{}:{}: invalid candle", path.display(), number),
+                    ))
+                }
+            }
+            if !terminated {
+                writeln!(OpenOptions::new().append(true).open(&path)?)?;
+            }
+            length += line.len() as u64;
+        }
+
+        Ok(Self {
+            path,
+            candles,
+            file: None,
+        })
+    }
+
+    // Inserts the candles which are new or differ from the stored ones and appends
+    // them to the file. Returns the number of such candles.
+    fn insert(&mut self, candles: impl IntoIterator<Item = Candle>) -> io::Result<usize> {
+        let mut changed = Vec::new();
+        for candle in candles {
+            if self.candles.get(&candle.time) != Some(&candle) {
+                changed.push(candle.clone());
+                self.candles.insert(candle.time, candle);
+            }
+        }
+        if changed.is_empty() {
+            return Ok(0);
+        }
+
+        if self.file.is_none() {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            self.file = Some(BufWriter::new(file));
+        }
+        let file = self.file.as_mut().unwrap();
+        for candle in &changed {
+            writeln!(file, "{}", format(candle))?;
+        }
+        file.flush()?;
+        Ok(changed.len())
+    }
+
+    // Rewrites the file with one line per candle, in order.
+    fn compact(&mut self) -> io::Result<()> {
+        self.file = None;
+        let tmp = self.path.with_extension("csv.tmp");
+        {
+            let mut file = BufWriter::new(File::create(&tmp)?);
+            for candle in self.candles.values() {
+                writeln!(file, "{}", format(candle))?;
+            }
+            file.flush()?;
+        }
+        fs::rename(tmp, &self.path)
+    }
+}
+
+fn format(candle: &Candle) -> String {
+    format!(
+        "{},{},{},{},{},{}",
+        candle.time.timestamp_millis(),
+        candle.open,
+        candle.high,
+        candle.low,
+        candle.close,
+        candle.volume
+    )
+}
+
+fn parse(line: &str) -> Option<Candle> {
+    let mut fields = line.split(',');
+    let time = Utc
+        .timestamp_millis_opt(fields.next()?.parse().ok()?)
+        .single()?;
+    let mut decimal = || Decimal::from_str(fields.next()?).ok();
+    let candle = Candle {
+        time,
+        open: decimal()?,
+        high: decimal()?,
+        low: decimal()?,
+        close: decimal()?,
+        volume: decimal()?,
+    };
+    fields.next().is_none().then_some(candle)
+}
+
+/// A local store of historical candles with one file per symbol and time range.
+///
+/// Candles are backfilled from the REST endpoint and appended from the candle updates
+/// of the market data feed. Each timestamp holds a single candle: a later version, e.g.
+/// the final state of a candle which was still open, replaces the earlier one. Updates
+/// are appended to the files, `compact` rewrites them without the replaced candles.
+pub struct CandleStore {
+    dir: PathBuf,
+    series: HashMap<(Symbol, TimeRange), Series>,
+}
+
+impl CandleStore {
+    /// Opens the store in `dir`, creating the directory if it doesn't exist.
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+            series: HashMap::new(),
+        })
+    }
+
+    /// The file the candles of `symbol` at `range` are stored in.
+    pub fn path(&self, symbol: Symbol, range: TimeRange) -> PathBuf {
+        let name: &str = range.into();
+        self.dir.join(format!("{}_{}.csv", symbol.as_ref(), name))
+    }
+
+    fn series(&mut self, symbol: Symbol, range: TimeRange) -> io::Result<&mut Series> {
+        if !self.series.contains_key(&(symbol, range)) {
+            let series = Series::load(self.path(symbol, range))?;
+            self.series.insert((symbol, range), series);
+        }
+        Ok(self.series.get_mut(&(symbol, range)).unwrap())
+    }
+
+    /// Stores `candles`, returning the number of candles which were new or changed.
+    pub fn insert<C, I>(
+        &mut self,
+        symbol: Symbol,
+        range: TimeRange,
+        candles: I,
+    ) -> io::Result<usize>
+    where
+        C: Into<Candle>,
+        I: IntoIterator<Item = C>,
+    {
+        self.series(symbol, range)?
+            .insert(candles.into_iter().map(Into::into))
+    }
+
+    /// Stores the candles of a market data update.
+    pub fn apply(&mut self, update: &Candles) -> io::Result<usize> {
+        self.insert(
+            update.symbol,
+            update.candle_type.into(),
+            update.candles.iter().cloned(),
+        )
+    }
+
+    /// Requests the candles Gemini provides for `symbol` at `range` and stores them.
+    /// Returns the number of candles which were new or changed.
+    pub async fn backfill(
+        &mut self,
+        client: &Client,
+        symbol: Symbol,
+        range: TimeRange,
+    ) -> Result<usize, Error> {
+        let candles = client
+            .request(
+                CandleHttpRequest::builder()
+                    .symbol(symbol)
+                    .time_range(range)
+                    .build(),
+            )
+            .await?;
+        Ok(self.insert(symbol, range, candles.candles)?)
+    }
+
+    /// The stored candles starting within `times`, in order.
+    pub fn range(
+        &mut self,
+        symbol: Symbol,
+        range: TimeRange,
+        times: Range<DateTime>,
+    ) -> io::Result<Vec<Candle>> {
+        Ok(self
+            .series(symbol, range)?
+            .candles
+            .range(times)
+            .map(|(_, candle)| candle.clone())
+            .collect())
+    }
+
+    /// The candles of `resolution` starting within `times`, in order. Resolutions
+    /// Gemini doesn't serve are resampled from the stored candles of their
+    /// `Resolution::served_source` over the whole interval of each candle, including
+    /// the part of the last one after `times`.
+    pub fn candles(
+        &mut self,
+        symbol: Symbol,
+        resolution: Resolution,
+        times: Range<DateTime>,
+    ) -> Result<Vec<Candle>, Error> {
+        let unsupported = resample::Error::Unsupported(resolution);
+        let source = resolution.served_source().ok_or(unsupported)?;
+        let range = TimeRange::try_from(source).map_err(|_| unsupported)?;
+        if source == resolution {
+            return Ok(self.range(symbol, range, times)?);
+        }
+
+        // served resolutions are never ticks, so neither is a resolution they divide
+        let interval = resolution.to_duration().ok_or(unsupported)?;
+        let start = resolution.align(times.start).ok_or(unsupported)?;
+        let end = match resolution.align(times.end).ok_or(unsupported)? {
+            last if last == times.end => last,
+            last => last + interval,
+        };
+        let candles = self.range(symbol, range, start..end)?;
+        Ok(resample(candles, source, resolution)?
+            .into_iter()
+            .filter(|candle| times.contains(&candle.time))
+            .collect())
+    }
+
+    /// All stored candles, in order.
+    pub fn all(&mut self, symbol: Symbol, range: TimeRange) -> io::Result<Vec<Candle>> {
+        Ok(self
+            .series(symbol, range)?
+            .candles
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    /// The first and last stored timestamp, if any.
+    pub fn bounds(
+        &mut self,
+        symbol: Symbol,
+        range: TimeRange,
+    ) -> io::Result<Option<(DateTime, DateTime)>> {
+        let candles = &self.series(symbol, range)?.candles;
+        Ok(candles
+            .keys()
+            .next()
+            .zip(candles.keys().next_back())
+            .map(|(first, last)| (*first, *last)))
+    }
+
+    /// The timestamps between the first and the last stored candle which are missing
+    /// a candle, as ranges from the start of the first missing candle to the start of
+    /// the next stored one.
+    ///
+    /// Gemini doesn't publish candles for intervals without trades, so gaps of quiet
+    /// markets are expected.
+    pub fn gaps(&mut self, symbol: Symbol, range: TimeRange) -> io::Result<Vec<Range<DateTime>>> {
+        let interval: Duration = range.to_duration();
+        let times = self
+            .series(symbol, range)?
+            .candles
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+
+        Ok(times
+            .windows(2)
+            .filter(|pair| pair[1] - pair[0] > interval)
+            .map(|pair| pair[0] + interval..pair[1])
+            .collect())
+    }
+
+    /// Rewrites the file of `symbol` at `range` without replaced candles.
+    pub fn compact(&mut self, symbol: Symbol, range: TimeRange) -> io::Result<()> {
+        self.series(symbol, range)?.compact()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("crypto-crab-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn candle(minute: i64, close: Decimal) -> Candle {
+        Candle {
+            time: Utc.timestamp_opt(minute * 60, 0).unwrap(),
+            open: dec!(10),
+            high: close.max(dec!(10)),
+            low: close.min(dec!(10)),
+            close,
+            volume: dec!(1.5),
+        }
+    }
+
+    #[test]
+    fn test_insert_and_reload() {
+        let dir = dir("store");
+        let mut store = CandleStore::open(&dir).unwrap();
+        let (symbol, range) = (Symbol::BTCUSD, TimeRange::Minute1);
+
+        let candles = vec![candle(2, dec!(11)), candle(0, dec!(9)), candle(1, dec!(12))];
+        assert_eq!(3, store.insert(symbol, range, candles.clone()).unwrap());
+        // duplicates are skipped and changed candles replace the stored ones
+        assert_eq!(0, store.insert(symbol, range, candles).unwrap());
+        assert_eq!(
+            2,
+            store
+                .insert(symbol, range, vec![candle(2, dec!(13)), candle(5, dec!(8))])
+                .unwrap()
+        );
+
+        let expected = vec![
+            candle(0, dec!(9)),
+            candle(1, dec!(12)),
+            candle(2, dec!(13)),
+            candle(5, dec!(8)),
+        ];
+        let mut reopened = CandleStore::open(&dir).unwrap();
+        assert_eq!(expected, reopened.all(symbol, range).unwrap());
+        assert_eq!(
+            expected[1..3].to_vec(),
+            reopened
+                .range(
+                    symbol,
+                    range,
+                    candle(1, dec!(0)).time..candle(5, dec!(0)).time
+                )
+                .unwrap()
+        );
+        assert_eq!(
+            Some((expected[0].time, expected[3].time)),
+            reopened.bounds(symbol, range).unwrap()
+        );
+
+        // compaction drops the replaced candle
+        let path = reopened.path(symbol, range);
+        assert_eq!(5, fs::read_to_string(&path).unwrap().lines().count());
+        reopened.compact(symbol, range).unwrap();
+        assert_eq!(4, fs::read_to_string(&path).unwrap().lines().count());
+        assert_eq!(
+            expected,
+            CandleStore::open(&dir).unwrap().all(symbol, range).unwrap()
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_gaps_and_updates() {
+        let dir = dir("gaps");
+        let mut store = CandleStore::open(&dir).unwrap();
+
+        let update = serde_json::from_str::<Candles>(
+            r#"{"type":"candles_5m_updates","symbol":"ETHUSD","changes":[
+                [1200000,"10","11","9","10.5","2"],
+                [600000,"10","11","9","10.5","2"],
+                [300000,"10","11","9","10.5","2"],
+                [0,"10","11","9","10.5","2"]
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(4, store.apply(&update).unwrap());
+
+        let minute = |minute: i64| Utc.timestamp_opt(minute * 60, 0).unwrap();
+        assert_eq!(
+            vec![minute(15)..minute(20)],
+            store.gaps(Symbol::ETHUSD, TimeRange::Minute5).unwrap()
+        );
+        assert!(store
+            .gaps(Symbol::ETHUSD, TimeRange::Minute1)
+            .unwrap()
+            .is_empty());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_resampled_candles() {
+        let dir = dir("resampled");
+        let mut store = CandleStore::open(&dir).unwrap();
+        let candles = (0..12).map(|hour| candle(hour * 60, Decimal::from(hour)));
+        store
+            .insert(Symbol::BTCUSD, TimeRange::Hour1, candles)
+            .unwrap();
+
+        let hour = |hour: i64| Utc.timestamp_opt(hour * 3600, 0).unwrap();
+        // the candle of hour 8 includes the hours after the range
+        let resampled = store
+            .candles(Symbol::BTCUSD, Resolution::Hour4, hour(2)..hour(10))
+            .unwrap();
+        assert_eq!(
+            vec![hour(4), hour(8)],
+            resampled.iter().map(|c| c.time).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            (dec!(11), dec!(6)),
+            (resampled[1].close, resampled[1].volume)
+        );
+
+        let hourly = store
+            .candles(Symbol::BTCUSD, Resolution::Hour, hour(2)..hour(4))
+            .unwrap();
+        assert_eq!(2, hourly.len());
+        assert!(matches!(
+            store.candles(Symbol::BTCUSD, Resolution::Second, hour(0)..hour(1)),
+            Err(Error::Resample(resample::Error::Unsupported(
+                Resolution::Second
+            )))
+        ));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        let dir = dir("invalid");
+        let mut store = CandleStore::open(&dir).unwrap();
+        let path = store.path(Symbol::BTCUSD, TimeRange::Hour1);
+        fs::write(&path, "0,1,2,3,4,5\n60000,1,2,3\n").unwrap();
+
+        let e = store.all(Symbol::BTCUSD, TimeRange::Hour1).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, e.kind());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_truncated_file() {
+        let dir = dir("truncated");
+        let (symbol, range) = (Symbol::BTCUSD, TimeRange::Minute1);
+        let path = CandleStore::open(&dir).unwrap().path(symbol, range);
+
+        // the partial line is dropped
+        fs::write(&path, "0,10,10,9,9,1.5\n60000,10,1").unwrap();
+        let mut store = CandleStore::open(&dir).unwrap();
+        assert_eq!(vec![candle(0, dec!(9))], store.all(symbol, range).unwrap());
+        store
+            .insert(symbol, range, vec![candle(2, dec!(11))])
+            .unwrap();
+        assert_eq!(
+            vec![candle(0, dec!(9)), candle(2, dec!(11))],
+            CandleStore::open(&dir).unwrap().all(symbol, range).unwrap()
+        );
+
+        // a complete line is kept
+        fs::write(&path, "0,10,10,9,9,1.5").unwrap();
+        let mut store = CandleStore::open(&dir).unwrap();
+        store
+            .insert(symbol, range, vec![candle(2, dec!(11))])
+            .unwrap();
+        assert_eq!(
+            vec![candle(0, dec!(9)), candle(2, dec!(11))],
+            CandleStore::open(&dir).unwrap().all(symbol, range).unwrap()
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
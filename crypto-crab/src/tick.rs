@@ -0,0 +1,39 @@
+use crate::{decimal::Decimal, time::DateTime};
+
+/// A single trade.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tick {
+    pub time: DateTime,
+    pub price: Decimal,
+    pub quantity: Decimal,
+}
+
+impl From<&gemini::common::Trade> for Tick {
+    fn from(trade: &gemini::common::Trade) -> Self {
+        Self {
+            time: trade.time,
+            price: trade.price,
+            quantity: trade.quantity,
+        }
+    }
+}
+
+impl From<&gemini::ws::marketv2::trade::Trade> for Tick {
+    fn from(trade: &gemini::ws::marketv2::trade::Trade) -> Self {
+        Self {
+            time: trade.timestamp,
+            price: trade.price,
+            quantity: trade.quantity,
+        }
+    }
+}
+
+impl From<&exchange_api::Trade> for Tick {
+    fn from(trade: &exchange_api::Trade) -> Self {
+        Self {
+            time: trade.time,
+            price: trade.price,
+            quantity: trade.quantity,
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use chrono::{TimeZone, Utc};
+use crypto_crab::{
+    algo::{Algorithm, MarketData, Pair},
+    backtest::Backtest,
+    candle::Candle,
+    order::Broker,
+    period::Resolution,
+    tick::Tick,
+};
+use crypto_crab_macros::Algorithm;
+use rust_decimal_macros::dec;
+
+#[derive(Algorithm)]
+#[algo(pair = "ETH/USD", resolution = "minute")]
+#[algo(pair = "eth/usd", resolution = "hour")]
+#[algo(resolution = "tick", pair = "BTC/USD")]
+#[algo(pair = "SOL/EUR", resolution = "day")]
+struct Multi;
+
+impl Algorithm for Multi {
+    fn on(&mut self, _: &Self::Data, _: &mut dyn Broker) {}
+}
+
+fn candle(close: rust_decimal::Decimal) -> Candle {
+    Candle {
+        time: Utc.timestamp_opt(0, 0).unwrap(),
+        open: close,
+        high: close,
+        low: close,
+        close,
+        volume: dec!(1),
+    }
+}
+
+#[test]
+fn test_data_accessors() {
+    let (eth, btc, sol) = (
+        Pair::new("ETH", "USD"),
+        Pair::new("BTC", "USD"),
+        Pair::new("SOL", "EUR"),
+    );
+    assert_eq!(
+        vec![
+            (eth.clone(), Resolution::Minute),
+            (eth.clone(), Resolution::Hour),
+            (btc.clone(), Resolution::Tick),
+            (sol.clone(), Resolution::Day),
+        ],
+        MultiAlgorithmData::subscriptions()
+    );
+
+    let mut data = MultiAlgorithmData::default();
+    assert!(data.get_ethusd_minute().is_none());
+    assert!(data.get_btcusd().is_none());
+
+    assert!(data.update(&eth, Resolution::Minute, &candle(dec!(1))));
+    assert!(data.update(&eth, Resolution::Hour, &candle(dec!(2))));
+    assert!(!data.update(&eth, Resolution::Day, &candle(dec!(3))));
+    assert!(!data.update(&btc, Resolution::Minute, &candle(dec!(4))));
+    assert!(data.update(&sol, Resolution::Day, &candle(dec!(5))));
+
+    assert_eq!(Some(dec!(1)), data.get_ethusd_minute().map(|c| c.close));
+    assert_eq!(Some(dec!(2)), data.get_ethusd_hour().map(|c| c.close));
+    assert_eq!(Some(dec!(5)), data.get_soleur().map(|c| c.close));
+
+    let tick = Tick {
+        time: Utc.timestamp_opt(0, 0).unwrap(),
+        price: dec!(5),
+        quantity: dec!(0.1),
+    };
+    assert!(data.update_tick(&btc, &tick));
+    assert!(!data.update_tick(&eth, &tick));
+    assert_eq!(Some(&tick), data.get_btcusd());
+    assert_eq!(Some(&tick), data.get_btcusd_tick());
+}
+
+#[test]
+fn test_backtest_derived() {
+    let candles = (0..3).map(|minute| gemini::common::Candle {
+        time: Utc.timestamp_opt(minute * 60, 0).unwrap(),
+        open: dec!(1),
+        high: dec!(1),
+        low: dec!(1),
+        close: dec!(1),
+        volume: dec!(1),
+    });
+
+    let report = Backtest::new(dec!(100))
+        .with_candles(Pair::new("ETH", "USD"), Resolution::Minute, candles)
+        .run(&mut Multi);
+    assert_eq!(3, report.equity.len());
+    assert_eq!(dec!(100), report.final_equity());
+}
+
+#[test]
+fn test_diagnostics() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,8 @@
+use crypto_crab_macros::Algorithm;
+
+#[derive(Algorithm)]
+#[algo(pair = "BTC/USD", resolution = "minute")]
+#[algo(pair = "btc/usd", resolution = "Minute")]
+struct MyAlgo;
+
+fn main() {}
@@ -0,0 +1,7 @@
+use crypto_crab_macros::Algorithm;
+
+#[derive(Algorithm)]
+#[algo(pair = "DOGEUSD", resolution = "minute")]
+struct MyAlgo;
+
+fn main() {}
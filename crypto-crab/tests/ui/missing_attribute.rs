@@ -0,0 +1,6 @@
+use crypto_crab_macros::Algorithm;
+
+#[derive(Algorithm)]
+struct MyAlgo;
+
+fn main() {}
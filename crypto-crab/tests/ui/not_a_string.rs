@@ -0,0 +1,7 @@
+use crypto_crab_macros::Algorithm;
+
+#[derive(Algorithm)]
+#[algo(pair = "BTC/USD", resolution = 60)]
+struct MyAlgo;
+
+fn main() {}
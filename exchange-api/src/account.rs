@@ -0,0 +1,40 @@
+use crate::data::{Pair, Side};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Balance {
+    pub asset: String,
+    pub total: Decimal,
+    /// The part of the total not held by open orders.
+    pub available: Decimal,
+}
+
+/// An execution of one of the account's orders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fill {
+    pub trade_id: String,
+    pub order_id: String,
+    pub pair: Pair,
+    pub side: Side,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub fee: Decimal,
+    pub fee_asset: String,
+    /// Whether the order took liquidity.
+    pub taker: bool,
+    pub time: DateTime<Utc>,
+}
+
+/// The holdings and trading history of an exchange account.
+#[async_trait]
+pub trait Account: Send + Sync {
+    type Error: fmt::Debug + Send;
+
+    async fn balances(&self) -> Result<Vec<Balance>, Self::Error>;
+
+    /// The recent fills of the account's orders of `pair`, oldest first.
+    async fn fills(&self, pair: &Pair) -> Result<Vec<Fill>, Self::Error>;
+}
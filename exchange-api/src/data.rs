@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use market::order_book::OrderBook;
+use rust_decimal::Decimal;
+use std::fmt;
+
+/// A traded pair of assets, e.g. BTC quoted in USD. Assets are named by their upper
+/// case ticker.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pair {
+    pub base: String,
+    pub quote: String,
+}
+
+impl Pair {
+    pub fn new(base: impl AsRef<str>, quote: impl AsRef<str>) -> Self {
+        Self {
+            base: base.as_ref().to_ascii_uppercase(),
+            quote: quote.as_ref().to_ascii_uppercase(),
+        }
+    }
+}
+
+impl fmt::Display for Pair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.base, self.quote)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candle {
+    // The start of the candle's interval.
+    pub time: DateTime<Utc>,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    /// The traded quantity of the base asset.
+    pub volume: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TradeKind {
+    /// On the continuous order book.
+    Continuous,
+    Auction,
+    /// Negotiated off the order book.
+    Block,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trade {
+    /// The exchange's id of the trade, unique per pair.
+    pub id: String,
+    pub time: DateTime<Utc>,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    /// The side of the order which took liquidity, if the exchange reports it.
+    pub taker: Option<Side>,
+    pub kind: TradeKind,
+    /// Whether the exchange reversed the trade after it executed.
+    pub broken: bool,
+}
+
+/// Public market data of an exchange.
+#[async_trait]
+pub trait MarketDataSource: Send + Sync {
+    type Error: fmt::Debug + Send;
+
+    /// The pairs traded on the exchange.
+    fn pairs(&self) -> Vec<Pair>;
+
+    /// The recent candles of `pair` spanning `interval` each, oldest first.
+    async fn candles(&self, pair: &Pair, interval: Duration) -> Result<Vec<Candle>, Self::Error>;
+
+    /// The recent trades of `pair`, oldest first.
+    async fn trades(&self, pair: &Pair) -> Result<Vec<Trade>, Self::Error>;
+
+    async fn order_book(&self, pair: &Pair) -> Result<OrderBook, Self::Error>;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pair() {
+        let pair = Pair::new("btc", "Usd");
+        assert_eq!(Pair::new("BTC", "USD"), pair);
+        assert_eq!("BTC/USD", pair.to_string());
+    }
+}
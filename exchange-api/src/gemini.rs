@@ -0,0 +1,467 @@
+use crate::{
+    account::{Account, Balance, Fill},
+    data::{Candle, MarketDataSource, Pair, Side, Trade, TradeKind},
+    order::{OrderGateway, OrderReport, OrderRequest, OrderState, TimeInForce},
+};
+use ::gemini::{
+    common,
+    rest::{
+        balances::{self, BalancesRequest},
+        candles::{CandleHttpRequest, TimeRange},
+        client::{self, Client},
+        my_trades::{self, MyTrade, MyTradesRequest},
+        order::{
+            self, CancelOrderRequest, NewOrderRequest, OrderOption, OrderStatus, OrderStatusRequest,
+        },
+        order_book::{self, OrderBookHttpRequest},
+        trade_history::{TradeHistory, TradeHistoryHttpRequest, TradeType},
+    },
+    symbol::Symbol,
+};
+use async_trait::async_trait;
+use chrono::Duration;
+use market::order_book::{Order, OrderBook, OrderData};
+use rust_decimal::Decimal;
+use std::{collections::BTreeMap, convert::TryFrom};
+use strum::IntoEnumIterator;
+
+const TIME_RANGES: [TimeRange; 7] = [
+    TimeRange::Minute1,
+    TimeRange::Minute5,
+    TimeRange::Minute15,
+    TimeRange::Minute30,
+    TimeRange::Hour1,
+    TimeRange::Hour6,
+    TimeRange::Day1,
+];
+
+#[derive(Debug)]
+pub enum Error {
+    Client(client::Error),
+    // The pair isn't traded on Gemini.
+    UnknownPair(Pair),
+    // Gemini reported a symbol this crate doesn't know.
+    UnknownSymbol,
+    // Gemini has no candles of the interval.
+    UnsupportedInterval(Duration),
+    InvalidOrderId(String),
+}
+
+impl From<client::Error> for Error {
+    fn from(err: client::Error) -> Self {
+        Self::Client(err)
+    }
+}
+
+/// Gemini's REST API behind the exchange-agnostic traits. The order gateway and the
+/// account need a client with credentials.
+pub struct Gemini {
+    client: Client,
+}
+
+impl Gemini {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+}
+
+/// The pair of `symbol`, or `None` for unknown symbols.
+pub fn pair(symbol: Symbol) -> Option<Pair> {
+    Some(Pair::new(symbol.base()?, symbol.quote()?))
+}
+
+impl TryFrom<&Pair> for Symbol {
+    type Error = Error;
+
+    fn try_from(pair: &Pair) -> Result<Self, Self::Error> {
+        let unknown = || Error::UnknownPair(pair.clone());
+        let base = pair.base.parse().map_err(|_| unknown())?;
+        let quote = pair.quote.parse().map_err(|_| unknown())?;
+        Symbol::from_currencies(base, quote).ok_or_else(unknown)
+    }
+}
+
+/// The range of Gemini's candles spanning `interval`.
+pub fn time_range(interval: Duration) -> Option<TimeRange> {
+    TIME_RANGES
+        .iter()
+        .copied()
+        .find(|range| range.to_duration() == interval)
+}
+
+impl From<common::TradeKind> for TradeKind {
+    fn from(kind: common::TradeKind) -> Self {
+        match kind {
+            common::TradeKind::Continuous => Self::Continuous,
+            common::TradeKind::Auction => Self::Auction,
+            common::TradeKind::Block => Self::Block,
+        }
+    }
+}
+
+impl From<common::Candle> for Candle {
+    fn from(candle: common::Candle) -> Self {
+        Self {
+            time: candle.time,
+            open: candle.open,
+            high: candle.high,
+            low: candle.low,
+            close: candle.close,
+            volume: candle.volume,
+        }
+    }
+}
+
+impl From<&TradeHistory> for Trade {
+    fn from(trade: &TradeHistory) -> Self {
+        let taker = match trade.ty {
+            TradeType::Buy => Some(Side::Buy),
+            TradeType::Sell => Some(Side::Sell),
+            TradeType::Auction | TradeType::Block => None,
+        };
+        Self {
+            id: trade.tid.to_string(),
+            time: trade.timestamp,
+            price: trade.price,
+            quantity: trade.amount,
+            taker,
+            kind: common::TradeKind::from(trade.ty).into(),
+            broken: trade.broken,
+        }
+    }
+}
+
+/// The order book of Gemini's book snapshot. The quantities of repeated prices are
+/// summed.
+pub fn order_book(book: order_book::OrderBook) -> OrderBook {
+    fn levels(orders: Vec<order_book::Order>) -> BTreeMap<Decimal, Decimal> {
+        let mut levels = BTreeMap::new();
+        for order in orders {
+            *levels.entry(order.price).or_insert(Decimal::ZERO) += order.amount;
+        }
+        levels
+    }
+
+    let mut converted = OrderBook::new();
+    for (price, quantity) in levels(book.bids) {
+        converted.update(Order::Bid(OrderData::new(price, quantity)));
+    }
+    for (price, quantity) in levels(book.asks) {
+        converted.update(Order::Ask(OrderData::new(price, quantity)));
+    }
+    converted
+}
+
+impl From<order::Side> for Side {
+    fn from(side: order::Side) -> Self {
+        match side {
+            order::Side::Buy => Self::Buy,
+            order::Side::Sell => Self::Sell,
+        }
+    }
+}
+
+impl From<Side> for order::Side {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::Buy => Self::Buy,
+            Side::Sell => Self::Sell,
+        }
+    }
+}
+
+impl From<my_trades::Side> for Side {
+    fn from(side: my_trades::Side) -> Self {
+        match side {
+            my_trades::Side::Buy => Self::Buy,
+            my_trades::Side::Sell => Self::Sell,
+        }
+    }
+}
+
+impl TryFrom<OrderStatus> for OrderReport {
+    type Error = Error;
+
+    fn try_from(status: OrderStatus) -> Result<Self, Self::Error> {
+        let state = if status.is_cancelled {
+            OrderState::Cancelled
+        } else if status.is_live {
+            OrderState::Open
+        } else {
+            OrderState::Filled
+        };
+        Ok(Self {
+            id: status.order_id.to_string(),
+            client_id: status.client_order_id,
+            pair: pair(status.symbol).ok_or(Error::UnknownSymbol)?,
+            side: status.side.into(),
+            price: status.price,
+            quantity: status.original_amount,
+            filled: status.executed_amount,
+            average_price: (!status.executed_amount.is_zero())
+                .then_some(status.avg_execution_price),
+            state,
+            time: status.timestamp,
+        })
+    }
+}
+
+impl TryFrom<MyTrade> for Fill {
+    type Error = Error;
+
+    fn try_from(trade: MyTrade) -> Result<Self, Self::Error> {
+        Ok(Self {
+            trade_id: trade.tid.to_string(),
+            order_id: trade.order_id,
+            pair: pair(trade.symbol).ok_or(Error::UnknownSymbol)?,
+            side: trade.side.into(),
+            price: trade.price,
+            quantity: trade.amount,
+            fee: trade.fee_amount,
+            fee_asset: trade.fee_currency.to_string(),
+            taker: trade.aggressor,
+            time: trade.timestamp,
+        })
+    }
+}
+
+impl From<balances::Balance> for Balance {
+    fn from(balance: balances::Balance) -> Self {
+        Self {
+            asset: balance.currency.to_string(),
+            total: balance.amount,
+            available: balance.available,
+        }
+    }
+}
+
+fn order_id(id: &str) -> Result<u64, Error> {
+    id.parse().map_err(|_| Error::InvalidOrderId(id.to_owned()))
+}
+
+#[async_trait]
+impl MarketDataSource for Gemini {
+    type Error = Error;
+
+    fn pairs(&self) -> Vec<Pair> {
+        Symbol::iter().filter_map(pair).collect()
+    }
+
+    async fn candles(&self, pair: &Pair, interval: Duration) -> Result<Vec<Candle>, Error> {
+        let req = CandleHttpRequest::builder()
+            .symbol(Symbol::try_from(pair)?)
+            .time_range(time_range(interval).ok_or(Error::UnsupportedInterval(interval))?)
+            .build();
+        let mut candles = self.client.request(req).await?.candles;
+        candles.sort_by_key(|candle| candle.time);
+        Ok(candles.into_iter().map(Candle::from).collect())
+    }
+
+    async fn trades(&self, pair: &Pair) -> Result<Vec<Trade>, Error> {
+        let req = TradeHistoryHttpRequest::builder()
+            .symbol(Symbol::try_from(pair)?)
+            .build();
+        let trades = self.client.request(req).await?;
+        Ok(trades.iter().rev().map(Trade::from).collect())
+    }
+
+    async fn order_book(&self, pair: &Pair) -> Result<OrderBook, Error> {
+        let req = OrderBookHttpRequest::builder()
+            .symbol(Symbol::try_from(pair)?)
+            .build();
+        Ok(order_book(self.client.request(req).await?))
+    }
+}
+
+#[async_trait]
+impl OrderGateway for Gemini {
+    type Error = Error;
+
+    async fn place(&self, order: OrderRequest) -> Result<OrderReport, Error> {
+        let symbol = Symbol::try_from(&order.pair)?;
+        let options = match order.time_in_force {
+            TimeInForce::GoodTillCancel => vec![],
+            TimeInForce::ImmediateOrCancel => vec![OrderOption::ImmediateOrCancel],
+            TimeInForce::FillOrKill => vec![OrderOption::FillOrKill],
+            TimeInForce::PostOnly => vec![OrderOption::MakerOrCancel],
+        };
+        let req = match order.client_id {
+            Some(client_id) => NewOrderRequest::builder()
+                .client_order_id(client_id)
+                .symbol(symbol)
+                .amount(order.quantity)
+                .price(order.price)
+                .side(order.side.into())
+                .options(options)
+                .build(),
+            None => NewOrderRequest::builder()
+                .symbol(symbol)
+                .amount(order.quantity)
+                .price(order.price)
+                .side(order.side.into())
+                .options(options)
+                .build(),
+        };
+        OrderReport::try_from(self.client.private_request(req).await?)
+    }
+
+    async fn cancel(&self, id: &str) -> Result<OrderReport, Error> {
+        let req = CancelOrderRequest::builder()
+            .order_id(order_id(id)?)
+            .build();
+        OrderReport::try_from(self.client.private_request(req).await?)
+    }
+
+    async fn status(&self, id: &str) -> Result<OrderReport, Error> {
+        let req = OrderStatusRequest::builder()
+            .order_id(order_id(id)?)
+            .build();
+        OrderReport::try_from(self.client.private_request(req).await?)
+    }
+}
+
+#[async_trait]
+impl Account for Gemini {
+    type Error = Error;
+
+    async fn balances(&self) -> Result<Vec<Balance>, Error> {
+        let balances = self
+            .client
+            .private_request(BalancesRequest::default())
+            .await?;
+        Ok(balances.into_iter().map(Balance::from).collect())
+    }
+
+    async fn fills(&self, pair: &Pair) -> Result<Vec<Fill>, Error> {
+        let req = MyTradesRequest::builder()
+            .symbol(Symbol::try_from(pair)?)
+            .build();
+        let trades = self.client.private_request(req).await?;
+        trades.into_iter().rev().map(Fill::try_from).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_symbols() {
+        assert_eq!(Some(Pair::new("ETH", "BTC")), pair(Symbol::ETHBTC));
+        assert_eq!(None, pair(Symbol::Unknown));
+        assert!(matches!(
+            Symbol::try_from(&Pair::new("btc", "usd")),
+            Ok(Symbol::BTCUSD)
+        ));
+        assert!(matches!(
+            Symbol::try_from(&Pair::new("BTC", "ETH")),
+            Err(Error::UnknownPair(_))
+        ));
+        assert!(matches!(
+            Symbol::try_from(&Pair::new("DOGE", "USD")),
+            Err(Error::UnknownPair(_))
+        ));
+
+        let gemini = Gemini::new(Client::new("https://api.gemini.com".parse().unwrap()));
+        assert!(gemini.pairs().contains(&Pair::new("BTC", "USD")));
+        assert_eq!(3, gemini.pairs().len());
+    }
+
+    #[test]
+    fn test_time_range() {
+        assert_eq!(Some(TimeRange::Hour6), time_range(Duration::hours(6)));
+        assert_eq!(None, time_range(Duration::hours(2)));
+    }
+
+    #[test]
+    fn test_trades() {
+        let json = r#"[{
+            "timestamp": 1547146811,
+            "timestampms": 1547146811357,
+            "tid": 5335307668,
+            "price": "3610.85",
+            "amount": "0.27413495",
+            "exchange": "gemini",
+            "type": "buy",
+            "broken": false
+        }, {
+            "timestamp": 1547146800,
+            "timestampms": 1547146800000,
+            "tid": 5335307660,
+            "price": "3611.00",
+            "amount": "10",
+            "exchange": "gemini",
+            "type": "block",
+            "broken": true
+        }]"#;
+
+        let trades = serde_json::from_str::<Vec<TradeHistory>>(json).unwrap();
+        let buy = Trade::from(&trades[0]);
+        assert_eq!("5335307668", buy.id);
+        assert_eq!(Some(Side::Buy), buy.taker);
+        assert_eq!(TradeKind::Continuous, buy.kind);
+
+        let block = Trade::from(&trades[1]);
+        assert_eq!(None, block.taker);
+        assert_eq!(TradeKind::Block, block.kind);
+        assert!(block.broken);
+    }
+
+    #[test]
+    fn test_order_book() {
+        let json = r#"{
+            "bids": [{"price": "3607.85", "amount": "6.643373", "timestamp": "1547147541"}],
+            "asks": [
+                {"price": "3607.86", "amount": "14.68205084", "timestamp": "1547147541"},
+                {"price": "3607.87", "amount": "1", "timestamp": "1547147541"},
+                {"price": "3607.86", "amount": "0.5", "timestamp": "1547147542"}
+            ]
+        }"#;
+
+        let book = order_book(serde_json::from_str::<order_book::OrderBook>(json).unwrap());
+        assert_eq!(Some(dec!(3607.855)), book.mid());
+        assert_eq!(2, book.asks_len());
+        // repeated prices are summed
+        let best = book.top(Order::Ask(())).unwrap();
+        assert_eq!(
+            (dec!(3607.86), dec!(15.18205084)),
+            (best.price.0, best.quantity.0)
+        );
+    }
+
+    #[test]
+    fn test_order_report() {
+        let json = r#"{
+            "order_id": "106817811",
+            "symbol": "btcusd",
+            "avg_execution_price": "0",
+            "side": "sell",
+            "type": "exchange limit",
+            "timestampms": 1547220404836,
+            "is_live": false,
+            "is_cancelled": true,
+            "executed_amount": "0",
+            "remaining_amount": "5",
+            "client_order_id": "42",
+            "price": "3633.00",
+            "original_amount": "5"
+        }"#;
+
+        let status = serde_json::from_str::<OrderStatus>(json).unwrap();
+        let report = OrderReport::try_from(status).unwrap();
+        assert_eq!("106817811", report.id);
+        assert_eq!(Some("42"), report.client_id.as_deref());
+        assert_eq!(Pair::new("BTC", "USD"), report.pair);
+        assert_eq!(Side::Sell, report.side);
+        assert_eq!(OrderState::Cancelled, report.state);
+        assert_eq!(None, report.average_price);
+        assert_eq!(dec!(5), report.remaining());
+
+        assert!(matches!(order_id("abc"), Err(Error::InvalidOrderId(_))));
+    }
+}
@@ -0,0 +1,14 @@
+//! Exchange-agnostic market data, order and account interfaces, so algorithms can run
+//! against any exchange with an adapter. The adapter for Gemini is in `gemini`, behind
+//! the (default) `gemini` feature. Order books are `market`'s.
+
+pub mod account;
+pub mod data;
+#[cfg(feature = "gemini")]
+pub mod gemini;
+pub mod order;
+
+pub use account::{Account, Balance, Fill};
+pub use data::{Candle, MarketDataSource, Pair, Side, Trade, TradeKind};
+pub use market::order_book::OrderBook;
+pub use order::{OrderGateway, OrderReport, OrderRequest, OrderState, TimeInForce};
@@ -0,0 +1,97 @@
+use crate::data::{Pair, Side};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::fmt;
+
+/// How long a limit order rests on the book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeInForce {
+    GoodTillCancel,
+    /// Fills what it can immediately and cancels the rest.
+    ImmediateOrCancel,
+    /// Fills completely and immediately or not at all.
+    FillOrKill,
+    /// Only adds liquidity, cancelled instead of taking any.
+    PostOnly,
+}
+
+/// A limit order to place on an exchange.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderRequest {
+    pub pair: Pair,
+    pub side: Side,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    pub time_in_force: TimeInForce,
+    /// An id of the caller's choosing, reported back with the order.
+    pub client_id: Option<String>,
+}
+
+impl OrderRequest {
+    /// A good-till-cancel order.
+    pub fn limit(pair: Pair, side: Side, quantity: Decimal, price: Decimal) -> Self {
+        Self {
+            pair,
+            side,
+            quantity,
+            price,
+            time_in_force: TimeInForce::GoodTillCancel,
+            client_id: None,
+        }
+    }
+
+    pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    pub fn with_client_id(mut self, client_id: impl Into<String>) -> Self {
+        self.client_id = Some(client_id.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrderState {
+    /// On the book, possibly partially filled.
+    Open,
+    Filled,
+    /// Cancelled before it was completely filled, possibly partially filled.
+    Cancelled,
+}
+
+/// The state of an order as reported by the exchange.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderReport {
+    /// The exchange's id of the order.
+    pub id: String,
+    pub client_id: Option<String>,
+    pub pair: Pair,
+    pub side: Side,
+    pub price: Option<Decimal>,
+    pub quantity: Decimal,
+    pub filled: Decimal,
+    /// The average price of the fills, if any.
+    pub average_price: Option<Decimal>,
+    pub state: OrderState,
+    pub time: DateTime<Utc>,
+}
+
+impl OrderReport {
+    pub fn remaining(&self) -> Decimal {
+        self.quantity - self.filled
+    }
+}
+
+/// Places and manages orders on an exchange.
+#[async_trait]
+pub trait OrderGateway: Send + Sync {
+    type Error: fmt::Debug + Send;
+
+    async fn place(&self, order: OrderRequest) -> Result<OrderReport, Self::Error>;
+
+    async fn cancel(&self, id: &str) -> Result<OrderReport, Self::Error>;
+
+    async fn status(&self, id: &str) -> Result<OrderReport, Self::Error>;
+}
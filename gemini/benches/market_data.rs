@@ -0,0 +1,105 @@
+//! Compares the derived market data types with `gemini::ws::fast` on recorded feeds.
+//!
+//! The feeds default to the samples in `benches/data`, which are synthetic: a random
+//! walk of an uncrossed BTCUSD book written in the format of the `Recorder`, with
+//! changes, trades and heartbeats in about the proportions of the live feeds. Actual
+//! recordings can be given with the `MARKETV2_FEED` and `MARKET_FEED` environment
+//! variables.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use gemini::ws::{
+    fast,
+    market::{self, Response},
+    marketv2::{self, l2::L2Updates, trade::Trade},
+    record::Frame,
+};
+use serde_json::Value;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+fn frames(var: &str, default: &str) -> Vec<String> {
+    let path = std::env::var(var)
+        .unwrap_or_else(|_| format!("{}/benches/data/{}", env!("CARGO_MANIFEST_DIR"), default));
+    BufReader::new(File::open(&path).unwrap())
+        .lines()
+        .map(|line| serde_json::from_str::<Frame>(&line.unwrap()).unwrap().text)
+        .collect()
+}
+
+fn frames_of_type(frames: &[String], ty: &str) -> Vec<String> {
+    frames
+        .iter()
+        .filter(|frame| serde_json::from_str::<Value>(frame).unwrap()["type"] == ty)
+        .cloned()
+        .collect()
+}
+
+fn bench<D, F>(c: &mut Criterion, name: &str, frames: &[String], derived: D, fast: F)
+where
+    D: Fn(&str),
+    F: Fn(&str),
+{
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(
+        frames.iter().map(|frame| frame.len() as u64).sum(),
+    ));
+    group.bench_function("derived", |b| {
+        b.iter(|| frames.iter().for_each(|frame| derived(frame)))
+    });
+    group.bench_function("fast", |b| {
+        b.iter(|| frames.iter().for_each(|frame| fast(frame)))
+    });
+    group.finish();
+}
+
+fn marketv2(c: &mut Criterion) {
+    let frames = frames("MARKETV2_FEED", "marketv2_btcusd.jsonl");
+
+    bench(
+        c,
+        "l2_updates",
+        &frames_of_type(&frames, "l2_updates"),
+        |frame| {
+            black_box(serde_json::from_str::<L2Updates>(frame).unwrap());
+        },
+        |frame| {
+            black_box(serde_json::from_str::<fast::L2Updates>(frame).unwrap());
+        },
+    );
+
+    // the derived types are read from a subscription through the untagged `Response`
+    bench(
+        c,
+        "trade",
+        &frames_of_type(&frames, "trade"),
+        |frame| {
+            black_box(serde_json::from_str::<marketv2::Response<Trade>>(frame).unwrap());
+        },
+        |frame| {
+            black_box(frame.parse::<fast::Message>().unwrap());
+        },
+    );
+}
+
+fn market(c: &mut Criterion) {
+    let frames = frames("MARKET_FEED", "market_btcusd.jsonl");
+
+    bench(
+        c,
+        "update",
+        &frames_of_type(&frames, "update"),
+        |frame| {
+            black_box(serde_json::from_str::<Response>(frame).unwrap());
+        },
+        |frame| {
+            black_box(market::Update::from(
+                serde_json::from_str::<fast::Update>(frame).unwrap(),
+            ));
+        },
+    );
+}
+
+criterion_group!(benches, marketv2, market);
+criterion_main!(benches);
@@ -0,0 +1,208 @@
+use crate::{
+    common::{AuctionEvent, AuctionPricing, AuctionSchedule},
+    symbol::Symbol,
+};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// How far an auction has got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AuctionPhase {
+    /// Opened for orders, before the first indicative.
+    Open,
+    /// Publishing indicatives.
+    Indicative,
+    /// Ran, successfully or not.
+    Closed,
+}
+
+/// What is known of one auction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuctionStatus {
+    /// `None` if the auction opened before it was tracked, or on the v2 feed, which
+    /// doesn't publish auction openings.
+    pub schedule: Option<AuctionSchedule>,
+    /// Ordered by time.
+    pub indicatives: Vec<AuctionPricing>,
+    pub result: Option<AuctionPricing>,
+}
+
+impl AuctionStatus {
+    fn new(schedule: Option<AuctionSchedule>) -> Self {
+        Self {
+            schedule,
+            indicatives: Vec::new(),
+            result: None,
+        }
+    }
+
+    pub fn phase(&self) -> AuctionPhase {
+        if self.result.is_some() {
+            AuctionPhase::Closed
+        } else if self.indicatives.is_empty() {
+            AuctionPhase::Open
+        } else {
+            AuctionPhase::Indicative
+        }
+    }
+
+    pub fn last_indicative(&self) -> Option<&AuctionPricing> {
+        self.indicatives.last()
+    }
+
+    /// The time of the latest event of the auction.
+    fn last_update(&self) -> Option<DateTime<Utc>> {
+        self.result
+            .as_ref()
+            .or_else(|| self.last_indicative())
+            .map(|pricing| pricing.time)
+            .or_else(|| self.schedule.map(|schedule| schedule.opened))
+    }
+}
+
+/// Follows the current auction of each symbol through its lifecycle from its opening
+/// through its indicatives to its result.
+///
+/// Events of any API can be mixed, e.g. a REST auction history to catch up followed by
+/// a feed. Repeated and out of date events are ignored, and an event which doesn't
+/// belong to the current auction, e.g. an indicative after a result, starts the next.
+#[derive(Debug, Clone, Default)]
+pub struct AuctionTracker {
+    auctions: HashMap<Symbol, AuctionStatus>,
+}
+
+impl AuctionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current or most recent auction of `symbol`.
+    pub fn get(&self, symbol: Symbol) -> Option<&AuctionStatus> {
+        self.auctions.get(&symbol)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Symbol, &AuctionStatus)> {
+        self.auctions
+            .iter()
+            .map(|(symbol, status)| (*symbol, status))
+    }
+
+    /// Applies an auction event of `symbol`, returning the phase of its auction
+    /// afterwards, or `None` if the event was ignored.
+    pub fn apply(&mut self, symbol: Symbol, event: AuctionEvent) -> Option<AuctionPhase> {
+        let current = self.auctions.get_mut(&symbol);
+        if let Some(last_update) = current.as_ref().and_then(|status| status.last_update()) {
+            if event.time() <= last_update {
+                return None;
+            }
+        }
+
+        let starts_next = match (&current, &event) {
+            (None, _) | (_, AuctionEvent::Open(_)) => true,
+            (Some(status), _) => status.phase() == AuctionPhase::Closed,
+        };
+        let status = if starts_next {
+            let schedule = match event {
+                AuctionEvent::Open(schedule) => Some(schedule),
+                _ => None,
+            };
+            self.auctions.insert(symbol, AuctionStatus::new(schedule));
+            self.auctions.get_mut(&symbol).unwrap()
+        } else {
+            current.unwrap()
+        };
+
+        match event {
+            AuctionEvent::Open(_) => (),
+            AuctionEvent::Indicative(pricing) => status.indicatives.push(pricing),
+            AuctionEvent::Result(pricing) => status.result = Some(pricing),
+        }
+        Some(status.phase())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::AuctionOutcome;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn pricing(secs: i64) -> AuctionPricing {
+        AuctionPricing {
+            eid: None,
+            time: Utc.timestamp_opt(secs, 0).unwrap(),
+            highest_bid_price: None,
+            lowest_ask_price: None,
+            collar_price: dec!(100),
+            outcome: AuctionOutcome::Success {
+                price: dec!(101),
+                quantity: dec!(2),
+            },
+        }
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let schedule = AuctionSchedule {
+            opened: Utc.timestamp_opt(0, 0).unwrap(),
+            auction_time: Utc.timestamp_opt(100, 0).unwrap(),
+            first_indicative: Utc.timestamp_opt(40, 0).unwrap(),
+            last_cancel_time: Utc.timestamp_opt(95, 0).unwrap(),
+        };
+        let mut tracker = AuctionTracker::new();
+
+        let open = AuctionEvent::Open(schedule);
+        assert_eq!(
+            Some(AuctionPhase::Open),
+            tracker.apply(Symbol::BTCUSD, open)
+        );
+        assert_eq!(
+            Some(AuctionPhase::Indicative),
+            tracker.apply(Symbol::BTCUSD, AuctionEvent::Indicative(pricing(40)))
+        );
+        assert_eq!(
+            Some(AuctionPhase::Indicative),
+            tracker.apply(Symbol::BTCUSD, AuctionEvent::Indicative(pricing(70)))
+        );
+        // repeated and out of date
+        assert_eq!(
+            None,
+            tracker.apply(Symbol::BTCUSD, AuctionEvent::Indicative(pricing(70)))
+        );
+        assert_eq!(
+            None,
+            tracker.apply(Symbol::BTCUSD, AuctionEvent::Indicative(pricing(50)))
+        );
+        assert_eq!(
+            Some(AuctionPhase::Closed),
+            tracker.apply(Symbol::BTCUSD, AuctionEvent::Result(pricing(100)))
+        );
+
+        let status = tracker.get(Symbol::BTCUSD).unwrap();
+        assert_eq!(Some(schedule), status.schedule);
+        assert_eq!(vec![pricing(40), pricing(70)], status.indicatives);
+        assert_eq!(Some(pricing(100)), status.result);
+        assert!(tracker.get(Symbol::ETHUSD).is_none());
+    }
+
+    #[test]
+    fn test_next_auction() {
+        let mut tracker = AuctionTracker::new();
+
+        // joined during the indicatives, without the opening
+        tracker.apply(Symbol::ETHUSD, AuctionEvent::Indicative(pricing(40)));
+        tracker.apply(Symbol::ETHUSD, AuctionEvent::Result(pricing(100)));
+        assert_eq!(None, tracker.get(Symbol::ETHUSD).unwrap().schedule);
+
+        // an indicative after the result belongs to the next auction
+        assert_eq!(
+            Some(AuctionPhase::Indicative),
+            tracker.apply(Symbol::ETHUSD, AuctionEvent::Indicative(pricing(86_440)))
+        );
+        let status = tracker.get(Symbol::ETHUSD).unwrap();
+        assert_eq!(vec![pricing(86_440)], status.indicatives);
+        assert_eq!(None, status.result);
+        assert_eq!(1, tracker.iter().count());
+    }
+}
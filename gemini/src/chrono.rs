@@ -1,14 +1,177 @@
-use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use chrono::{DateTime, TimeZone, Utc};
 
-pub trait FromMilliseconds {
-    fn from_milliseconds(ms: i64) -> Self;
+pub trait FromMilliseconds: Sized {
+    /// The time `ms` milliseconds after the UNIX epoch, or `None` if it is out of range.
+    fn from_milliseconds(ms: i64) -> Option<Self>;
 }
 
 impl FromMilliseconds for DateTime<Utc> {
-    fn from_milliseconds(ms: i64) -> Self {
-        DateTime::<Utc>::from_utc(
-            NaiveDateTime::from_timestamp(Duration::milliseconds(ms).num_seconds(), 0),
-            Utc,
-        )
+    fn from_milliseconds(ms: i64) -> Option<Self> {
+        Utc.timestamp_millis_opt(ms).single()
+    }
+}
+
+/// (De)serializes a `DateTime<Utc>` as milliseconds since the UNIX epoch, for use with
+/// `#[serde(with = "crate::chrono::ts_milliseconds")]`.
+///
+/// Unlike `chrono::serde::ts_milliseconds`, timestamps may also be given as strings
+/// of digits, as some of Gemini's endpoints do, and out of range timestamps are errors.
+pub mod ts_milliseconds {
+    use super::FromMilliseconds;
+    use chrono::{DateTime, Utc};
+    use serde::{de, Deserializer, Serializer};
+    use std::{convert::TryFrom, fmt};
+
+    pub fn serialize<S: Serializer>(
+        time: &DateTime<Utc>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(time.timestamp_millis())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        deserializer.deserialize_any(Visitor)
+    }
+
+    struct Visitor;
+
+    impl<'de> de::Visitor<'de> for Visitor {
+        type Value = DateTime<Utc>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a timestamp in milliseconds")
+        }
+
+        fn visit_i64<E: de::Error>(self, ms: i64) -> Result<Self::Value, E> {
+            DateTime::<Utc>::from_milliseconds(ms)
+                .ok_or_else(|| E::custom(format!("timestamp out of range: {}", ms)))
+        }
+
+        fn visit_u64<E: de::Error>(self, ms: u64) -> Result<Self::Value, E> {
+            let ms = i64::try_from(ms)
+                .map_err(|_| E::custom(format!("timestamp out of range: {}", ms)))?;
+            self.visit_i64(ms)
+        }
+
+        fn visit_str<E: de::Error>(self, ms: &str) -> Result<Self::Value, E> {
+            let ms = ms
+                .parse::<i64>()
+                .map_err(|_| E::invalid_value(de::Unexpected::Str(ms), &self))?;
+            self.visit_i64(ms)
+        }
+    }
+}
+
+/// Like `ts_milliseconds`, for optional timestamps which may be `null` or missing.
+pub mod ts_milliseconds_option {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Wrapper(#[serde(with = "super::ts_milliseconds")] DateTime<Utc>);
+
+    pub fn serialize<S: Serializer>(
+        time: &Option<DateTime<Utc>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        time.map(Wrapper).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<DateTime<Utc>>, D::Error> {
+        Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|wrapper| wrapper.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{common::Candle, ws::marketv2::trade::Trade};
+    use proptest::prelude::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Times {
+        #[serde(with = "ts_milliseconds")]
+        time: DateTime<Utc>,
+        #[serde(default, with = "ts_milliseconds_option")]
+        optional: Option<DateTime<Utc>>,
+    }
+
+    // The milliseconds `DateTime<Utc>` can represent.
+    fn valid_ms() -> impl Strategy<Value = i64> {
+        let min = DateTime::<Utc>::MIN_UTC.timestamp_millis();
+        let max = DateTime::<Utc>::MAX_UTC.timestamp_millis();
+        min..=max
+    }
+
+    #[test]
+    fn test_from_milliseconds() {
+        let time = DateTime::<Utc>::from_milliseconds(1559755800123).unwrap();
+        assert_eq!(1559755800123, time.timestamp_millis());
+        assert_eq!(123_000_000, time.timestamp_subsec_nanos());
+
+        let before_epoch = DateTime::<Utc>::from_milliseconds(-1).unwrap();
+        assert_eq!("1969-12-31T23:59:59.999+00:00", before_epoch.to_rfc3339());
+        assert_eq!(None, DateTime::<Utc>::from_milliseconds(i64::MAX));
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let times = serde_json::from_str::<Times>(r#"{"time":"1483018200123"}"#).unwrap();
+        assert_eq!(1483018200123, times.time.timestamp_millis());
+        assert_eq!(None, times.optional);
+
+        let times =
+            serde_json::from_str::<Times>(r#"{"time":1,"optional":1483018200123}"#).unwrap();
+        assert_eq!(
+            Some(1483018200123),
+            times.optional.map(|t| t.timestamp_millis())
+        );
+
+        for json in &[
+            r#"{"time":9223372036854775807}"#,
+            r#"{"time":18446744073709551615}"#,
+            r#"{"time":"soon"}"#,
+            r#"{"time":1.5}"#,
+        ] {
+            assert!(serde_json::from_str::<Times>(json).is_err(), "{}", json);
+        }
+        assert!(serde_json::from_str::<Candle>(r#"[9223372036854775807,1,1,1,1,1]"#).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn test_roundtrip(time in valid_ms(), optional in proptest::option::of(valid_ms())) {
+            let json = format!(
+                r#"{{"time":{},"optional":{}}}"#,
+                time,
+                optional.map_or("null".to_string(), |ms| ms.to_string())
+            );
+            let times = serde_json::from_str::<Times>(&json).unwrap();
+            prop_assert_eq!(time, times.time.timestamp_millis());
+            prop_assert_eq!(optional, times.optional.map(|t| t.timestamp_millis()));
+            prop_assert_eq!(json, serde_json::to_string(&times).unwrap());
+        }
+
+        #[test]
+        fn test_candle_time(time in valid_ms()) {
+            let json = format!("[{},7781.6,7820.23,7776.56,7819.39,34.76]", time);
+            let candle = serde_json::from_str::<Candle>(&json).unwrap();
+            prop_assert_eq!(time, candle.time.timestamp_millis());
+        }
+
+        #[test]
+        fn test_trade_time(time in valid_ms()) {
+            let json = format!(
+                r#"{{"type":"trade","symbol":"BTCUSD","event_id":1,"timestamp":{},"price":"1","quantity":"1","side":"buy"}}"#,
+                time
+            );
+            let trade = serde_json::from_str::<Trade>(&json).unwrap();
+            prop_assert_eq!(time, trade.timestamp.timestamp_millis());
+        }
     }
 }
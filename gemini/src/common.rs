@@ -1,12 +1,21 @@
-use crate::chrono::FromMilliseconds;
+use crate::{
+    chrono::FromMilliseconds,
+    rest::{auction_history, trade_history},
+    ws::{market, marketv2},
+};
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use serde::Deserialize;
+use serde::{
+    ser::{Error as _, SerializeMap, SerializeTuple},
+    Deserialize, Serialize, Serializer,
+};
+use serde_json::value::RawValue;
+use std::convert::TryFrom;
 
 type CandleRepr = (i64, Decimal, Decimal, Decimal, Decimal, Decimal);
 
 #[derive(Deserialize, Debug, Clone)]
-#[serde(from = "CandleRepr")]
+#[serde(try_from = "CandleRepr")]
 pub struct Candle {
     pub time: DateTime<Utc>,
     pub open: Decimal,
@@ -16,15 +25,598 @@ pub struct Candle {
     pub volume: Decimal,
 }
 
-impl From<CandleRepr> for Candle {
-    fn from(repr: CandleRepr) -> Self {
-        Self {
-            time: DateTime::<Utc>::from_milliseconds(repr.0),
+impl TryFrom<CandleRepr> for Candle {
+    type Error = String;
+
+    fn try_from(repr: CandleRepr) -> Result<Self, Self::Error> {
+        Ok(Self {
+            time: DateTime::<Utc>::from_milliseconds(repr.0)
+                .ok_or_else(|| format!("timestamp out of range: {}", repr.0))?,
             open: repr.1,
             high: repr.2,
             low: repr.3,
             close: repr.4,
             volume: repr.5,
+        })
+    }
+}
+
+/// Candles are written as numbers rather than the strings used for decimals elsewhere.
+impl Serialize for Candle {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(6)?;
+        tuple.serialize_element(&self.time.timestamp_millis())?;
+        for value in &[self.open, self.high, self.low, self.close, self.volume] {
+            tuple.serialize_element(&Number(value))?;
+        }
+        tuple.end()
+    }
+}
+
+/// Serializes a decimal as a JSON number with exactly its digits rather than the
+/// nearest `f64`. Only JSON serializers can write it.
+struct Number<'a>(&'a Decimal);
+
+impl Serialize for Number<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RawValue::from_string(self.0.to_string())
+            .map_err(S::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+/// The result of an auction or indicative flattened into its event, for use with
+/// `#[serde(into = "crate::common::AuctionResultRepr")]`.
+///
+/// Gemini writes failed auctions with a zero price and quantity. The fields are named
+/// differently by each API.
+pub(crate) struct AuctionResultRepr {
+    result: &'static str,
+    price: &'static str,
+    // `None` if the quantity isn't part of the result
+    quantity: Option<&'static str>,
+    // the price and quantity of a success
+    success: Option<(Decimal, Decimal)>,
+}
+
+impl AuctionResultRepr {
+    fn auction(result: &'static str, success: Option<(Decimal, Decimal)>) -> Self {
+        Self {
+            result,
+            price: "auction_price",
+            quantity: Some("auction_quantity"),
+            success,
+        }
+    }
+}
+
+impl Serialize for AuctionResultRepr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (result, (price, quantity)) = match self.success {
+            Some(success) => ("success", success),
+            None => ("failure", (Decimal::ZERO, Decimal::ZERO)),
+        };
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry(self.result, result)?;
+        map.serialize_entry(self.price, &price)?;
+        if let Some(key) = self.quantity {
+            map.serialize_entry(key, &quantity)?;
+        }
+        map.end()
+    }
+}
+
+impl From<auction_history::AuctionResult> for AuctionResultRepr {
+    fn from(result: auction_history::AuctionResult) -> Self {
+        let success = match result {
+            auction_history::AuctionResult::Success {
+                auction_price,
+                auction_quantity,
+            } => Some((auction_price, auction_quantity)),
+            auction_history::AuctionResult::Failure => None,
+        };
+        Self::auction("auction_result", success)
+    }
+}
+
+impl From<market::auction::AuctionIndicativeResult> for AuctionResultRepr {
+    fn from(result: market::auction::AuctionIndicativeResult) -> Self {
+        use market::auction::AuctionIndicativeResult;
+
+        // the quantity is a field of the indicative
+        Self {
+            result: "result",
+            price: "indicative_price",
+            quantity: None,
+            success: match result {
+                AuctionIndicativeResult::Success { indicative_price } => {
+                    Some((indicative_price, Decimal::ZERO))
+                }
+                AuctionIndicativeResult::Failure => None,
+            },
+        }
+    }
+}
+
+impl From<market::auction::AuctionOutcomeResult> for AuctionResultRepr {
+    fn from(result: market::auction::AuctionOutcomeResult) -> Self {
+        let success = match result {
+            market::auction::AuctionOutcomeResult::Success {
+                auction_price,
+                auction_quantity,
+            } => Some((auction_price, auction_quantity)),
+            market::auction::AuctionOutcomeResult::Failure => None,
+        };
+        Self::auction("result", success)
+    }
+}
+
+impl From<marketv2::auction::Result> for AuctionResultRepr {
+    fn from(result: marketv2::auction::Result) -> Self {
+        let success = match result {
+            marketv2::auction::Result::Success {
+                auction_price,
+                auction_quantity,
+            } => Some((auction_price, auction_quantity)),
+            marketv2::auction::Result::Failure => None,
+        };
+        Self::auction("result", success)
+    }
+}
+
+/// How a trade was matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TradeKind {
+    /// On the continuous order book.
+    Continuous,
+    Auction,
+    /// Negotiated off the order book.
+    Block,
+}
+
+impl From<trade_history::TradeType> for TradeKind {
+    fn from(ty: trade_history::TradeType) -> Self {
+        match ty {
+            trade_history::TradeType::Buy | trade_history::TradeType::Sell => Self::Continuous,
+            trade_history::TradeType::Auction => Self::Auction,
+            trade_history::TradeType::Block => Self::Block,
+        }
+    }
+}
+
+impl From<market::trade::MakerSide> for TradeKind {
+    fn from(side: market::trade::MakerSide) -> Self {
+        match side {
+            market::trade::MakerSide::Bid | market::trade::MakerSide::Ask => Self::Continuous,
+            market::trade::MakerSide::Auction => Self::Auction,
+        }
+    }
+}
+
+/// A trade, whichever API it was received from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trade {
+    pub tid: u64,
+    pub time: DateTime<Utc>,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub kind: TradeKind,
+    /// Whether the exchange broke, i.e. reversed, the trade after it executed. Only the
+    /// REST trade history reports breaks.
+    pub broken: bool,
+}
+
+impl Trade {
+    /// The trade of a v1 market data event, which doesn't carry its time, or `None` for
+    /// other events.
+    pub fn from_v1(event: &market::Event, time: DateTime<Utc>) -> Option<Self> {
+        let (data, kind) = match event {
+            market::Event::Trade(trade) => (&trade.data, trade.maker_side.into()),
+            market::Event::BlockTrade(trade) => (&trade.data, TradeKind::Block),
+            _ => return None,
+        };
+        Some(Self {
+            tid: data.tid,
+            time,
+            price: data.price,
+            quantity: data.amount,
+            kind,
+            broken: false,
+        })
+    }
+}
+
+impl From<&trade_history::TradeHistory> for Trade {
+    fn from(trade: &trade_history::TradeHistory) -> Self {
+        Self {
+            tid: trade.tid,
+            time: trade.timestamp,
+            price: trade.price,
+            quantity: trade.amount,
+            kind: trade.ty.into(),
+            broken: trade.broken,
+        }
+    }
+}
+
+// The v2 feed doesn't tell auction trades apart, its block trades aren't published.
+impl From<&marketv2::trade::Trade> for Trade {
+    fn from(trade: &marketv2::trade::Trade) -> Self {
+        Self {
+            tid: trade.event_id,
+            time: trade.timestamp,
+            price: trade.price,
+            quantity: trade.quantity,
+            kind: TradeKind::Continuous,
+            broken: false,
+        }
+    }
+}
+
+/// The times of an auction, published by the v1 feed when it opens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AuctionSchedule {
+    pub opened: DateTime<Utc>,
+    /// When the auction runs.
+    pub auction_time: DateTime<Utc>,
+    pub first_indicative: DateTime<Utc>,
+    /// Auction-only orders can't be cancelled after this time.
+    pub last_cancel_time: DateTime<Utc>,
+}
+
+/// The price and quantity an auction ran at, or would run at for an indicative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuctionOutcome {
+    Success { price: Decimal, quantity: Decimal },
+    Failure,
+}
+
+/// An indicative or the result of an auction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AuctionPricing {
+    /// Not published by the v2 feed.
+    pub eid: Option<u64>,
+    pub time: DateTime<Utc>,
+    /// The best bid of the continuous order book, if any.
+    pub highest_bid_price: Option<Decimal>,
+    /// The best ask of the continuous order book, if any.
+    pub lowest_ask_price: Option<Decimal>,
+    /// An auction only succeeds within five percent of the collar price.
+    pub collar_price: Decimal,
+    pub outcome: AuctionOutcome,
+}
+
+impl AuctionPricing {
+    /// The largest fraction of the collar price an auction price may differ from it by,
+    /// i.e. five percent.
+    pub fn collar() -> Decimal {
+        Decimal::new(5, 2)
+    }
+
+    /// The fraction of the collar price the price differs from it by, e.g. -0.02 for a
+    /// price 2% below it, or `None` for failures.
+    pub fn collar_deviation(&self) -> Option<Decimal> {
+        match self.outcome {
+            AuctionOutcome::Success { price, .. } if !self.collar_price.is_zero() => {
+                Some((price - self.collar_price) / self.collar_price)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the price is close enough to the collar price for the auction to run at
+    /// it, `false` for failures.
+    pub fn within_collar(&self) -> bool {
+        matches!(self.collar_deviation(), Some(deviation) if deviation.abs() <= Self::collar())
+    }
+}
+
+/// An auction event, whichever API it was received from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuctionEvent {
+    Open(AuctionSchedule),
+    Indicative(AuctionPricing),
+    Result(AuctionPricing),
+}
+
+impl AuctionEvent {
+    pub fn time(&self) -> DateTime<Utc> {
+        match self {
+            Self::Open(schedule) => schedule.opened,
+            Self::Indicative(pricing) | Self::Result(pricing) => pricing.time,
+        }
+    }
+}
+
+impl From<auction_history::Auction> for AuctionEvent {
+    fn from(auction: auction_history::Auction) -> Self {
+        let pricing = AuctionPricing {
+            eid: Some(auction.eid),
+            time: auction.timestamp,
+            highest_bid_price: auction.highest_bid_price,
+            lowest_ask_price: auction.lowest_ask_price,
+            collar_price: auction.collar_price,
+            outcome: match auction.auction_result {
+                auction_history::AuctionResult::Success {
+                    auction_price,
+                    auction_quantity,
+                } => AuctionOutcome::Success {
+                    price: auction_price,
+                    quantity: auction_quantity,
+                },
+                auction_history::AuctionResult::Failure => AuctionOutcome::Failure,
+            },
+        };
+        match auction.event_type {
+            auction_history::EventType::Indicative => Self::Indicative(pricing),
+            auction_history::EventType::Auction => Self::Result(pricing),
+        }
+    }
+}
+
+fn v1_pricing(data: market::auction::AuctionData, outcome: AuctionOutcome) -> AuctionPricing {
+    AuctionPricing {
+        eid: Some(data.eid),
+        time: data.timestamp,
+        highest_bid_price: data.highest_bid_price,
+        lowest_ask_price: data.lowest_ask_price,
+        collar_price: data.collar_price,
+        outcome,
+    }
+}
+
+impl From<market::auction::Auction> for AuctionEvent {
+    fn from(auction: market::auction::Auction) -> Self {
+        use market::auction::{Auction, AuctionIndicativeResult, AuctionOutcomeResult};
+
+        match auction {
+            Auction::Open(open) => Self::Open(AuctionSchedule {
+                opened: open.auction_open_ms,
+                auction_time: open.auction_time_ms,
+                first_indicative: open.first_indicative_ms,
+                last_cancel_time: open.last_cancel_time_ms,
+            }),
+            Auction::Indicative(indicative) => Self::Indicative(v1_pricing(
+                indicative.data,
+                match indicative.result {
+                    AuctionIndicativeResult::Success { indicative_price } => {
+                        AuctionOutcome::Success {
+                            price: indicative_price,
+                            quantity: indicative.indicative_quantity,
+                        }
+                    }
+                    AuctionIndicativeResult::Failure => AuctionOutcome::Failure,
+                },
+            )),
+            Auction::Outcome(outcome) => Self::Result(v1_pricing(
+                outcome.data,
+                match outcome.result {
+                    AuctionOutcomeResult::Success {
+                        auction_price,
+                        auction_quantity,
+                    } => AuctionOutcome::Success {
+                        price: auction_price,
+                        quantity: auction_quantity,
+                    },
+                    AuctionOutcomeResult::Failure => AuctionOutcome::Failure,
+                },
+            )),
+        }
+    }
+}
+
+impl From<marketv2::auction::AuctionData> for AuctionPricing {
+    fn from(data: marketv2::auction::AuctionData) -> Self {
+        Self {
+            eid: None,
+            time: data.timestamp,
+            highest_bid_price: data.highest_bid_price,
+            lowest_ask_price: data.lowest_ask_price,
+            collar_price: data.collar_price,
+            outcome: match data.result {
+                marketv2::auction::Result::Success {
+                    auction_price,
+                    auction_quantity,
+                } => AuctionOutcome::Success {
+                    price: auction_price,
+                    quantity: auction_quantity,
+                },
+                marketv2::auction::Result::Failure => AuctionOutcome::Failure,
+            },
+        }
+    }
+}
+
+/// The symbol of a v2 auction is `marketv2::auction::Auction::symbol`.
+impl From<marketv2::auction::Auction> for AuctionEvent {
+    fn from(auction: marketv2::auction::Auction) -> Self {
+        match auction {
+            marketv2::auction::Auction::Indicative(indicative) => {
+                Self::Indicative(indicative.data.into())
+            }
+            marketv2::auction::Auction::Result(result) => Self::Result(result.data.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::symbol::Symbol;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_candle_serialize() {
+        let candle = Candle {
+            time: DateTime::<Utc>::from_milliseconds(1559755800000).unwrap(),
+            open: dec!(7781.6),
+            high: dec!(7800),
+            low: dec!(-1.5),
+            close: dec!(0.1234567890123456789),
+            volume: dec!(12345678901234.56789),
+        };
+        assert_eq!(
+            "[1559755800000,7781.6,7800,-1.5,0.1234567890123456789,12345678901234.56789]",
+            serde_json::to_string(&candle).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_trades() {
+        let history = serde_json::from_str::<Vec<trade_history::TradeHistory>>(
+            r#"[{
+                "timestamp": 1547146811,
+                "timestampms": 1547146811357,
+                "tid": 5335307668,
+                "price": "3610.85",
+                "amount": "0.27413495",
+                "exchange": "gemini",
+                "type": "buy",
+                "broken": false
+            }, {
+                "timestamp": 1547146811,
+                "timestampms": 1547146811357,
+                "tid": 5335307669,
+                "price": "3600",
+                "amount": "1000",
+                "exchange": "gemini",
+                "type": "block",
+                "broken": true
+            }]"#,
+        )
+        .unwrap();
+        let trades = history.iter().map(Trade::from).collect::<Vec<_>>();
+        assert_eq!(5335307668, trades[0].tid);
+        assert_eq!(TradeKind::Continuous, trades[0].kind);
+        assert!(!trades[0].broken);
+        assert_eq!(TradeKind::Block, trades[1].kind);
+        assert!(trades[1].broken);
+
+        let time = DateTime::<Utc>::from_milliseconds(1547146811357).unwrap();
+        let auction = serde_json::from_str::<market::Event>(
+            r#"{"type":"trade","tid":5375547515,"price":"3632.54","amount":"1","makerSide":"auction"}"#,
+        )
+        .unwrap();
+        let trade = Trade::from_v1(&auction, time).unwrap();
+        assert_eq!((5375547515, TradeKind::Auction), (trade.tid, trade.kind));
+        assert_eq!(dec!(1), trade.quantity);
+
+        let block = serde_json::from_str::<market::Event>(
+            r#"{"type":"block_trade","tid":1111597035,"price":"10100.00","amount":"1000"}"#,
+        )
+        .unwrap();
+        assert_eq!(TradeKind::Block, Trade::from_v1(&block, time).unwrap().kind);
+    }
+
+    #[test]
+    fn test_auction_events() {
+        let v1 = serde_json::from_str::<market::auction::Auction>(
+            r#"{
+                "type": "auction_indicative",
+                "eid": 2248762586,
+                "result": "success",
+                "time_ms": 1510865640000,
+                "highest_bid_price": "7730.69",
+                "lowest_ask_price": "7730.7",
+                "collar_price": "7730.695",
+                "indicative_price": "7750",
+                "indicative_quantity": "45.43325086"
+            }"#,
+        )
+        .unwrap();
+        let pricing = AuctionPricing {
+            eid: Some(2248762586),
+            time: DateTime::<Utc>::from_milliseconds(1510865640000).unwrap(),
+            highest_bid_price: Some(dec!(7730.69)),
+            lowest_ask_price: Some(dec!(7730.7)),
+            collar_price: dec!(7730.695),
+            outcome: AuctionOutcome::Success {
+                price: dec!(7750),
+                quantity: dec!(45.43325086),
+            },
+        };
+        assert_eq!(AuctionEvent::Indicative(pricing), v1.into());
+
+        let v2 = serde_json::from_str::<marketv2::auction::Auction>(
+            r#"{
+                "type": "auction_indicative",
+                "symbol": "BTCUSD",
+                "result": "success",
+                "time_ms": 1510865640000,
+                "highest_bid_price": "7730.69",
+                "lowest_ask_price": "7730.7",
+                "collar_price": "7730.695",
+                "auction_price": "7750",
+                "auction_quantity": "45.43325086"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(Symbol::BTCUSD, v2.symbol());
+        let v2 = AuctionEvent::from(v2);
+        assert_eq!(
+            AuctionEvent::Indicative(AuctionPricing {
+                eid: None,
+                ..pricing
+            }),
+            v2
+        );
+        assert_eq!(pricing.time, v2.time());
+
+        assert_eq!(
+            Some(dec!(0.0025)),
+            pricing.collar_deviation().map(|d| d.round_dp(4))
+        );
+        assert!(pricing.within_collar());
+        let outside = AuctionPricing {
+            outcome: AuctionOutcome::Success {
+                price: dec!(8200),
+                quantity: dec!(1),
+            },
+            ..pricing
+        };
+        assert!(!outside.within_collar());
+
+        let rest = serde_json::from_str::<auction_history::Auction>(
+            r#"{
+                "auction_id": 3,
+                "auction_price": "0",
+                "auction_quantity": "0",
+                "eid": 3920,
+                "collar_price": "1.1",
+                "auction_result": "failure",
+                "timestamp": 1471902471,
+                "timestampms": 1471902471225,
+                "event_type": "auction"
+            }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            rest.into(),
+            AuctionEvent::Result(AuctionPricing {
+                eid: Some(3920),
+                highest_bid_price: None,
+                outcome: AuctionOutcome::Failure,
+                ..
+            })
+        ));
+
+        let open = serde_json::from_str::<market::auction::Auction>(
+            r#"{
+                "auction_open_ms": 1486591200000,
+                "auction_time_ms": 1486674000000,
+                "first_indicative_ms": 1486673400000,
+                "last_cancel_time_ms": 1486673985000,
+                "type": "auction_open"
+            }"#,
+        )
+        .unwrap();
+        match AuctionEvent::from(open) {
+            AuctionEvent::Open(schedule) => {
+                assert_eq!(1486591200000, schedule.opened.timestamp_millis());
+                assert_eq!(1486674000000, schedule.auction_time.timestamp_millis());
+                assert_eq!(1486673985000, schedule.last_cancel_time.timestamp_millis());
+            }
+            event => panic!("expected an open event, got {:?}", event),
         }
     }
 }
@@ -4,8 +4,71 @@ pub trait Price: StrLike {}
 
 pub trait Quantity: StrLike {}
 
+/// A currency held in an account, e.g. the base or quote currency of a `Symbol`.
+#[derive(
+    serde::Deserialize,
+    serde::Serialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    strum_macros::AsRefStr,
+    strum_macros::Display,
+    strum_macros::EnumString,
+    strum_macros::IntoStaticStr,
+)]
+#[serde(try_from = "&str", into = "&str")]
+pub enum Currency {
+    #[strum(ascii_case_insensitive)]
+    USD,
+    #[strum(ascii_case_insensitive)]
+    BTC,
+    #[strum(ascii_case_insensitive)]
+    ETH,
+}
+
+// The currencies Gemini quotes prices in, fiat and stablecoins before cryptocurrencies
+// and longer tickers first so that e.g. GUSD isn't taken for USD.
+const QUOTE_CURRENCIES: [&str; 13] = [
+    "GUSD", "USDC", "USDT", "DAI", "USD", "EUR", "GBP", "SGD", "BTC", "ETH", "BCH", "LTC", "FIL",
+];
+
+/// Splits a pair as Gemini names it, e.g. `LTCBTC`, into its base and quote currency,
+/// or returns `None` if it isn't quoted in a currency Gemini quotes prices in.
+pub fn split_pair(pair: &str) -> Option<(&str, &str)> {
+    QUOTE_CURRENCIES.iter().find_map(|quote| {
+        let base = pair.strip_suffix(quote)?;
+        (!base.is_empty()).then(|| (base, &pair[base.len()..]))
+    })
+}
+
+/// Whether `first` is the quote currency of a pair of `first` and `second`, e.g. of
+/// USD and BTC, telling by the currencies Gemini quotes prices in. Otherwise `second`
+/// is, as e.g. of two currencies Gemini doesn't quote prices in.
+pub(crate) fn quoted_first(first: &str, second: &str) -> bool {
+    let rank = |currency: &str| {
+        QUOTE_CURRENCIES
+            .iter()
+            .position(|quote| quote.eq_ignore_ascii_case(currency))
+    };
+    match (rank(first), rank(second)) {
+        (Some(first), Some(second)) => first < second,
+        (first, _) => first.is_some(),
+    }
+}
+
+impl std::convert::TryFrom<&str> for Currency {
+    type Error = strum::ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 macro_rules! impl_currency {
-    ($curr:ident, $repr:literal, $($tr:ident),*) => {
+    ($curr:ident, $variant:ident, $repr:literal, $($tr:ident),*) => {
         #[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy)]
         pub struct $curr;
 
@@ -30,9 +93,44 @@ macro_rules! impl_currency {
                 }
             }
         }
+
+        impl From<$curr> for Currency {
+            fn from(_: $curr) -> Currency {
+                Currency::$variant
+            }
+        }
     };
 }
 
-impl_currency!(USDollar, "usd", Quantity);
-impl_currency!(Bitcoin, "btc", Quantity, Price);
-impl_currency!(Ethereum, "eth", Quantity, Price);
+impl_currency!(USDollar, USD, "usd", Quantity);
+impl_currency!(Bitcoin, BTC, "btc", Quantity, Price);
+impl_currency!(Ethereum, ETH, "eth", Quantity, Price);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_currency() {
+        assert_eq!(Currency::BTC, Bitcoin.into());
+        assert_eq!(Ok(Currency::USD), "usd".parse());
+        assert_eq!(
+            Currency::ETH,
+            serde_json::from_str::<Currency>(r#""ETH""#).unwrap()
+        );
+        assert_eq!(r#""USD""#, serde_json::to_string(&Currency::USD).unwrap());
+        assert!(serde_json::from_str::<Currency>(r#""XYZ""#).is_err());
+    }
+
+    #[test]
+    fn test_pairs() {
+        assert_eq!(Some(("ETH", "GUSD")), split_pair("ETHGUSD"));
+        assert_eq!(Some(("SOL", "USD")), split_pair("SOLUSD"));
+        assert_eq!(None, split_pair("USD"));
+
+        assert!(quoted_first("USD", "LTC"));
+        assert!(!quoted_first("LTC", "BTC"));
+        assert!(quoted_first("BTC", "ETH"));
+        assert!(!quoted_first("SOL", "XYZ"));
+    }
+}
@@ -1,4 +1,4 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 pub const fn error_code_reason(ec: u16) -> &'static str {
     match ec {
@@ -18,14 +18,28 @@ pub const fn error_code_reason(ec: u16) -> &'static str {
 
 crate::string_field_impl!(ErrorResult, "error");
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Error {
     result: ErrorResult,
     reason: ErrorReason,
     message: String,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+impl Error {
+    pub fn reason(&self) -> &ErrorReason {
+        &self.reason
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}: {}", self.reason, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub enum ErrorReason {
     AuctionNotOpen, //Failed to place an auction-only order because there is no current auction open for this symbol
     ClientOrderIdTooLong, // 	The Client Order ID must be under 100 characters
@@ -1,7 +1,9 @@
+pub mod auction;
 pub mod chrono;
 pub mod common;
 pub mod currency;
 pub mod error;
+pub mod resolution;
 pub mod rest;
 pub mod symbol;
 pub mod util;
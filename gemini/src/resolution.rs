@@ -0,0 +1,202 @@
+use crate::{
+    rest::candles::TimeRange,
+    ws::marketv2::{candle::CandleType, SubscriptionType},
+};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::convert::TryFrom;
+
+/// The granularity of market data, shared by the REST candles, the candle feeds and
+/// algorithms.
+///
+/// Gemini serves candles of the resolutions which convert to a `TimeRange`; the others
+/// are built from trades or resampled from finer candles. Candles are aligned to UTC:
+/// days start at midnight, longer intervals at multiples of their length since the
+/// UNIX epoch, and weeks on Mondays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Resolution {
+    /// Individual trades rather than candles.
+    Tick,
+    Second,
+    Minute,
+    Minute5,
+    Minute15,
+    Minute30,
+    Hour,
+    Hour4,
+    Hour6,
+    Day,
+    Week,
+}
+
+impl Resolution {
+    pub const ALL: [Self; 11] = [
+        Self::Tick,
+        Self::Second,
+        Self::Minute,
+        Self::Minute5,
+        Self::Minute15,
+        Self::Minute30,
+        Self::Hour,
+        Self::Hour4,
+        Self::Hour6,
+        Self::Day,
+        Self::Week,
+    ];
+
+    /// The interval of a candle of this resolution, or `None` for ticks.
+    pub fn to_duration(self) -> Option<Duration> {
+        Some(match self {
+            Self::Tick => return None,
+            Self::Second => Duration::seconds(1),
+            Self::Minute => Duration::minutes(1),
+            Self::Minute5 => Duration::minutes(5),
+            Self::Minute15 => Duration::minutes(15),
+            Self::Minute30 => Duration::minutes(30),
+            Self::Hour => Duration::hours(1),
+            Self::Hour4 => Duration::hours(4),
+            Self::Hour6 => Duration::hours(6),
+            Self::Day => Duration::days(1),
+            Self::Week => Duration::weeks(1),
+        })
+    }
+
+    /// The start of the first candle, every later candle starts a multiple of the
+    /// interval after it.
+    pub fn origin(self) -> DateTime<Utc> {
+        match self {
+            // the epoch was a Thursday
+            Self::Week => Utc.timestamp_opt(4 * 86400, 0).unwrap(),
+            _ => Utc.timestamp_opt(0, 0).unwrap(),
+        }
+    }
+
+    /// The start of the candle containing `time`, or `None` for ticks.
+    pub fn align(self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let interval = self.to_duration()?.num_milliseconds();
+        let (time, origin) = (time.timestamp_millis(), self.origin().timestamp_millis());
+        let offset = (time - origin).rem_euclid(interval);
+        Utc.timestamp_millis_opt(time - offset).single()
+    }
+
+    /// Whether candles of `coarser` can be built from whole candles of this resolution.
+    pub fn divides(self, coarser: Self) -> bool {
+        match (self.to_duration(), coarser.to_duration()) {
+            (Some(fine), Some(coarse)) => {
+                let (fine, coarse) = (fine.num_milliseconds(), coarse.num_milliseconds());
+                let origins = coarser.origin() - self.origin();
+                coarse % fine == 0 && origins.num_milliseconds() % fine == 0
+            }
+            _ => false,
+        }
+    }
+
+    /// The coarsest resolution Gemini serves candles of which divides this one, i.e.
+    /// this one itself if it is served.
+    pub fn served_source(self) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .rev()
+            .copied()
+            .find(|source| TimeRange::try_from(*source).is_ok() && source.divides(self))
+    }
+
+    /// The candle feed of this resolution, if Gemini has one.
+    pub fn candle_subscription(self) -> Option<SubscriptionType> {
+        Some(match self {
+            Self::Minute => SubscriptionType::Candles1m,
+            Self::Minute5 => SubscriptionType::Candles5m,
+            Self::Minute15 => SubscriptionType::Candles15m,
+            Self::Minute30 => SubscriptionType::Candles30m,
+            Self::Hour => SubscriptionType::Candles1h,
+            Self::Hour6 => SubscriptionType::Candles6h,
+            Self::Day => SubscriptionType::Candles1d,
+            _ => return None,
+        })
+    }
+}
+
+impl From<TimeRange> for Resolution {
+    fn from(range: TimeRange) -> Self {
+        match range {
+            TimeRange::Minute1 => Self::Minute,
+            TimeRange::Minute5 => Self::Minute5,
+            TimeRange::Minute15 => Self::Minute15,
+            TimeRange::Minute30 => Self::Minute30,
+            TimeRange::Hour1 => Self::Hour,
+            TimeRange::Hour6 => Self::Hour6,
+            TimeRange::Day1 => Self::Day,
+        }
+    }
+}
+
+impl From<CandleType> for Resolution {
+    fn from(candle_type: CandleType) -> Self {
+        TimeRange::from(candle_type).into()
+    }
+}
+
+impl TryFrom<Resolution> for TimeRange {
+    /// The resolution itself, which Gemini doesn't serve candles of.
+    type Error = Resolution;
+
+    fn try_from(resolution: Resolution) -> Result<Self, Self::Error> {
+        Ok(match resolution {
+            Resolution::Minute => Self::Minute1,
+            Resolution::Minute5 => Self::Minute5,
+            Resolution::Minute15 => Self::Minute15,
+            Resolution::Minute30 => Self::Minute30,
+            Resolution::Hour => Self::Hour1,
+            Resolution::Hour6 => Self::Hour6,
+            Resolution::Day => Self::Day1,
+            _ => return Err(resolution),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        for resolution in &Resolution::ALL {
+            match TimeRange::try_from(*resolution) {
+                Ok(range) => {
+                    assert_eq!(*resolution, Resolution::from(range));
+                    assert_eq!(resolution.to_duration(), Some(range.to_duration()));
+                    assert!(resolution.candle_subscription().is_some());
+                }
+                Err(_) => assert!(resolution.candle_subscription().is_none()),
+            }
+        }
+        assert_eq!(Resolution::Hour6, Resolution::from(CandleType::Hour6));
+    }
+
+    #[test]
+    fn test_align() {
+        // Wednesday 2021-06-02 13:47:12 UTC
+        let time = Utc.timestamp_opt(1622641632, 0).unwrap();
+        let align = |resolution: Resolution| resolution.align(time).unwrap().to_rfc3339();
+
+        assert_eq!("2021-06-02T13:45:00+00:00", align(Resolution::Minute15));
+        assert_eq!("2021-06-02T12:00:00+00:00", align(Resolution::Hour4));
+        assert_eq!("2021-06-02T12:00:00+00:00", align(Resolution::Hour6));
+        assert_eq!("2021-06-02T00:00:00+00:00", align(Resolution::Day));
+        assert_eq!("2021-05-31T00:00:00+00:00", align(Resolution::Week));
+        assert_eq!(None, Resolution::Tick.align(time));
+    }
+
+    #[test]
+    fn test_sources() {
+        assert!(Resolution::Minute5.divides(Resolution::Hour4));
+        assert!(Resolution::Minute.divides(Resolution::Minute));
+        assert!(!Resolution::Hour6.divides(Resolution::Hour4));
+        assert!(Resolution::Day.divides(Resolution::Week));
+        assert!(!Resolution::Tick.divides(Resolution::Minute));
+
+        assert_eq!(Some(Resolution::Hour), Resolution::Hour4.served_source());
+        assert_eq!(Some(Resolution::Day), Resolution::Week.served_source());
+        assert_eq!(Some(Resolution::Hour), Resolution::Hour.served_source());
+        assert_eq!(None, Resolution::Second.served_source());
+    }
+}
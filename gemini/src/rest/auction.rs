@@ -1,66 +1,86 @@
-use crate::{rest::HttpRequest, symbol::Symbol};
-use chrono::{
-    serde::{ts_milliseconds, ts_milliseconds_option},
-    DateTime, Utc,
+use crate::{
+    chrono::{ts_milliseconds, ts_milliseconds_option},
+    rest::HttpRequest,
+    symbol::Symbol,
 };
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Auction {
     // If the auction is not currently open, show the time at which the next auction opens.
     // Not present if the auction has already opened.
-    #[serde(with = "ts_milliseconds_option")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "ts_milliseconds_option"
+    )]
     pub closed_until_ms: Option<DateTime<Utc>>,
 
     // After an auction opens, the unique event ID for last specific auction event.
     // Changes when an auction event occurs: the auction opens, an indicative price is published, and the auction itself runs.
     // Not present before the auction opens.
-    pub last_auction_edi: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_auction_eid: Option<u64>,
 
     // If available, show the auction price from the last successful auction for this trading pair.
     // Not present after current auction begins publishing indicative prices.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub last_auction_price: Option<Decimal>,
 
     // If available, show the auction quantity from the last successful auction for this trading pair.
     // Not present after current auction begins publishing indicative prices.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub last_auction_quantity: Option<Decimal>,
 
     // If available, show the highest bid price from the continuous trading order book at the time of the last successful auction for this trading pair.
     // Not present after current auction begins publishing indicative prices.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub last_highest_bid_price: Option<Decimal>,
 
     // If available, show the lowest ask price from the continuous trading order book at the time of the last successful auction for this trading pair.
     // Not present after current auction begins publishing indicative prices.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub last_lowest_ask_price: Option<Decimal>,
 
     // If available, show the collar price at the time of the last successful auction for this trading pair.
     // Not present after current auction begins publishing indicative prices.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub last_collar_price: Option<Decimal>,
 
     // The most recently published indicative price for the auction.
     // Not present before the current auction begins publishing indicatives.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub most_recent_indicative_price: Option<Decimal>,
 
     // The most recently published indicative quantity for the auction.
     // Not present before the current auction begins publishing indicatives.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub most_recent_indicative_quantity: Option<Decimal>,
 
     // The most recent highest bid at the time of the indicative price for the auction.
     // Not present before the current auction begins publishing indicatives.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub most_recent_highest_bid_price: Option<Decimal>,
 
     // The most recent lowest ask at the time of the indicative price for the auction.
     // Not present before the current auction begins publishing indicatives.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub most_recent_lowest_ask_price: Option<Decimal>,
 
     // The most recent collar price at the time of the indicative price for the auction.
     // Not present before the current auction begins publishing indicatives.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub most_recent_collar_price: Option<Decimal>,
 
     // Timestamp of the next event in this auction, either the publication of an indicative price/quantity or the auction itself.
-    #[serde(with = "ts_milliseconds_option")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "ts_milliseconds_option"
+    )]
     pub next_update_ms: Option<DateTime<Utc>>,
 
     // Timestamp of when the next auction will run.
@@ -81,8 +101,49 @@ impl HttpRequest for AuctionHttpRequest {
     fn url(self, mut url: reqwest::Url) -> reqwest::Url {
         url.path_segments_mut()
             .unwrap()
-            .extend(["v1", self.symbol.as_ref()]);
+            .extend(["v1", "auction", self.symbol.as_ref()]);
 
         url
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_auction_request_url() {
+        let req = AuctionHttpRequest::builder().symbol(Symbol::BTCUSD).build();
+        let url = req.url(reqwest::Url::parse("https://domain.com").unwrap());
+        assert_eq!("https://domain.com/v1/auction/BTCUSD", url.as_str());
+    }
+
+    #[test]
+    fn test_auction_deserialize() {
+        let json = r#"{
+            "last_auction_eid": 109929,
+            "last_auction_price": "629.92",
+            "last_auction_quantity": "430.12917506",
+            "last_highest_bid_price": "630.10",
+            "last_lowest_ask_price": "632.44",
+            "last_collar_price": "631.27",
+            "most_recent_indicative_price": "632.33",
+            "most_recent_indicative_quantity": "151.9",
+            "most_recent_highest_bid_price": "633.26",
+            "most_recent_lowest_ask_price": "633.83",
+            "most_recent_collar_price": "633.545",
+            "next_update_ms": 1474567782895,
+            "next_auction_ms": 1474567782895
+        }"#;
+
+        let auction = serde_json::from_str::<Auction>(json).unwrap();
+        assert_eq!(None, auction.closed_until_ms);
+        assert_eq!(Some(109929), auction.last_auction_eid);
+        assert_eq!(Some(dec!(632.33)), auction.most_recent_indicative_price);
+        assert_eq!(Some(dec!(633.545)), auction.most_recent_collar_price);
+        assert_eq!(1474567782895, auction.next_auction_ms.timestamp_millis());
+
+        crate::util::assert_roundtrip::<Auction>(json);
+    }
+}
@@ -1,18 +1,22 @@
-use crate::{rest::HttpRequest, symbol::Symbol};
-use chrono::{serde::ts_milliseconds, DateTime, Utc};
+use crate::{chrono::ts_milliseconds, rest::HttpRequest, symbol::Symbol};
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
 
-#[derive(Deserialize, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Hash, Debug)]
 #[serde(rename_all = "lowercase")]
 pub enum EventType {
     Indicative,
     Auction,
 }
 
-#[derive(Deserialize, Copy, Clone, PartialEq, Eq, Hash, Debug)]
-#[serde(rename_all = "lowercase", tag = "auction_result")]
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[serde(
+    rename_all = "lowercase",
+    tag = "auction_result",
+    into = "crate::common::AuctionResultRepr"
+)]
 pub enum AuctionResult {
     Success {
         auction_price: Decimal,
@@ -21,7 +25,7 @@ pub enum AuctionResult {
     Failure,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Auction {
     #[serde(rename = "timestampms", with = "ts_milliseconds")]
     pub timestamp: DateTime<Utc>,
@@ -34,19 +38,21 @@ pub struct Auction {
     pub auction_result: AuctionResult,
 
     // Highest bid price from the continuous trading order book at the time of the auction event, if available.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub highest_bid_price: Option<Decimal>,
 
     // Lowest ask price from the continuous trading order book at the time of the auction event, if available.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub lowest_ask_price: Option<Decimal>,
 
     // The auction_price must be within plus or minus five percent of the collar price for result to be success.
     pub collar_price: Decimal,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(transparent)]
 pub struct AuctionHistory {
-    auctions: Vec<Auction>,
+    pub auctions: Vec<Auction>,
 }
 
 #[derive(TypedBuilder)]
@@ -160,5 +166,7 @@ mod test {
         if let AuctionResult::Success { .. } = failure.auction_result {
             panic!("auction_result should be a failure");
         }
+
+        crate::util::assert_roundtrip::<AuctionHistory>(json);
     }
 }
@@ -0,0 +1,56 @@
+use crate::{currency::Currency, rest::private::PrivateHttpRequest};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Lists the account's balance in each currency.
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+pub struct BalancesRequest {}
+
+impl PrivateHttpRequest for BalancesRequest {
+    type Response = Vec<Balance>;
+
+    const PATH: &'static str = "/v1/balances";
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Balance {
+    pub currency: Currency,
+    pub amount: Decimal,
+    /// The amount not held by open orders.
+    pub available: Decimal,
+    pub available_for_withdrawal: Decimal,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rest::private::Credentials;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_balances_payload() {
+        let signed = Credentials::new("key", "secret").sign(&BalancesRequest::default(), 7);
+        assert_eq!(
+            r#"{"request":"/v1/balances","nonce":7}"#,
+            String::from_utf8(base64::decode(signed.payload).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_balances_deserialize() {
+        let json = r#"[{
+            "type": "exchange",
+            "currency": "BTC",
+            "amount": "1154.62034001",
+            "available": "1129.10517279",
+            "availableForWithdrawal": "1129.10517279"
+        }]"#;
+
+        let balances = serde_json::from_str::<Vec<Balance>>(json).unwrap();
+        assert_eq!(1, balances.len());
+        assert_eq!(Currency::BTC, balances[0].currency);
+        assert_eq!(dec!(1154.62034001), balances[0].amount);
+        assert_eq!(dec!(1129.10517279), balances[0].available);
+    }
+}
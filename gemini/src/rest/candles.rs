@@ -1,12 +1,13 @@
-use crate::{rest::HttpRequest, symbol::Symbol};
+use crate::{rest::HttpRequest, symbol::Symbol, ws::marketv2::candle::CandleType};
+use chrono::Duration;
 use reqwest::{Method, Url};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use strum_macros::{AsRefStr, IntoStaticStr};
 use typed_builder::TypedBuilder;
 
 pub use crate::common::Candle;
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(transparent)]
 pub struct Candles {
     pub candles: Vec<Candle>,
@@ -30,6 +31,35 @@ pub enum TimeRange {
     Day1,
 }
 
+impl TimeRange {
+    /// The interval of a candle of this range.
+    pub fn to_duration(self) -> Duration {
+        match self {
+            Self::Minute1 => Duration::minutes(1),
+            Self::Minute5 => Duration::minutes(5),
+            Self::Minute15 => Duration::minutes(15),
+            Self::Minute30 => Duration::minutes(30),
+            Self::Hour1 => Duration::hours(1),
+            Self::Hour6 => Duration::hours(6),
+            Self::Day1 => Duration::days(1),
+        }
+    }
+}
+
+impl From<CandleType> for TimeRange {
+    fn from(candle_type: CandleType) -> Self {
+        match candle_type {
+            CandleType::Minute1 => Self::Minute1,
+            CandleType::Minute5 => Self::Minute5,
+            CandleType::Minute15 => Self::Minute15,
+            CandleType::Minute30 => Self::Minute30,
+            CandleType::Hour1 => Self::Hour1,
+            CandleType::Hour6 => Self::Hour6,
+            CandleType::Day1 => Self::Day1,
+        }
+    }
+}
+
 #[derive(TypedBuilder)]
 pub struct CandleHttpRequest {
     symbol: Symbol,
@@ -87,5 +117,7 @@ mod test {
         assert_eq!(candle.low, dec!(7776.56));
         assert_eq!(candle.close, dec!(7819.39));
         assert_eq!(candle.volume, dec!(34.7624802159));
+
+        crate::util::assert_roundtrip::<Candles>(json);
     }
 }
@@ -1,16 +1,25 @@
-use reqwest::{StatusCode, Url};
+use reqwest::{Method, StatusCode, Url};
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use super::HttpRequest;
+use super::{
+    private::{Credentials, PrivateHttpRequest},
+    HttpRequest,
+};
 
 pub struct Client {
     inner: reqwest::Client,
     url: Url,
+    credentials: Option<Credentials>,
+    // The most recently used nonce of a private request.
+    nonce: AtomicU64,
 }
 
 #[derive(Debug)]
 pub enum Error {
     Gemini(crate::error::Error),
     Reqwest(reqwest::Error),
+    // A private request was made by a client without credentials.
+    MissingCredentials,
 }
 
 impl From<reqwest::Error> for Error {
@@ -19,6 +28,18 @@ impl From<reqwest::Error> for Error {
     }
 }
 
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Gemini(e) => write!(f, "gemini: {}", e),
+            Self::Reqwest(e) => write!(f, "request: {}", e),
+            Self::MissingCredentials => write!(f, "private request without credentials"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 impl From<crate::error::Error> for Error {
     fn from(e: crate::error::Error) -> Self {
         Self::Gemini(e)
@@ -30,9 +51,17 @@ impl Client {
         Self {
             inner: reqwest::Client::default(),
             url,
+            credentials: None,
+            nonce: AtomicU64::new(0),
         }
     }
 
+    /// Authenticates private requests with `credentials`.
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
     pub async fn request<R: HttpRequest>(&self, req: R) -> Result<R::Response, Error> {
         let resp = self
             .inner
@@ -45,4 +74,47 @@ impl Client {
             _ => Err(resp.json::<crate::error::Error>().await?.into()),
         }
     }
+
+    pub async fn private_request<R: PrivateHttpRequest>(
+        &self,
+        req: R,
+    ) -> Result<R::Response, Error> {
+        let credentials = self.credentials.as_ref().ok_or(Error::MissingCredentials)?;
+        let signed = credentials.sign(&req, self.next_nonce());
+
+        let mut url = self.url.clone();
+        url.path_segments_mut()
+            .unwrap()
+            .extend(R::PATH.trim_start_matches('/').split('/'));
+
+        let resp = self
+            .inner
+            .request(Method::POST, url)
+            .header("Content-Type", "text/plain")
+            .header("Content-Length", "0")
+            .header("Cache-Control", "no-cache")
+            .header("X-GEMINI-APIKEY", &credentials.api_key)
+            .header("X-GEMINI-PAYLOAD", signed.payload)
+            .header("X-GEMINI-SIGNATURE", signed.signature)
+            .send()
+            .await?;
+
+        match resp.status() {
+            StatusCode::OK => Ok(resp.json::<R::Response>().await?),
+            _ => Err(resp.json::<crate::error::Error>().await?.into()),
+        }
+    }
+
+    // Nonces must increase with every request, so the current time in milliseconds
+    // is only used while it is larger than the previous nonce.
+    fn next_nonce(&self) -> u64 {
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let prev = self
+            .nonce
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |prev| {
+                Some(now.max(prev + 1))
+            })
+            .unwrap();
+        now.max(prev + 1)
+    }
 }
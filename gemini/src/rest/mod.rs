@@ -1,9 +1,13 @@
 pub mod auction;
 pub mod auction_history;
+pub mod balances;
 pub mod candles;
 pub mod client;
+pub mod my_trades;
+pub mod order;
 pub mod order_book;
 pub mod price_feed;
+pub mod private;
 pub mod ticker;
 pub mod trade_history;
 
@@ -0,0 +1,114 @@
+use crate::{
+    chrono::ts_milliseconds, currency::Currency, rest::private::PrivateHttpRequest, symbol::Symbol,
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+/// Lists the account's past trades of a symbol, most recent first.
+#[derive(Serialize, TypedBuilder, Debug, Clone, Copy)]
+pub struct MyTradesRequest {
+    symbol: Symbol,
+    #[builder(default, setter(strip_option))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit_trades: Option<u32>,
+    /// Only trades at or after this time are returned.
+    #[builder(default, setter(strip_option))]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "crate::chrono::ts_milliseconds_option"
+    )]
+    timestamp: Option<DateTime<Utc>>,
+}
+
+impl PrivateHttpRequest for MyTradesRequest {
+    type Response = Vec<MyTrade>;
+
+    const PATH: &'static str = "/v1/mytrades";
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct MyTrade {
+    pub symbol: Symbol,
+    pub price: Decimal,
+    pub amount: Decimal,
+    #[serde(rename = "timestampms", with = "ts_milliseconds")]
+    pub timestamp: DateTime<Utc>,
+    #[serde(rename = "type")]
+    pub side: Side,
+    /// Whether the order took liquidity.
+    pub aggressor: bool,
+    pub fee_currency: Currency,
+    pub fee_amount: Decimal,
+    pub tid: u64,
+    pub order_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_order_id: Option<String>,
+    #[serde(default)]
+    pub is_auction_fill: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rest::private::Credentials;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_my_trades_payload() {
+        let req = MyTradesRequest::builder()
+            .symbol(Symbol::BTCUSD)
+            .timestamp(Utc.timestamp_millis_opt(1547232911000).unwrap())
+            .build();
+
+        let signed = Credentials::new("key", "secret").sign(&req, 7);
+        assert_eq!(
+            r#"{"request":"/v1/mytrades","nonce":7,"symbol":"BTCUSD","timestamp":1547232911000}"#,
+            String::from_utf8(base64::decode(signed.payload).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_my_trades_deserialize() {
+        let json = r#"[{
+            "price": "3648.09",
+            "amount": "0.0027343246",
+            "timestamp": 1547232911,
+            "timestampms": 1547232911273,
+            "type": "Buy",
+            "aggressor": true,
+            "fee_currency": "USD",
+            "fee_amount": "0.024937655575035",
+            "tid": 107317526,
+            "order_id": "107317524",
+            "exchange": "gemini",
+            "is_auction_fill": false,
+            "is_clearing_fill": false,
+            "symbol": "BTCUSD"
+        }]"#;
+
+        let trades = serde_json::from_str::<Vec<MyTrade>>(json).unwrap();
+        assert_eq!(1, trades.len());
+
+        let trade = &trades[0];
+        assert_eq!(Symbol::BTCUSD, trade.symbol);
+        assert_eq!(dec!(3648.09), trade.price);
+        assert_eq!(dec!(0.0027343246), trade.amount);
+        assert_eq!(1547232911273, trade.timestamp.timestamp_millis());
+        assert_eq!(Side::Buy, trade.side);
+        assert!(trade.aggressor);
+        assert_eq!(Currency::USD, trade.fee_currency);
+        assert_eq!(dec!(0.024937655575035), trade.fee_amount);
+        assert_eq!(None, trade.client_order_id);
+
+        crate::util::assert_roundtrip::<Vec<MyTrade>>(json);
+    }
+}
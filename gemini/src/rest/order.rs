@@ -0,0 +1,216 @@
+use crate::{chrono::ts_milliseconds, rest::private::PrivateHttpRequest, symbol::Symbol};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use typed_builder::TypedBuilder;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum OrderType {
+    #[serde(rename = "exchange limit")]
+    ExchangeLimit,
+    #[serde(rename = "exchange stop limit")]
+    ExchangeStopLimit,
+    #[serde(other)]
+    Other,
+}
+
+/// Execution options of a limit order. At most one option may be given.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OrderOption {
+    MakerOrCancel,
+    ImmediateOrCancel,
+    FillOrKill,
+    AuctionOnly,
+    IndicationOfInterest,
+}
+
+/// Places a new order. Gemini only supports limit orders, market orders are
+/// emulated with aggressively priced immediate-or-cancel orders.
+#[derive(Serialize, TypedBuilder, Debug, Clone)]
+pub struct NewOrderRequest {
+    #[builder(default, setter(strip_option, into))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_order_id: Option<String>,
+    symbol: Symbol,
+    amount: Decimal,
+    price: Decimal,
+    side: Side,
+    #[builder(default = OrderType::ExchangeLimit, setter(skip))]
+    #[serde(rename = "type")]
+    ty: OrderType,
+    #[builder(default, setter(into))]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    options: Vec<OrderOption>,
+}
+
+impl PrivateHttpRequest for NewOrderRequest {
+    type Response = OrderStatus;
+
+    const PATH: &'static str = "/v1/order/new";
+}
+
+/// Cancels an order by the id assigned by the exchange.
+#[derive(Serialize, TypedBuilder, Debug, Clone, Copy)]
+pub struct CancelOrderRequest {
+    order_id: u64,
+}
+
+impl PrivateHttpRequest for CancelOrderRequest {
+    type Response = OrderStatus;
+
+    const PATH: &'static str = "/v1/order/cancel";
+}
+
+#[derive(Serialize, TypedBuilder, Debug, Clone, Copy)]
+pub struct OrderStatusRequest {
+    order_id: u64,
+}
+
+impl PrivateHttpRequest for OrderStatusRequest {
+    type Response = OrderStatus;
+
+    const PATH: &'static str = "/v1/order/status";
+}
+
+/// Looks up orders by the client order id they were placed with, e.g. when the
+/// response to placing one was lost. Client order ids needn't be unique.
+#[derive(Serialize, TypedBuilder, Debug, Clone)]
+pub struct ClientOrderStatusRequest {
+    #[builder(setter(into))]
+    client_order_id: String,
+}
+
+impl PrivateHttpRequest for ClientOrderStatusRequest {
+    type Response = Vec<OrderStatus>;
+
+    const PATH: &'static str = "/v1/order/status";
+}
+
+fn from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let s = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+    s.parse().map_err(serde::de::Error::custom)
+}
+
+fn to_str<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: std::fmt::Display,
+{
+    serializer.collect_str(value)
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct OrderStatus {
+    #[serde(deserialize_with = "from_str", serialize_with = "to_str")]
+    pub order_id: u64,
+    pub client_order_id: Option<String>,
+    #[serde(with = "crate::symbol::lowercase")]
+    pub symbol: Symbol,
+    pub side: Side,
+    #[serde(rename = "type")]
+    pub ty: OrderType,
+    #[serde(rename = "timestampms", with = "ts_milliseconds")]
+    pub timestamp: DateTime<Utc>,
+    pub is_live: bool,
+    pub is_cancelled: bool,
+    pub price: Option<Decimal>,
+    pub avg_execution_price: Decimal,
+    pub original_amount: Decimal,
+    pub executed_amount: Decimal,
+    pub remaining_amount: Decimal,
+    #[serde(default)]
+    pub options: Vec<OrderOption>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rest::private::Credentials;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_new_order_payload() {
+        let req = NewOrderRequest::builder()
+            .client_order_id("42")
+            .symbol(Symbol::BTCUSD)
+            .amount(dec!(0.5))
+            .price(dec!(3633.00))
+            .side(Side::Buy)
+            .options(vec![OrderOption::MakerOrCancel])
+            .build();
+
+        let signed = Credentials::new("key", "secret").sign(&req, 7);
+        let payload = base64::decode(signed.payload).unwrap();
+        assert_eq!(
+            r#"{"request":"/v1/order/new","nonce":7,"client_order_id":"42","symbol":"BTCUSD","amount":"0.5","price":"3633.00","side":"buy","type":"exchange limit","options":["maker-or-cancel"]}"#,
+            String::from_utf8(payload).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_client_order_status_payload() {
+        let req = ClientOrderStatusRequest::builder()
+            .client_order_id("42")
+            .build();
+
+        let signed = Credentials::new("key", "secret").sign(&req, 7);
+        let payload = base64::decode(signed.payload).unwrap();
+        assert_eq!(
+            r#"{"request":"/v1/order/status","nonce":7,"client_order_id":"42"}"#,
+            String::from_utf8(payload).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_order_status_deserialize() {
+        let json = r#"{
+            "order_id": "106817811",
+            "id": "106817811",
+            "symbol": "btcusd",
+            "exchange": "gemini",
+            "avg_execution_price": "3632.8508430064554",
+            "side": "buy",
+            "type": "exchange limit",
+            "timestamp": "1547220404",
+            "timestampms": 1547220404836,
+            "is_live": true,
+            "is_cancelled": false,
+            "is_hidden": false,
+            "was_forced": false,
+            "executed_amount": "3.7567928949",
+            "remaining_amount": "1.2432071051",
+            "client_order_id": "20190110-4738721",
+            "options": [],
+            "price": "3633.00",
+            "original_amount": "5"
+        }"#;
+
+        let status = serde_json::from_str::<OrderStatus>(json).unwrap();
+        assert_eq!(106817811, status.order_id);
+        assert_eq!(Some("20190110-4738721"), status.client_order_id.as_deref());
+        assert!(matches!(status.symbol, Symbol::BTCUSD));
+        assert_eq!(Side::Buy, status.side);
+        assert_eq!(OrderType::ExchangeLimit, status.ty);
+        assert_eq!(1547220404836, status.timestamp.timestamp_millis());
+        assert!(status.is_live);
+        assert_eq!(Some(dec!(3633.00)), status.price);
+        assert_eq!(dec!(3.7567928949), status.executed_amount);
+        assert_eq!(dec!(1.2432071051), status.remaining_amount);
+        assert_eq!(dec!(5), status.original_amount);
+
+        crate::util::assert_roundtrip::<OrderStatus>(json);
+    }
+}
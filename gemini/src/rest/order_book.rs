@@ -5,17 +5,17 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Order {
-    price: Decimal,
-    amount: Decimal,
+    pub price: Decimal,
+    pub amount: Decimal,
     // timestamp -> Unused
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct OrderBook {
-    bids: Vec<Order>,
-    asks: Vec<Order>,
+    pub bids: Vec<Order>,
+    pub asks: Vec<Order>,
 }
 
 #[derive(Serialize, Debug, Clone)]
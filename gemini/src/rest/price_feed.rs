@@ -1,20 +1,33 @@
 use rust_decimal::Decimal;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::symbol::Symbol;
+use crate::{currency, symbol::Symbol};
 
 use super::HttpRequest;
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct PriceFeed {
-    #[serde(rename = "pair")]
-    pub symbol: Symbol,
+    /// The pair as Gemini names it, e.g. `BTCUSD`, including pairs without a `Symbol`.
+    pub pair: String,
     pub price: Decimal,
     #[serde(rename = "percentChange24h")]
     pub percent_change_24h: Decimal,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+impl PriceFeed {
+    /// The symbol of the pair, or `Symbol::Unknown` if it isn't one this crate knows.
+    pub fn symbol(&self) -> Symbol {
+        self.pair.parse().unwrap_or(Symbol::Unknown)
+    }
+
+    /// The base and quote currency of the pair, or `None` if it isn't quoted in a known
+    /// quote currency.
+    pub fn currencies(&self) -> Option<(&str, &str)> {
+        currency::split_pair(&self.pair)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(transparent)]
 pub struct PriceFeeds {
     pub feeds: Vec<PriceFeed>,
@@ -32,3 +45,28 @@ impl HttpRequest for PriceFeedsHttpRequest {
         url
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_price_feed() {
+        let feeds: PriceFeeds = serde_json::from_str(
+            r#"[
+                {"pair":"BTCUSD","price":"40000","percentChange24h":"0.0100"},
+                {"pair":"ETHGUSD","price":"2000","percentChange24h":"-0.0200"},
+                {"pair":"ZECLTC","price":"1","percentChange24h":"0.0000"},
+                {"pair":"XYZ","price":"1","percentChange24h":"0.0000"}
+            ]"#,
+        )
+        .unwrap();
+        let feeds = feeds.feeds;
+        assert_eq!(Symbol::BTCUSD, feeds[0].symbol());
+        assert_eq!(Symbol::Unknown, feeds[2].symbol());
+        assert_eq!(Some(("BTC", "USD")), feeds[0].currencies());
+        assert_eq!(Some(("ETH", "GUSD")), feeds[1].currencies());
+        assert_eq!(Some(("ZEC", "LTC")), feeds[2].currencies());
+        assert_eq!(None, feeds[3].currencies());
+    }
+}
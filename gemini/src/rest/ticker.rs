@@ -1,5 +1,5 @@
-use crate::{rest::HttpRequest, symbol::Symbol};
-use chrono::{serde::ts_milliseconds, DateTime, Utc};
+use crate::{chrono::ts_milliseconds, rest::HttpRequest, symbol::Symbol};
+use chrono::{DateTime, Utc};
 use reqwest::Method;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -7,78 +7,129 @@ use typed_builder::TypedBuilder;
 
 pub mod v1 {
     use super::*;
-    use std::collections::HashMap;
+    use crate::currency;
+    use serde::de::{self, Error as _};
 
-    #[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
-    pub enum PriceSymbol {
-        USD,
+    /// The volume traded over the last 24 hours, in both currencies of a pair.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Volume {
+        pub timestamp: DateTime<Utc>,
+        /// The base currency as Gemini names it, e.g. `LTC`.
+        pub base_currency: String,
+        /// The quote currency as Gemini names it, e.g. `USD`.
+        pub quote_currency: String,
+        /// In the base currency.
+        pub base: Decimal,
+        /// In the quote currency.
+        pub quote: Decimal,
     }
 
-    #[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
-    pub enum QuantitySymbol {
-        BTC,
-    }
+    impl Volume {
+        /// The pair as Gemini names it, e.g. `LTCUSD`.
+        pub fn pair(&self) -> String {
+            format!("{}{}", self.base_currency, self.quote_currency)
+        }
 
-    #[derive(Debug, Clone)]
-    pub struct Pair<A, B> {
-        a: A,
-        b: B,
-    }
+        /// The symbol of the pair, `Unknown` for the pairs without one.
+        pub fn symbol(&self) -> Symbol {
+            self.pair().parse().unwrap_or(Symbol::Unknown)
+        }
 
-    #[derive(Default)]
-    struct PairVisitor<A, B> {
-        pub(crate) _phantom: std::marker::PhantomData<(A, B)>,
+        /// Assigns the volumes to the base and quote currency of `pair`, e.g. the pair
+        /// the ticker was requested for. Returns `false` if the volumes aren't of it.
+        pub fn orient(&mut self, pair: &str) -> bool {
+            if self.pair().eq_ignore_ascii_case(pair) {
+                return true;
+            }
+            let reversed = format!("{}{}", self.quote_currency, self.base_currency);
+            if !reversed.eq_ignore_ascii_case(pair) {
+                return false;
+            }
+            std::mem::swap(&mut self.base_currency, &mut self.quote_currency);
+            std::mem::swap(&mut self.base, &mut self.quote);
+            true
+        }
     }
 
-    impl<'de, A, B> serde::de::Visitor<'de> for PairVisitor<A, B>
-    where
-        A: Deserialize<'de>,
-        B: Deserialize<'de>,
-    {
-        type Value = Pair<A, B>;
+    #[derive(Deserialize)]
+    struct Timestamp(#[serde(with = "ts_milliseconds")] DateTime<Utc>);
+
+    struct VolumeVisitor;
+
+    impl<'de> de::Visitor<'de> for VolumeVisitor {
+        type Value = Volume;
 
         fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-            write!(formatter, "A single key-value pair")
+            write!(
+                formatter,
+                "a timestamp and the volumes of a pair's currencies"
+            )
         }
 
         fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
         where
-            M: serde::de::MapAccess<'de>,
+            M: de::MapAccess<'de>,
         {
-            map.next_entry::<A, B>()?
-                .map(|(a, b)| Pair { a, b })
-                .ok_or_else(|| {
-                    serde::de::Error::custom("`Pair` must contain a single key-value pair")
-                })
+            let mut timestamp = None;
+            let mut volumes = Vec::with_capacity(2);
+            while let Some(key) = map.next_key::<String>()? {
+                if key == "timestamp" {
+                    timestamp = Some(map.next_value::<Timestamp>()?.0);
+                } else {
+                    volumes.push((key, map.next_value::<Decimal>()?));
+                }
+            }
+
+            let timestamp = timestamp.ok_or_else(|| M::Error::missing_field("timestamp"))?;
+            let mut volumes = volumes.into_iter();
+            let (mut base, mut quote) = match (volumes.next(), volumes.next(), volumes.next()) {
+                (Some(first), Some(second), None) => (first, second),
+                _ => {
+                    return Err(M::Error::custom(
+                        "expected the volumes of a pair's two currencies",
+                    ))
+                }
+            };
+            // Gemini names the base currency first, but that isn't guaranteed
+            if currency::quoted_first(&base.0, &quote.0) {
+                std::mem::swap(&mut base, &mut quote);
+            }
+
+            Ok(Volume {
+                timestamp,
+                base_currency: base.0,
+                quote_currency: quote.0,
+                base: base.1,
+                quote: quote.1,
+            })
         }
     }
 
-    impl<'de, A, B> serde::Deserialize<'de> for Pair<A, B>
-    where
-        A: Deserialize<'de>,
-        B: Deserialize<'de>,
-    {
+    impl<'de> Deserialize<'de> for Volume {
         fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where
             D: serde::Deserializer<'de>,
         {
-            deserializer.deserialize_map(PairVisitor::<A, B> {
-                _phantom: std::marker::PhantomData,
-            })
+            deserializer.deserialize_map(VolumeVisitor)
         }
     }
 
-    #[derive(Deserialize, Clone, Debug)]
-    pub struct Volume {
-        #[serde(with = "ts_milliseconds")]
-        pub timestamp: DateTime<Utc>,
+    impl Serialize for Volume {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            use serde::ser::SerializeMap;
 
-        // TODO: OPTIMIZE!!!
-        #[serde(flatten)]
-        pub other: HashMap<String, Decimal>,
+            let mut map = serializer.serialize_map(Some(3))?;
+            map.serialize_entry(&self.base_currency, &self.base)?;
+            map.serialize_entry(&self.quote_currency, &self.quote)?;
+            map.serialize_entry("timestamp", &self.timestamp.timestamp_millis())?;
+            map.end()
+        }
     }
 
-    #[derive(Deserialize, Clone, Debug)]
+    #[derive(Deserialize, Serialize, Clone, Debug)]
     pub struct Ticker {
         pub bid: Decimal,
         pub ask: Decimal,
@@ -86,9 +137,11 @@ pub mod v1 {
         pub volume: Volume,
     }
 
+    /// The ticker of any pair, e.g. `Symbol::BTCUSD.as_ref()` or `"LTCUSD"`.
     #[derive(TypedBuilder)]
     pub struct TickerHttpRequest {
-        symbol: Symbol,
+        #[builder(setter(into))]
+        symbol: String,
     }
 
     impl HttpRequest for TickerHttpRequest {
@@ -99,7 +152,7 @@ pub mod v1 {
         fn url(self, mut url: reqwest::Url) -> reqwest::Url {
             url.path_segments_mut()
                 .unwrap()
-                .extend(["v1", "pubticker", self.symbol.as_ref()]);
+                .extend(["v1", "pubticker", &self.symbol]);
             url
         }
     }
@@ -108,9 +161,11 @@ pub mod v1 {
 pub mod v2 {
     use super::*;
 
-    #[derive(Deserialize, Clone, Debug)]
+    #[derive(Deserialize, Serialize, Clone, Debug)]
     pub struct Ticker {
-        pub symbol: Symbol,
+        /// The pair as Gemini names it, in lower case, e.g. `ltcusd`.
+        #[serde(rename = "symbol")]
+        pub pair: String,
         // Open price from 24 hours ago
         pub open: Decimal,
         // High price from 24 hours ago
@@ -127,9 +182,18 @@ pub mod v2 {
         pub ask: Decimal,
     }
 
+    impl Ticker {
+        /// The symbol of the pair, `Unknown` for the pairs without one.
+        pub fn symbol(&self) -> Symbol {
+            self.pair.parse().unwrap_or(Symbol::Unknown)
+        }
+    }
+
+    /// The ticker of any pair, e.g. `Symbol::BTCUSD.as_ref()` or `"LTCUSD"`.
     #[derive(TypedBuilder)]
     pub struct TickerHttpRequest {
-        symbol: Symbol,
+        #[builder(setter(into))]
+        symbol: String,
     }
 
     impl HttpRequest for TickerHttpRequest {
@@ -140,19 +204,132 @@ pub mod v2 {
         fn url(self, mut url: reqwest::Url) -> reqwest::Url {
             url.path_segments_mut()
                 .unwrap()
-                .extend(["v2", "ticker", self.symbol.as_ref()]);
+                .extend(["v2", "ticker", &self.symbol]);
             url
         }
     }
 }
 
+/// The ticker of a pair, merging the bid, ask, last price and volume of the v1 ticker
+/// with the prices of the v2 ticker over the last 24 hours.
+#[derive(Debug, Clone)]
+pub struct Ticker {
+    /// The pair as Gemini names it, in upper case, e.g. `LTCUSD`.
+    pub pair: String,
+    pub bid: Decimal,
+    pub ask: Decimal,
+    pub last: Decimal,
+    /// In the base and quote currency of `pair`.
+    pub volume: v1::Volume,
+    // Open price from 24 hours ago
+    pub open: Decimal,
+    // High price over the last 24 hours
+    pub high: Decimal,
+    // Low price over the last 24 hours
+    pub low: Decimal,
+    // Price of the most recent trade
+    pub close: Decimal,
+    // Hourly prices descending for past 24 hours
+    pub changes: [Decimal; 24],
+}
+
+impl Ticker {
+    /// Merges the tickers of a pair, or returns `None` if they are of different pairs.
+    /// The volumes are assigned to the currencies of the pair of `v2`.
+    pub fn merge(v1: v1::Ticker, v2: v2::Ticker) -> Option<Self> {
+        let v1::Ticker {
+            bid,
+            ask,
+            last,
+            mut volume,
+        } = v1;
+        volume.orient(&v2.pair).then(|| Self {
+            pair: v2.pair.to_ascii_uppercase(),
+            bid,
+            ask,
+            last,
+            volume,
+            open: v2.open,
+            high: v2.high,
+            low: v2.low,
+            close: v2.close,
+            changes: v2.changes,
+        })
+    }
+
+    /// The symbol of the pair, `Unknown` for the pairs without one.
+    pub fn symbol(&self) -> Symbol {
+        self.pair.parse().unwrap_or(Symbol::Unknown)
+    }
+
+    /// The change of the last price over the last 24 hours as a fraction of the open
+    /// price, or `None` if the open price is zero.
+    pub fn change_24h(&self) -> Option<Decimal> {
+        (!self.open.is_zero()).then(|| (self.last - self.open) / self.open)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::symbol::Symbol;
     use rust_decimal_macros::dec;
 
+    #[test]
+    fn test_ticker_merge() {
+        let v1 = serde_json::from_str::<super::v1::Ticker>(
+            r#"{"ask":"2.01","bid":"1.99","last":"2","volume":{"USD":"10","ETH":"5","timestamp":0}}"#,
+        )
+        .unwrap();
+        let changes = vec!["\"1.5\""; 24];
+        let v2 = serde_json::from_str::<super::v2::Ticker>(&format!(
+            r#"{{"symbol":"ETHUSD","open":"1.6","high":"2.1","low":"1.5","close":"2","changes":[{}],"bid":"1.99","ask":"2.01"}}"#,
+            changes.join(",")
+        ))
+        .unwrap();
+
+        let ticker = super::Ticker::merge(v1.clone(), v2.clone()).unwrap();
+        assert_eq!(Symbol::ETHUSD, ticker.symbol());
+        assert_eq!((dec!(2), dec!(1.6)), (ticker.last, ticker.open));
+        assert_eq!(
+            (dec!(5), dec!(10)),
+            (ticker.volume.base, ticker.volume.quote)
+        );
+        assert_eq!(dec!(1.5), ticker.changes[23]);
+        assert_eq!(Some(dec!(0.25)), ticker.change_24h());
+
+        let btc = super::v2::Ticker {
+            pair: "btcusd".to_owned(),
+            ..v2.clone()
+        };
+        assert!(super::Ticker::merge(v1, btc).is_none());
+
+        // pairs without a symbol, whichever currency comes first
+        let v1 = serde_json::from_str::<super::v1::Ticker>(
+            r#"{"ask":"81","bid":"80","last":"80","volume":{"USD":"800","LTC":"10","timestamp":0}}"#,
+        )
+        .unwrap();
+        let ltc = super::v2::Ticker {
+            pair: "ltcusd".to_owned(),
+            ..v2
+        };
+        let ticker = super::Ticker::merge(v1, ltc).unwrap();
+        assert_eq!(
+            ("LTCUSD", Symbol::Unknown),
+            (ticker.pair.as_str(), ticker.symbol())
+        );
+        assert_eq!(
+            ("LTC", dec!(10), "USD", dec!(800)),
+            (
+                ticker.volume.base_currency.as_str(),
+                ticker.volume.base,
+                ticker.volume.quote_currency.as_str(),
+                ticker.volume.quote
+            )
+        );
+    }
+
     mod v1 {
-        use super::super::v1::Ticker;
+        use super::super::v1::{Ticker, Volume};
         use super::*;
 
         #[test]
@@ -175,11 +352,57 @@ mod test {
             assert_eq!(ticker.last, dec!(977.65));
             assert_eq!(ticker.volume.timestamp.timestamp_millis(), 1483018200000);
 
-            assert_eq!(ticker.volume.other.get("BTC"), Some(&dec!(2210.505328803)));
+            assert_eq!(ticker.volume.symbol(), Symbol::BTCUSD);
+            assert_eq!(ticker.volume.base, dec!(2210.505328803));
+            assert_eq!(ticker.volume.quote, dec!(2135477.463379586263));
+
+            crate::util::assert_roundtrip::<Ticker>(json);
+        }
+
+        #[test]
+        fn test_volume_deserialize() {
+            // the quote currency comes first
+            let volume = serde_json::from_str::<Volume>(
+                r#"{"BTC": "1.5", "timestamp": 1483018200000, "ETH": "30"}"#,
+            )
+            .unwrap();
+            assert_eq!(
+                (Symbol::ETHBTC, dec!(30), dec!(1.5)),
+                (volume.symbol(), volume.base, volume.quote)
+            );
+
+            // any pair, named by its currencies
+            let json = r#"{"SOL": "20", "USD": "2000", "timestamp": 0}"#;
+            let volume = serde_json::from_str::<Volume>(json).unwrap();
             assert_eq!(
-                ticker.volume.other.get("USD"),
-                Some(&dec!(2135477.463379586263))
+                ("SOLUSD", Symbol::Unknown, dec!(20), dec!(2000)),
+                (
+                    volume.pair().as_str(),
+                    volume.symbol(),
+                    volume.base,
+                    volume.quote
+                )
             );
+            crate::util::assert_roundtrip::<Volume>(json);
+
+            // oriented by the requested pair where the currencies don't tell
+            let mut volume =
+                serde_json::from_str::<Volume>(r#"{"ABC": "1", "XYZ": "3", "timestamp": 0}"#)
+                    .unwrap();
+            assert_eq!("ABCXYZ", volume.pair());
+            assert!(volume.orient("xyzabc"));
+            assert_eq!(
+                ("XYZ", dec!(3)),
+                (volume.base_currency.as_str(), volume.base)
+            );
+            assert!(!volume.orient("XYZUSD"));
+
+            assert!(serde_json::from_str::<Volume>(r#"{"BTC": "1", "timestamp": 0}"#).is_err());
+            assert!(serde_json::from_str::<Volume>(
+                r#"{"BTC": "1", "USD": "1", "ETH": "1", "timestamp": 0}"#
+            )
+            .is_err());
+            assert!(serde_json::from_str::<Volume>(r#"{"BTC": "1", "USD": "1"}"#).is_err());
         }
     }
 
@@ -227,7 +450,7 @@ mod test {
 
             let ticker = serde_json::from_str::<Ticker>(json).unwrap();
 
-            assert_eq!(ticker.symbol, Symbol::BTCUSD);
+            assert_eq!(ticker.symbol(), Symbol::BTCUSD);
             assert_eq!(ticker.open, dec!(9121.76));
             assert_eq!(ticker.high, dec!(9440.66));
             assert_eq!(ticker.low, dec!(9106.51));
@@ -244,6 +467,8 @@ mod test {
                 .collect::<Vec<_>>();
 
             assert!(ticker.changes.iter().eq(changes.iter()));
+
+            crate::util::assert_roundtrip::<Ticker>(json);
         }
     }
 }
@@ -1,11 +1,12 @@
-use chrono::{serde::ts_milliseconds, DateTime, Utc};
+use crate::chrono::ts_milliseconds;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
 
 use crate::{rest::HttpRequest, symbol::Symbol};
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum TradeType {
     Buy,
@@ -14,8 +15,8 @@ pub enum TradeType {
     Block,
 }
 
-#[derive(Deserialize, Debug, Clone, Copy)]
-#[serde(from = "&str")]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(from = "&str", into = "&str")]
 pub struct Gemini;
 
 impl From<&str> for Gemini {
@@ -25,11 +26,17 @@ impl From<&str> for Gemini {
     }
 }
 
-#[derive(Deserialize, Debug, Clone)]
+impl From<Gemini> for &'static str {
+    fn from(_: Gemini) -> Self {
+        "gemini"
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct TradeHistory {
     #[serde(rename = "timestampms", with = "ts_milliseconds")]
     pub timestamp: DateTime<Utc>,
-    pub tid: u32,
+    pub tid: u64,
     pub price: Decimal,
     pub amount: Decimal,
     pub exchange: Gemini,
@@ -50,7 +57,7 @@ pub struct TradeHistoryHttpRequest {
 }
 
 impl HttpRequest for TradeHistoryHttpRequest {
-    type Response = TradeHistory;
+    type Response = Vec<TradeHistory>;
 
     const METHOD: reqwest::Method = reqwest::Method::GET;
 
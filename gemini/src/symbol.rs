@@ -1,5 +1,8 @@
+use crate::currency::Currency;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use strum_macros::{AsRefStr, Display, EnumString, IntoStaticStr};
+use strum::IntoEnumIterator;
+use strum_macros::{AsRefStr, Display, EnumIter, EnumString, IntoStaticStr};
 
 #[derive(
     Deserialize,
@@ -12,6 +15,7 @@ use strum_macros::{AsRefStr, Display, EnumString, IntoStaticStr};
     Hash,
     AsRefStr,
     Display,
+    EnumIter,
     EnumString,
     IntoStaticStr,
 )]
@@ -21,6 +25,8 @@ pub enum Symbol {
     BTCUSD,
     #[strum(ascii_case_insensitive)]
     ETHUSD,
+    #[strum(ascii_case_insensitive)]
+    ETHBTC,
     #[serde(other)]
     Unknown,
 }
@@ -30,3 +36,70 @@ impl From<&str> for Symbol {
         s.parse().unwrap()
     }
 }
+
+impl Symbol {
+    /// The currency which is bought or sold, or `None` for unknown symbols.
+    pub fn base(self) -> Option<Currency> {
+        match self {
+            Self::BTCUSD => Some(Currency::BTC),
+            Self::ETHUSD | Self::ETHBTC => Some(Currency::ETH),
+            Self::Unknown => None,
+        }
+    }
+
+    /// The symbol trading `base` for `quote`, or `None` if there is none.
+    pub fn from_currencies(base: Currency, quote: Currency) -> Option<Self> {
+        Self::iter().find(|symbol| symbol.base() == Some(base) && symbol.quote() == Some(quote))
+    }
+
+    /// The smallest increment of a price, or `None` for unknown symbols.
+    pub fn tick_size(self) -> Option<Decimal> {
+        match self {
+            Self::BTCUSD | Self::ETHUSD => Some(Decimal::new(1, 2)),
+            Self::ETHBTC => Some(Decimal::new(1, 5)),
+            Self::Unknown => None,
+        }
+    }
+
+    /// The currency prices are quoted in, or `None` for unknown symbols.
+    pub fn quote(self) -> Option<Currency> {
+        match self {
+            Self::BTCUSD | Self::ETHUSD => Some(Currency::USD),
+            Self::ETHBTC => Some(Currency::BTC),
+            Self::Unknown => None,
+        }
+    }
+}
+
+/// (De)serializes a `Symbol` in lower case, for use with
+/// `#[serde(with = "crate::symbol::lowercase")]` on the fields Gemini writes that way,
+/// e.g. in order statuses.
+pub mod lowercase {
+    use super::Symbol;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(symbol: &Symbol, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&symbol.as_ref().to_ascii_lowercase())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Symbol, D::Error> {
+        Symbol::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_symbol() {
+        assert_eq!(Symbol::ETHBTC, "ethbtc".parse().unwrap());
+        assert!("ZECLTC".parse::<Symbol>().is_err());
+        assert_eq!(
+            Some(Symbol::ETHBTC),
+            Symbol::from_currencies(Currency::ETH, Currency::BTC)
+        );
+        assert_eq!(None, Symbol::from_currencies(Currency::USD, Currency::BTC));
+        assert_eq!(Some(Decimal::new(1, 5)), Symbol::ETHBTC.tick_size());
+    }
+}
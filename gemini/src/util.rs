@@ -32,3 +32,41 @@ macro_rules! string_field_impl {
         }
     };
 }
+
+/// Asserts that `json` deserializes into a `T` which serializes back into it, except for
+/// the fields `T` ignores, and that the serialization deserializes into the same `T`.
+#[cfg(test)]
+pub(crate) fn assert_roundtrip<T>(json: &str)
+where
+    T: serde::de::DeserializeOwned + serde::Serialize,
+{
+    use serde_json::Value;
+
+    fn assert_subset(ser: &Value, wire: &Value, path: &str) {
+        match (ser, wire) {
+            (Value::Object(ser), Value::Object(wire)) => {
+                for (key, value) in ser {
+                    let path = format!("{}.{}", path, key);
+                    match wire.get(key) {
+                        Some(expected) => assert_subset(value, expected, &path),
+                        None => panic!("{} is not in the fixture", path),
+                    }
+                }
+            }
+            (Value::Array(ser), Value::Array(wire)) => {
+                assert_eq!(wire.len(), ser.len(), "{}", path);
+                for (i, (value, expected)) in ser.iter().zip(wire).enumerate() {
+                    assert_subset(value, expected, &format!("{}[{}]", path, i));
+                }
+            }
+            _ => assert_eq!(wire, ser, "{}", path),
+        }
+    }
+
+    let ser = serde_json::to_string(&serde_json::from_str::<T>(json).unwrap()).unwrap();
+    let value = serde_json::from_str::<Value>(&ser).unwrap();
+    assert_subset(&value, &serde_json::from_str(json).unwrap(), "$");
+
+    let again = serde_json::to_value(serde_json::from_str::<T>(&ser).unwrap()).unwrap();
+    assert_eq!(value, again);
+}
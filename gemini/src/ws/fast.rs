@@ -0,0 +1,385 @@
+//! Parsers for the busiest market data messages which avoid the buffering done by the
+//! derived implementations of `#[serde(flatten)]` and `#[serde(untagged)]`.
+//!
+//! Those collect every field of a message into an intermediate tree, copying each
+//! string and decimal into its own `String`, before deserializing it again. The
+//! messages here borrow from the input instead, so decimals are parsed straight from
+//! it, and they produce the same event types. `L2Updates` and `Update` are read in a
+//! single pass, a `Message` takes two, see `Message::from_str`.
+
+use super::{
+    heartbeat::Heartbeat,
+    market::{self, auction, change, trade},
+    marketv2::{auction::Auction, candle::Candles, l2::Change, trade::Trade, HeartbeatData},
+};
+use crate::{chrono::ts_milliseconds_option, symbol::Symbol};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::{fmt, str::FromStr};
+
+mod tag {
+    crate::string_field_impl!(L2Updates, "l2_updates");
+    crate::string_field_impl!(Update, "update");
+}
+
+/// A `marketv2::l2::L2Updates` message, or the `L2Initial` message which starts a
+/// subscription and also lists the recent trades and auction events.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct L2Updates {
+    #[serde(rename = "type")]
+    ty: tag::L2Updates,
+    pub symbol: Symbol,
+    pub changes: Vec<Change>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trades: Vec<Trade>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub auction_events: Vec<Auction>,
+}
+
+/// A message of the v2 market data feed, which is told apart by its type before it is
+/// read rather than by trying to read it as each type in turn like the untagged
+/// `marketv2::Response`.
+///
+/// Parsing takes two passes over the input: the first only reads the type and skips
+/// the other fields without buffering them, the second reads the message of that type.
+#[derive(Debug, Clone)]
+pub enum Message {
+    L2Updates(L2Updates),
+    Trade(Trade),
+    Candles(Candles),
+    Auction(Auction),
+    Heartbeat(Heartbeat<HeartbeatData>),
+}
+
+#[derive(Deserialize)]
+struct MessageType<'a> {
+    #[serde(rename = "type")]
+    ty: &'a str,
+}
+
+impl FromStr for Message {
+    type Err = serde_json::Error;
+
+    fn from_str(json: &str) -> Result<Self, Self::Err> {
+        // skipping the other fields is much cheaper than buffering them
+        let MessageType { ty } = serde_json::from_str(json)?;
+        Ok(match ty {
+            "l2_updates" => Self::L2Updates(serde_json::from_str(json)?),
+            "trade" => Self::Trade(serde_json::from_str(json)?),
+            "auction_indicative" | "auction_result" => Self::Auction(serde_json::from_str(json)?),
+            "heartbeat" => Self::Heartbeat(serde_json::from_str(json)?),
+            ty if ty.starts_with("candles_") => Self::Candles(serde_json::from_str(json)?),
+            other => return Err(de::Error::unknown_variant(other, MESSAGE_TYPES)),
+        })
+    }
+}
+
+const MESSAGE_TYPES: &[&str] = &[
+    "l2_updates",
+    "trade",
+    "auction_indicative",
+    "auction_result",
+    "heartbeat",
+    "candles_1m_updates",
+];
+
+/// A `market::Response::Update` message of the v1 market data feed.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Update {
+    #[serde(rename = "type")]
+    ty: tag::Update,
+    pub socket_sequence: u64,
+    #[serde(rename = "eventId")]
+    pub event_id: u64,
+    #[serde(deserialize_with = "events")]
+    pub events: Vec<market::Event>,
+}
+
+impl From<Update> for market::Update {
+    fn from(update: Update) -> Self {
+        Self {
+            socket_sequence: update.socket_sequence,
+            event_id: update.event_id,
+            events: update.events,
+        }
+    }
+}
+
+fn events<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<market::Event>, D::Error> {
+    struct Visitor;
+
+    impl<'de> de::Visitor<'de> for Visitor {
+        type Value = Vec<market::Event>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a list of market data events")
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut events = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(fields) = seq.next_element::<EventFields>()? {
+                events.push(fields.into_event()?);
+            }
+            Ok(events)
+        }
+    }
+
+    deserializer.deserialize_seq(Visitor)
+}
+
+const EVENT_TYPES: &[&str] = &[
+    "change",
+    "trade",
+    "block_trade",
+    "auction_open",
+    "auction_indicative",
+    "auction_result",
+];
+
+const RESULTS: &[&str] = &["success", "failure"];
+
+/// The fields of every kind of `market::Event`, so that an event can be read before
+/// its type is known and then built from the fields of that type.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct EventFields<'a> {
+    #[serde(rename = "type")]
+    ty: &'a str,
+
+    // changes
+    side: Option<change::Side>,
+    reason: Option<change::Reason>,
+    remaining: Option<Decimal>,
+    delta: Option<Decimal>,
+
+    // trades
+    price: Option<Decimal>,
+    tid: Option<u64>,
+    amount: Option<Decimal>,
+    #[serde(rename = "makerSide")]
+    maker_side: Option<trade::MakerSide>,
+
+    // auctions
+    #[serde(with = "ts_milliseconds_option")]
+    auction_open_ms: Option<DateTime<Utc>>,
+    #[serde(with = "ts_milliseconds_option")]
+    auction_time_ms: Option<DateTime<Utc>>,
+    #[serde(with = "ts_milliseconds_option")]
+    first_indicative_ms: Option<DateTime<Utc>>,
+    #[serde(with = "ts_milliseconds_option")]
+    last_cancel_time_ms: Option<DateTime<Utc>>,
+    eid: Option<u64>,
+    #[serde(with = "ts_milliseconds_option")]
+    time_ms: Option<DateTime<Utc>>,
+    #[serde(borrow)]
+    result: Option<&'a str>,
+    highest_bid_price: Option<Decimal>,
+    lowest_ask_price: Option<Decimal>,
+    collar_price: Option<Decimal>,
+    indicative_price: Option<Decimal>,
+    indicative_quantity: Option<Decimal>,
+    auction_price: Option<Decimal>,
+    auction_quantity: Option<Decimal>,
+}
+
+fn required<T, E: de::Error>(value: Option<T>, field: &'static str) -> Result<T, E> {
+    value.ok_or_else(|| E::missing_field(field))
+}
+
+impl EventFields<'_> {
+    fn into_event<E: de::Error>(self) -> Result<market::Event, E> {
+        Ok(match self.ty {
+            "change" => market::Event::Change(change::Change {
+                ty: change::tag::Change,
+                price: required(self.price, "price")?,
+                side: required(self.side, "side")?,
+                reason: required(self.reason, "reason")?,
+                remaining: required(self.remaining, "remaining")?,
+                delta: required(self.delta, "delta")?,
+            }),
+            "trade" => market::Event::Trade(trade::Trade {
+                ty: trade::TradeTag,
+                data: self.trade_data()?,
+                maker_side: required(self.maker_side, "makerSide")?,
+            }),
+            "block_trade" => market::Event::BlockTrade(trade::BlockTrade {
+                ty: trade::BlockTradeTag,
+                data: self.trade_data()?,
+            }),
+            "auction_open" => {
+                market::Event::Auction(auction::Auction::Open(auction::AuctionOpen {
+                    ty: auction::AuctionOpenTag,
+                    auction_open_ms: required(self.auction_open_ms, "auction_open_ms")?,
+                    auction_time_ms: required(self.auction_time_ms, "auction_time_ms")?,
+                    first_indicative_ms: required(self.first_indicative_ms, "first_indicative_ms")?,
+                    last_cancel_time_ms: required(self.last_cancel_time_ms, "last_cancel_time_ms")?,
+                }))
+            }
+            "auction_indicative" => {
+                let result = match required(self.result, "result")? {
+                    "success" => auction::AuctionIndicativeResult::Success {
+                        indicative_price: required(self.indicative_price, "indicative_price")?,
+                    },
+                    "failure" => auction::AuctionIndicativeResult::Failure,
+                    other => return Err(E::unknown_variant(other, RESULTS)),
+                };
+                market::Event::Auction(auction::Auction::Indicative(auction::AuctionIndicative {
+                    ty: auction::AuctionIndicativeTag,
+                    result,
+                    indicative_quantity: required(self.indicative_quantity, "indicative_quantity")?,
+                    data: self.auction_data()?,
+                }))
+            }
+            "auction_result" => {
+                let result = match required(self.result, "result")? {
+                    "success" => auction::AuctionOutcomeResult::Success {
+                        auction_price: required(self.auction_price, "auction_price")?,
+                        auction_quantity: required(self.auction_quantity, "auction_quantity")?,
+                    },
+                    "failure" => auction::AuctionOutcomeResult::Failure,
+                    other => return Err(E::unknown_variant(other, RESULTS)),
+                };
+                market::Event::Auction(auction::Auction::Outcome(auction::AuctionOutcome {
+                    ty: auction::AuctionOutcomeTag,
+                    result,
+                    data: self.auction_data()?,
+                }))
+            }
+            other => return Err(E::unknown_variant(other, EVENT_TYPES)),
+        })
+    }
+
+    fn trade_data<E: de::Error>(&self) -> Result<trade::TradeData, E> {
+        Ok(trade::TradeData {
+            tid: required(self.tid, "tid")?,
+            price: required(self.price, "price")?,
+            amount: required(self.amount, "amount")?,
+        })
+    }
+
+    fn auction_data<E: de::Error>(&self) -> Result<auction::AuctionData, E> {
+        Ok(auction::AuctionData {
+            eid: required(self.eid, "eid")?,
+            timestamp: required(self.time_ms, "time_ms")?,
+            highest_bid_price: self.highest_bid_price,
+            lowest_ask_price: self.lowest_ask_price,
+            collar_price: required(self.collar_price, "collar_price")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ws::marketv2::l2;
+    use serde_json::json;
+
+    #[test]
+    fn test_l2_updates() {
+        let json = r#"{
+            "type": "l2_updates",
+            "symbol": "BTCUSD",
+            "changes": [["sell", "987.65", "0.123"], ["buy", "123.45", "0.456"]],
+            "trades": [{
+                "type": "trade",
+                "symbol": "BTCUSD",
+                "event_id": 42,
+                "timestamp": 151231241,
+                "price": "123.45",
+                "quantity": "67.89",
+                "side": "sell"
+            }],
+            "auction_events": []
+        }"#;
+
+        let fast = serde_json::from_str::<L2Updates>(json).unwrap();
+        let derived = serde_json::from_str::<l2::L2Initial>(json).unwrap();
+        assert_eq!(
+            serde_json::to_value(&derived.data.changes).unwrap(),
+            serde_json::to_value(&fast.changes).unwrap()
+        );
+        assert_eq!(1, fast.trades.len());
+        assert!(fast.auction_events.is_empty());
+
+        let updates = r#"{"type":"l2_updates","symbol":"ETHUSD","changes":[]}"#;
+        let fast = serde_json::from_str::<L2Updates>(updates).unwrap();
+        assert_eq!(Symbol::ETHUSD, fast.symbol);
+        assert!(fast.trades.is_empty());
+
+        assert!(serde_json::from_str::<L2Updates>(r#"{"type":"trade"}"#).is_err());
+    }
+
+    #[test]
+    fn test_message() {
+        let heartbeat = r#"{"type":"heartbeat","timestamp":1559755800000}"#;
+        assert!(matches!(heartbeat.parse(), Ok(Message::Heartbeat(_))));
+
+        let trade = r#"{"type":"trade","symbol":"BTCUSD","event_id":1,"timestamp":1,"price":"1","quantity":"1","side":"buy"}"#;
+        assert!(matches!(trade.parse(), Ok(Message::Trade(_))));
+
+        let candles = r#"{"type":"candles_15m_updates","symbol":"BTCUSD","changes":[[1559755800000,7781.6,7820.23,7776.56,7819.39,34.76]]}"#;
+        assert!(matches!(candles.parse(), Ok(Message::Candles(c)) if c.candles.len() == 1));
+
+        let l2 = r#"{"type":"l2_updates","symbol":"BTCUSD","changes":[["buy","1","2"]]}"#;
+        assert!(matches!(l2.parse(), Ok(Message::L2Updates(_))));
+
+        assert!(r#"{"type":"unknown"}"#.parse::<Message>().is_err());
+        assert!(r#"{"symbol":"BTCUSD"}"#.parse::<Message>().is_err());
+    }
+
+    #[test]
+    fn test_update() {
+        let events = market::change::test::CHANGE_TEST_STRINGS
+            .iter()
+            .chain(market::trade::test::TRADE_TEST_STRINGS)
+            .chain(market::trade::test::BLOCK_TRADE_TEST_STRINGS)
+            .chain(market::auction::test::AUCTION_TEST_STRINGS)
+            .map(|event| serde_json::from_str::<serde_json::Value>(event).unwrap())
+            .collect::<Vec<_>>();
+        let json = json!({
+            "type": "update",
+            "eventId": 5375547515u64,
+            "timestamp": 1547760288,
+            "timestampms": 1547760288001u64,
+            "socket_sequence": 15,
+            "events": events,
+        })
+        .to_string();
+
+        let fast = market::Update::from(serde_json::from_str::<Update>(&json).unwrap());
+        let market::Response::Update(derived) =
+            serde_json::from_str::<market::Response>(&json).unwrap();
+        assert_eq!(15, fast.socket_sequence);
+        assert_eq!(5375547515, fast.event_id);
+        assert_eq!(events.len(), fast.events.len());
+        assert_eq!(
+            serde_json::to_value(&derived.events).unwrap(),
+            serde_json::to_value(&fast.events).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_events() {
+        let update = |event: &str| {
+            format!(
+                r#"{{"type":"update","eventId":1,"socket_sequence":1,"events":[{}]}}"#,
+                event
+            )
+        };
+
+        for event in &[
+            r#"{"type":"change","side":"bid","price":"1","remaining":"1","delta":"1"}"#,
+            r#"{"type":"trade","tid":1,"price":"1","amount":"1","makerSide":"up"}"#,
+            r#"{"type":"auction_result","eid":1,"time_ms":1,"collar_price":"1","result":"maybe"}"#,
+            r#"{"type":"unknown"}"#,
+            r#"{"tid":1}"#,
+        ] {
+            assert!(
+                serde_json::from_str::<Update>(&update(event)).is_err(),
+                "{}",
+                event
+            );
+        }
+    }
+}
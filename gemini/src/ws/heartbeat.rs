@@ -1,10 +1,10 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 mod tag {
     crate::string_field_impl!(Heartbeat, "heartbeat");
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Heartbeat<D>
 where
     D: Clone + std::fmt::Debug,
@@ -1,12 +1,13 @@
-use chrono::{serde::ts_milliseconds, DateTime, Utc};
+use crate::chrono::ts_milliseconds;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 crate::string_field_impl!(AuctionOpenTag, "auction_open");
 crate::string_field_impl!(AuctionIndicativeTag, "auction_indicative");
 crate::string_field_impl!(AuctionOutcomeTag, "auction_result");
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct AuctionOpen {
     #[serde(rename = "type")]
     pub ty: AuctionOpenTag,
@@ -20,25 +21,30 @@ pub struct AuctionOpen {
     pub last_cancel_time_ms: DateTime<Utc>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct AuctionData {
     pub eid: u64,
     #[serde(rename = "time_ms", with = "ts_milliseconds")]
     pub timestamp: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub highest_bid_price: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub lowest_ask_price: Option<Decimal>,
     pub collar_price: Decimal,
 }
 
-// TODO: move to common
-#[derive(Deserialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
-#[serde(rename_all = "lowercase", tag = "result")]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[serde(
+    rename_all = "lowercase",
+    tag = "result",
+    into = "crate::common::AuctionResultRepr"
+)]
 pub enum AuctionIndicativeResult {
     Success { indicative_price: Decimal },
     Failure,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct AuctionIndicative {
     #[serde(rename = "type")]
     pub ty: AuctionIndicativeTag,
@@ -50,8 +56,12 @@ pub struct AuctionIndicative {
     pub data: AuctionData,
 }
 
-#[derive(Deserialize, Debug, Clone)]
-#[serde(rename_all = "lowercase", tag = "result")]
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(
+    rename_all = "lowercase",
+    tag = "result",
+    into = "crate::common::AuctionResultRepr"
+)]
 pub enum AuctionOutcomeResult {
     Success {
         auction_price: Decimal,
@@ -60,7 +70,7 @@ pub enum AuctionOutcomeResult {
     Failure,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct AuctionOutcome {
     #[serde(rename = "type")]
     pub ty: AuctionOutcomeTag,
@@ -70,9 +80,8 @@ pub struct AuctionOutcome {
     pub data: AuctionData,
 }
 
-
 // TODO: maybe tag and make tagged/untagged variant versions
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(untagged)]
 pub enum Auction {
     Open(AuctionOpen),
@@ -85,7 +94,13 @@ pub(crate) mod test {
     use super::*;
     use rust_decimal_macros::dec;
 
-    pub const AUCTION_TEST_STRINGS: &[&str] = &[AUCTION_OPEN, AUCTION_INDICATIVE_SUCCESS, AUCTION_INDICATIVE_FAILURE, AUCTION_OUTCOME_SUCCESS, AUCTION_OUTCOME_FAILURE];
+    pub const AUCTION_TEST_STRINGS: &[&str] = &[
+        AUCTION_OPEN,
+        AUCTION_INDICATIVE_SUCCESS,
+        AUCTION_INDICATIVE_FAILURE,
+        AUCTION_OUTCOME_SUCCESS,
+        AUCTION_OUTCOME_FAILURE,
+    ];
 
     const AUCTION_OPEN: &str = r#"{
         "auction_open_ms": 1486591200000,
@@ -147,6 +162,8 @@ pub(crate) mod test {
         assert_eq!(open.auction_time_ms.timestamp_millis(), 1486674000000);
         assert_eq!(open.first_indicative_ms.timestamp_millis(), 1486673400000);
         assert_eq!(open.last_cancel_time_ms.timestamp_millis(), 1486673985000);
+
+        crate::util::assert_roundtrip::<AuctionOpen>(AUCTION_OPEN);
     }
 
     #[test]
@@ -174,6 +191,9 @@ pub(crate) mod test {
         assert_eq!(failure.data.collar_price, dec!(7730.695));
         assert_eq!(failure.indicative_quantity, dec!(45.43325086));
         assert!(matches!(failure.result, AuctionIndicativeResult::Failure));
+
+        crate::util::assert_roundtrip::<AuctionIndicative>(AUCTION_INDICATIVE_SUCCESS);
+        crate::util::assert_roundtrip::<AuctionIndicative>(AUCTION_INDICATIVE_FAILURE);
     }
 
     #[test]
@@ -197,6 +217,9 @@ pub(crate) mod test {
         assert!(failure.data.lowest_ask_price.is_none());
         assert_eq!(failure.data.collar_price, dec!(7769.005));
         assert!(matches!(failure.result, AuctionOutcomeResult::Failure));
+
+        crate::util::assert_roundtrip::<AuctionOutcome>(AUCTION_OUTCOME_SUCCESS);
+        crate::util::assert_roundtrip::<AuctionOutcome>(AUCTION_OUTCOME_FAILURE);
     }
 
     #[test]
@@ -1,14 +1,14 @@
 use rust_decimal::Decimal;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 #[serde(rename_all = "lowercase")]
 pub enum Side {
     Bid,
     Ask,
 }
 
-#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 #[serde(rename_all = "lowercase")]
 pub enum Reason {
     Place,
@@ -21,7 +21,7 @@ pub mod tag {
     crate::string_field_impl!(Change, "change");
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Change {
     #[serde(rename = "type")]
     pub ty: tag::Change,
@@ -69,5 +69,7 @@ pub(crate) mod test {
         assert_eq!(change.remaining, dec!(1.6));
         assert_eq!(change.delta, dec!(0.8));
         assert_eq!(change.reason, Reason::Place);
+
+        crate::util::assert_roundtrip::<Change>(CHANGE);
     }
 }
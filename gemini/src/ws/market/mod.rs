@@ -9,7 +9,7 @@ use crate::symbol::Symbol;
 
 use super::client::WssRequest;
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(untagged)]
 pub enum Event {
     Trade(trade::Trade),
@@ -20,7 +20,7 @@ pub enum Event {
     Auction(auction::Auction),
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Update<E = Event> {
     pub socket_sequence: u64,
     #[serde(rename = "eventId")]
@@ -31,7 +31,7 @@ pub struct Update<E = Event> {
     pub events: Vec<E>,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum Response {
     // TODO
@@ -91,6 +91,7 @@ mod test {
         ($strings:path, $match:pat_param) => {
             for s in $strings {
                 assert!(matches!(serde_json::from_str::<Event>(s).unwrap(), $match));
+                crate::util::assert_roundtrip::<Event>(s);
             }
         };
     }
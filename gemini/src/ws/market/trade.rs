@@ -1,7 +1,7 @@
 use rust_decimal::Decimal;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 #[serde(rename_all = "lowercase")]
 pub enum MakerSide {
     Bid,
@@ -9,7 +9,7 @@ pub enum MakerSide {
     Auction,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct TradeData {
     // The trade id.
     pub tid: u64,
@@ -23,7 +23,7 @@ pub struct TradeData {
 
 crate::string_field_impl!(TradeTag, "trade");
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Trade {
     #[serde(rename = "type")]
     pub ty: TradeTag,
@@ -38,7 +38,7 @@ pub struct Trade {
 
 crate::string_field_impl!(BlockTradeTag, "block_trade");
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct BlockTrade {
     #[serde(rename = "type")]
     pub ty: BlockTradeTag,
@@ -78,6 +78,8 @@ pub(crate) mod test {
         assert_eq!(trade.data.price, dec!(3632.54));
         assert_eq!(trade.data.amount, dec!(0.1362819142));
         assert!(matches!(trade.maker_side, MakerSide::Ask));
+
+        crate::util::assert_roundtrip::<Trade>(TRADE);
     }
 
     #[test]
@@ -87,5 +89,7 @@ pub(crate) mod test {
         assert_eq!(trade.data.tid, 1111597035);
         assert_eq!(trade.data.price, dec!(10100.00));
         assert_eq!(trade.data.amount, dec!(1000));
+
+        crate::util::assert_roundtrip::<BlockTrade>(BLOCK_TRADE);
     }
 }
@@ -1,11 +1,16 @@
-use chrono::{serde::ts_milliseconds, DateTime, Utc};
+use crate::chrono::ts_milliseconds;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::symbol::Symbol;
 
-#[derive(Deserialize, Clone, Debug)]
-#[serde(rename_all = "lowercase", tag = "result")]
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(
+    rename_all = "lowercase",
+    tag = "result",
+    into = "crate::common::AuctionResultRepr"
+)]
 pub enum Result {
     Success {
         auction_price: Decimal,
@@ -14,31 +19,33 @@ pub enum Result {
     Failure,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct AuctionData {
     pub symbol: Symbol,
     #[serde(rename = "time_ms", with = "ts_milliseconds")]
     pub timestamp: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub highest_bid_price: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub lowest_ask_price: Option<Decimal>,
     pub collar_price: Decimal,
     #[serde(flatten)]
     pub result: Result,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct AuctionIndicative {
     #[serde(flatten)]
     pub data: AuctionData,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct AuctionResult {
     #[serde(flatten)]
     pub data: AuctionData,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(tag = "type")]
 pub enum Auction {
     #[serde(rename = "auction_indicative")]
@@ -47,6 +54,19 @@ pub enum Auction {
     Result(AuctionResult),
 }
 
+impl Auction {
+    pub fn symbol(&self) -> Symbol {
+        self.data().symbol
+    }
+
+    pub fn data(&self) -> &AuctionData {
+        match self {
+            Self::Indicative(indicative) => &indicative.data,
+            Self::Result(result) => &result.data,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -120,6 +140,9 @@ mod test {
         assert!(failure.data.lowest_ask_price.is_none());
         assert_eq!(failure.data.collar_price, dec!(1.2));
         assert!(matches!(failure.data.result, Result::Failure));
+
+        crate::util::assert_roundtrip::<AuctionIndicative>(AUCTION_INDICATIVE_SUCCESS);
+        crate::util::assert_roundtrip::<AuctionIndicative>(AUCTION_INDICATIVE_FAILURE);
     }
 
     #[test]
@@ -144,6 +167,9 @@ mod test {
         assert!(failure.data.lowest_ask_price.is_none());
         assert_eq!(failure.data.collar_price, dec!(1.2));
         assert!(matches!(failure.data.result, Result::Failure));
+
+        crate::util::assert_roundtrip::<AuctionResult>(AUCTION_OUTCOME_SUCCESS);
+        crate::util::assert_roundtrip::<AuctionResult>(AUCTION_OUTCOME_FAILURE);
     }
 
     #[test]
@@ -187,5 +213,14 @@ mod test {
                 }
             })
         ));
+
+        for json in &[
+            AUCTION_INDICATIVE_SUCCESS,
+            AUCTION_INDICATIVE_FAILURE,
+            AUCTION_OUTCOME_SUCCESS,
+            AUCTION_OUTCOME_FAILURE,
+        ] {
+            crate::util::assert_roundtrip::<Auction>(json);
+        }
     }
 }
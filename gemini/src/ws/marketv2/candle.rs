@@ -1,5 +1,5 @@
 use crate::symbol::Symbol;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 pub use crate::common::Candle;
 
@@ -15,7 +15,7 @@ candles 	Array of Arrays (TOHLCV) 	Changes to order book
 -- -- volume 	decimal 	Volume
 */
 
-#[derive(Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub enum CandleType {
     #[serde(rename = "candles_1m_updates")]
     Minute1,
@@ -33,7 +33,7 @@ pub enum CandleType {
     Day1,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Candles {
     #[serde(rename = "type")]
     pub candle_type: CandleType,
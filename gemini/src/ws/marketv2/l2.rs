@@ -1,11 +1,11 @@
 use crate::symbol::Symbol;
 use rust_decimal::Decimal;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 pub use super::auction::{Auction, AuctionData, AuctionIndicative, AuctionResult};
 pub use super::trade::Trade;
 
-#[derive(Deserialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ChangeType {
     Buy,
@@ -14,12 +14,12 @@ pub enum ChangeType {
 
 #[derive(Debug, Clone)]
 pub struct ChangeData {
-    price_level: Decimal,
-    quantity: Decimal,
+    pub price_level: Decimal,
+    pub quantity: Decimal,
 }
 
-#[derive(Deserialize, Debug, Clone)]
-#[serde(from = "ChangeRepr")]
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(from = "ChangeRepr", into = "ChangeRepr")]
 pub enum Change {
     Buy(ChangeData),
     Sell(ChangeData),
@@ -40,11 +40,20 @@ impl From<ChangeRepr> for Change {
     }
 }
 
+impl From<Change> for ChangeRepr {
+    fn from(change: Change) -> Self {
+        match change {
+            Change::Buy(data) => (ChangeType::Buy, data.price_level, data.quantity),
+            Change::Sell(data) => (ChangeType::Sell, data.price_level, data.quantity),
+        }
+    }
+}
+
 mod tag {
     crate::string_field_impl!(L2Updates, "l2_updates");
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct L2Data {
     #[serde(rename = "type")]
     ty: tag::L2Updates,
@@ -52,7 +61,7 @@ pub struct L2Data {
     pub changes: Vec<Change>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct L2Initial {
     #[serde(flatten)]
     pub data: L2Data,
@@ -60,7 +69,7 @@ pub struct L2Initial {
     pub auction_events: Vec<Auction>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct L2Updates {
     #[serde(flatten)]
     pub data: L2Data,
@@ -102,5 +111,7 @@ mod test {
                 price_level,
                 quantity,
             }) if price_level == dec!(123.45) && quantity == dec!(0.456)));
+
+        crate::util::assert_roundtrip::<L2Updates>(json);
     }
 }
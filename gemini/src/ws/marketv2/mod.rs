@@ -4,12 +4,13 @@ pub mod l2;
 pub mod trade;
 
 use super::heartbeat::Heartbeat;
+use crate::chrono::ts_milliseconds;
 use crate::symbol::Symbol;
-use chrono::{serde::ts_milliseconds, DateTime, Utc};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
 
-#[derive(Deserialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum Side {
     Buy,
@@ -64,13 +65,13 @@ pub struct Unsubscribe {
     subscriptions: Vec<Subscription>,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct HeartbeatData {
     #[serde(with = "ts_milliseconds")]
     timestamp: DateTime<Utc>,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(untagged)]
 pub enum Response<D> {
     Heartbeat(Heartbeat<HeartbeatData>),
@@ -1,7 +1,8 @@
 use super::Side;
-use chrono::{serde::ts_milliseconds, DateTime, Utc};
+use crate::chrono::ts_milliseconds;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::symbol::Symbol;
 
@@ -9,7 +10,7 @@ mod tag {
     crate::string_field_impl!(Trade, "trade");
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Trade {
     #[serde(rename = "type")]
     ty: tag::Trade,
@@ -47,5 +48,7 @@ mod test {
         assert_eq!(trade.price, dec!(123.45));
         assert_eq!(trade.quantity, dec!(67.89));
         assert!(matches!(trade.side, Side::Sell));
+
+        crate::util::assert_roundtrip::<Trade>(json);
     }
 }
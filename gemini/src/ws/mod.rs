@@ -1,4 +1,6 @@
 pub mod client;
+pub mod fast;
 pub mod heartbeat;
 pub mod market;
 pub mod marketv2;
+pub mod record;
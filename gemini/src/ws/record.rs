@@ -0,0 +1,333 @@
+use std::{
+    fs::File,
+    future::Future,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::chrono::ts_milliseconds;
+use async_tungstenite::tungstenite::{Error, Message};
+use chrono::{DateTime, Utc};
+use futures::{ready, Sink, Stream};
+use serde::{Deserialize, Serialize};
+use tokio::time::{Instant, Sleep};
+
+/// A single text frame received over a websocket along with the time it was received.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    #[serde(rename = "timestampms", with = "ts_milliseconds")]
+    pub timestamp: DateTime<Utc>,
+    pub text: String,
+}
+
+#[derive(Serialize)]
+struct FrameRef<'a> {
+    #[serde(rename = "timestampms", with = "ts_milliseconds")]
+    timestamp: DateTime<Utc>,
+    text: &'a str,
+}
+
+/// Wraps a websocket stream and writes every received text frame to `writer`,
+/// one JSON encoded `Frame` per line.
+///
+/// The recorder is itself a `Stream` and `Sink` of websocket messages, so it can be
+/// used in place of the stream it wraps.
+pub struct Recorder<S, W> {
+    stream: S,
+    writer: W,
+}
+
+impl<S> Recorder<S, BufWriter<File>> {
+    /// Creates (or truncates) the file at `path` and records `stream` into it.
+    pub fn create(stream: S, path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self::new(stream, BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<S, W> Recorder<S, W>
+where
+    W: Write,
+{
+    pub fn new(stream: S, writer: W) -> Self {
+        Self { stream, writer }
+    }
+
+    pub fn into_inner(self) -> (S, W) {
+        (self.stream, self.writer)
+    }
+
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn record(&mut self, text: &str) -> std::io::Result<()> {
+        let frame = FrameRef {
+            timestamp: Utc::now(),
+            text,
+        };
+        serde_json::to_writer(&mut self.writer, &frame)?;
+        self.writer.write_all(b"\n")
+    }
+}
+
+impl<S, W> Stream for Recorder<S, W>
+where
+    S: Stream<Item = Result<Message, Error>> + Unpin,
+    W: Write + Unpin,
+{
+    type Item = Result<Message, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = ready!(Pin::new(&mut self.stream).poll_next(cx));
+
+        let recorded = match &item {
+            Some(Ok(Message::Text(text))) => self.record(text),
+            None => self.flush(),
+            _ => Ok(()),
+        };
+
+        match recorded {
+            Ok(()) => Poll::Ready(item),
+            Err(e) => Poll::Ready(Some(Err(Error::Io(e)))),
+        }
+    }
+}
+
+impl<S, W> Sink<Message> for Recorder<S, W>
+where
+    S: Sink<Message, Error = Error> + Unpin,
+    W: Unpin,
+{
+    type Error = Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.stream).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Message) -> Result<(), Error> {
+        Pin::new(&mut self.stream).start_send(item)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+}
+
+/// The pace at which a `Replayer` yields recorded frames.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Speed {
+    /// Frames are yielded with the same spacing they were recorded with.
+    RealTime,
+    /// Frames are yielded `n` times faster than they were recorded.
+    Factor(f64),
+    /// Frames are yielded as fast as they are polled.
+    Max,
+}
+
+/// Replays a recording made by a `Recorder` as a `Stream` of websocket messages.
+///
+/// Messages sent into the replayer are discarded, so code which subscribes before
+/// reading the feed works unchanged against a recording.
+pub struct Replayer<R> {
+    frames: std::io::Lines<R>,
+    speed: Speed,
+    // The instant the first frame was replayed and the time it was recorded at.
+    origin: Option<(Instant, DateTime<Utc>)>,
+    pending: Option<Frame>,
+    delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl Replayer<BufReader<File>> {
+    pub fn open(path: impl AsRef<Path>, speed: Speed) -> std::io::Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(path)?), speed))
+    }
+}
+
+impl<R> Replayer<R>
+where
+    R: BufRead,
+{
+    pub fn new(reader: R, speed: Speed) -> Self {
+        Self {
+            frames: reader.lines(),
+            speed,
+            origin: None,
+            pending: None,
+            delay: None,
+        }
+    }
+
+    fn next_frame(&mut self) -> Option<std::io::Result<Frame>> {
+        self.frames
+            .next()
+            .map(|line| Ok(serde_json::from_str::<Frame>(&line?)?))
+    }
+
+    fn deadline(&mut self, timestamp: DateTime<Utc>) -> Option<Instant> {
+        let factor = match self.speed {
+            Speed::RealTime => 1.0,
+            Speed::Factor(factor) => factor,
+            Speed::Max => return None,
+        };
+
+        let (start, first) = *self
+            .origin
+            .get_or_insert_with(|| (Instant::now(), timestamp));
+        // frames recorded out of order are replayed immediately
+        let offset = (timestamp - first).to_std().ok()?;
+        Some(start + offset.div_f64(factor))
+    }
+}
+
+impl<R> Stream for Replayer<R>
+where
+    R: BufRead + Unpin,
+{
+    type Item = Result<Message, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        if this.pending.is_none() {
+            match this.next_frame() {
+                Some(Ok(frame)) => {
+                    this.delay = this
+                        .deadline(frame.timestamp)
+                        .map(|deadline| Box::pin(tokio::time::sleep_until(deadline)));
+                    this.pending = Some(frame);
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(Error::Io(e)))),
+                None => return Poll::Ready(None),
+            }
+        }
+
+        if let Some(delay) = &mut this.delay {
+            ready!(delay.as_mut().poll(cx));
+            this.delay = None;
+        }
+
+        match this.pending.take() {
+            Some(frame) => Poll::Ready(Some(Ok(Message::Text(frame.text)))),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+impl<R> Sink<Message> for Replayer<R> {
+    type Error = Error;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, _item: Message) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::{SinkExt, StreamExt};
+    use std::io::Cursor;
+
+    fn feed() -> impl Stream<Item = Result<Message, Error>> + Unpin {
+        futures::stream::iter(vec![
+            Ok(Message::Text(r#"{"type":"heartbeat"}"#.to_owned())),
+            Ok(Message::Ping(vec![1, 2, 3])),
+            Ok(Message::Text("line\nbreak".to_owned())),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_record_replay() {
+        let mut recorder = Recorder::new(feed(), Vec::new());
+        let received = (&mut recorder).collect::<Vec<_>>().await;
+        assert_eq!(3, received.len());
+
+        let (_, recording) = recorder.into_inner();
+        assert_eq!(2, recording.iter().filter(|b| **b == b'\n').count());
+
+        let mut replayer = Replayer::new(Cursor::new(recording), Speed::Max);
+        replayer
+            .send(Message::Text("subscribe".to_owned()))
+            .await
+            .unwrap();
+
+        let replayed = replayer.map(|msg| msg.unwrap()).collect::<Vec<_>>().await;
+        assert_eq!(
+            replayed,
+            vec![
+                Message::Text(r#"{"type":"heartbeat"}"#.to_owned()),
+                Message::Text("line\nbreak".to_owned()),
+            ]
+        );
+    }
+
+    fn recording(seconds: &[i64]) -> Cursor<Vec<u8>> {
+        let mut recording = Vec::new();
+        for (i, second) in seconds.iter().enumerate() {
+            let frame = Frame {
+                timestamp: chrono::TimeZone::timestamp_opt(&Utc, *second, 0).unwrap(),
+                text: i.to_string(),
+            };
+            serde_json::to_writer(&mut recording, &frame).unwrap();
+            recording.push(b'\n');
+        }
+        Cursor::new(recording)
+    }
+
+    // The seconds after the first frame each frame is replayed at.
+    async fn replay_times(speed: Speed, seconds: &[i64]) -> Vec<f64> {
+        let mut replayer = Replayer::new(recording(seconds), speed);
+        let start = Instant::now();
+        let mut times = Vec::new();
+        while let Some(message) = replayer.next().await {
+            message.unwrap();
+            times.push((Instant::now() - start).as_secs_f64());
+        }
+        times
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_replay_pacing() {
+        assert_eq!(
+            vec![0.0, 2.0, 5.0],
+            replay_times(Speed::RealTime, &[10, 12, 15]).await
+        );
+        assert_eq!(
+            vec![0.0, 1.0, 2.5],
+            replay_times(Speed::Factor(2.0), &[10, 12, 15]).await
+        );
+        assert_eq!(
+            vec![0.0, 0.0, 0.0],
+            replay_times(Speed::Max, &[10, 12, 15]).await
+        );
+        // frames recorded out of order are replayed immediately
+        assert_eq!(
+            vec![0.0, 2.0, 2.0, 3.0],
+            replay_times(Speed::RealTime, &[10, 12, 11, 13]).await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_replay_invalid_frame() {
+        let mut replayer = Replayer::new(Cursor::new("not json\n"), Speed::RealTime);
+        assert!(matches!(replayer.next().await, Some(Err(Error::Io(_)))));
+        assert!(replayer.next().await.is_none());
+    }
+}
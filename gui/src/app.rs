@@ -1,12 +1,14 @@
 use crate::decimal::*;
 use async_tungstenite::tungstenite;
 use chrono::{Date, DateTime, Duration, Local, TimeZone, Utc};
+use futures::{Sink, Stream};
 use futures_util::{SinkExt, StreamExt};
 use gemini::{
     symbol::Symbol,
     ws::{
         self,
         marketv2::{l2::*, trade::Trade},
+        record::{Recorder, Replayer, Speed},
     },
 };
 use iced::{
@@ -21,14 +23,29 @@ use reqwest::header::InvalidHeaderName;
 use rust_decimal::{prelude::ToPrimitive, Decimal};
 use rust_decimal_macros::dec;
 use std::hash::Hasher;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
-struct PollOB;
+/// Where the order book subscription reads its market data from.
+#[derive(Debug, Clone)]
+pub enum Feed {
+    /// Connects to the Gemini market data feed, recording it to the file if one is given.
+    Live(Option<PathBuf>),
+    /// Replays a recording made by a live feed.
+    Replay(PathBuf, Speed),
+}
+
+impl Default for Feed {
+    fn default() -> Self {
+        Feed::Live(None)
+    }
+}
+
+struct PollOB(Feed);
 
 pub enum State<S: futures::Stream> {
-    Start,
+    Start(S),
     Polling(S),
-    End,
 }
 
 impl<H: Hasher, I> iced_native::subscription::Recipe<H, I> for PollOB {
@@ -37,63 +54,87 @@ impl<H: Hasher, I> iced_native::subscription::Recipe<H, I> for PollOB {
     fn hash(&self, state: &mut H) {
         use std::hash::Hash;
         std::any::TypeId::of::<Self>().hash(state);
+        format!("{:?}", self.0).hash(state);
     }
 
     fn stream(
         self: Box<Self>,
         _input: iced_futures::BoxStream<I>,
     ) -> iced_futures::BoxStream<Self::Output> {
-        Box::pin(futures::stream::unfold(
-            State::<gemini::ws::client::WssStream>::Start,
-            |state| async move {
-                match state {
-                    State::Start => {
-                        let (mut stream, _) = gemini::ws::client::connect_wss(
-                            reqwest::Url::parse("wss://api.gemini.com/v2/marketdata").unwrap(),
-                        )
+        match self.0 {
+            Feed::Live(record) => Box::pin(
+                futures::stream::once(async move {
+                    let (stream, _) = gemini::ws::client::connect_wss(
+                        reqwest::Url::parse("wss://api.gemini.com/v2/marketdata").unwrap(),
+                    )
+                    .await
+                    .unwrap();
+
+                    match record {
+                        Some(path) => poll_feed(Recorder::create(stream, path).unwrap()),
+                        None => poll_feed(stream),
+                    }
+                })
+                .flatten(),
+            ),
+            Feed::Replay(path, speed) => poll_feed(Replayer::open(path, speed).unwrap()),
+        }
+    }
+}
+
+/// Subscribes to the BTCUSD order book on a live or replayed feed and yields its messages
+/// until the feed ends.
+fn poll_feed<S>(stream: S) -> iced_futures::BoxStream<Message>
+where
+    S: Stream<Item = Result<tungstenite::Message, tungstenite::Error>>
+        + Sink<tungstenite::Message, Error = tungstenite::Error>
+        + Unpin
+        + Send
+        + 'static,
+{
+    Box::pin(futures::stream::unfold(
+        State::Start(stream),
+        |state| async move {
+            match state {
+                State::Start(mut stream) => {
+                    let req = ws::marketv2::Subscribe::builder()
+                        .subscriptions(vec![ws::marketv2::Subscription::builder()
+                            .name(ws::marketv2::SubscriptionType::L2)
+                            .symbols(vec![Symbol::BTCUSD])
+                            .build()])
+                        .build();
+                    stream
+                        .send(tungstenite::Message::Text(
+                            serde_json::to_string(&req).unwrap(),
+                        ))
                         .await
                         .unwrap();
 
-                        let req = ws::marketv2::Subscribe::builder()
-                            .subscriptions(vec![ws::marketv2::Subscription::builder()
-                                .name(ws::marketv2::SubscriptionType::L2)
-                                .symbols(vec![Symbol::BTCUSD])
-                                .build()])
-                            .build();
-                        stream
-                            .send(tungstenite::Message::Text(
-                                serde_json::to_string(&req).unwrap(),
-                            ))
-                            .await
-                            .unwrap();
-
-                        let initial = match stream.next().await {
-                            Some(Ok(tungstenite::Message::Text(text))) => {
-                                serde_json::from_str::<gemini::ws::marketv2::l2::L2Initial>(&text)
-                                    .unwrap()
-                            }
-                            Some(e) => panic!("{:?}", e),
-                            None => panic!("AHH"),
-                        };
-
-                        Some((Message::Init(initial), State::Polling(stream)))
-                    }
-                    State::Polling(mut stream) => match stream.next().await {
+                    let initial = match stream.next().await {
                         Some(Ok(tungstenite::Message::Text(text))) => {
-                            if let Ok(trade) = serde_json::from_str(&text) {
-                                Some((Message::Trade(trade), State::Polling(stream)))
-                            } else {
-                                Some((Message::Other(text), State::Polling(stream)))
-                            }
+                            serde_json::from_str::<gemini::ws::marketv2::l2::L2Initial>(&text)
+                                .unwrap()
                         }
                         Some(e) => panic!("{:?}", e),
-                        None => panic!("ahh"),
-                    },
-                    State::End => None,
+                        None => return None,
+                    };
+
+                    Some((Message::Init(initial), State::Polling(stream)))
                 }
-            },
-        ))
-    }
+                State::Polling(mut stream) => match stream.next().await {
+                    Some(Ok(tungstenite::Message::Text(text))) => {
+                        if let Ok(trade) = serde_json::from_str(&text) {
+                            Some((Message::Trade(trade), State::Polling(stream)))
+                        } else {
+                            Some((Message::Other(text), State::Polling(stream)))
+                        }
+                    }
+                    Some(e) => panic!("{:?}", e),
+                    None => None,
+                },
+            }
+        },
+    ))
 }
 
 fn parse_time(t: &str) -> Date<Local> {
@@ -112,17 +153,19 @@ pub enum Message {
 }
 
 pub struct App {
+    feed: Feed,
     ob: StatsChart,
 }
 
 impl Application for App {
     type Message = Message;
     type Executor = executor::Default;
-    type Flags = ();
+    type Flags = Feed;
 
-    fn new(_flags: Self::Flags) -> (Self, iced::Command<Self::Message>) {
+    fn new(flags: Self::Flags) -> (Self, iced::Command<Self::Message>) {
         (
             Self {
+                feed: flags,
                 ob: Default::default(),
             },
             Command::none(),
@@ -153,7 +196,7 @@ impl Application for App {
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        Subscription::from_recipe(PollOB)
+        Subscription::from_recipe(PollOB(self.feed.clone()))
     }
 }
 
@@ -184,11 +227,13 @@ impl StatsChart {
         let mut add_trade = |trade: Trade| {
             self.price = trade.price;
             self.curr.add(trade.price);
-            self.data.push(MeanDate {
-                mean: self.curr.mean(),
-                date: trade.timestamp,
-                stddev: self.curr.stddev(),
-            });
+            if let (Some(mean), Some(stddev)) = (self.curr.mean(), self.curr.stddev()) {
+                self.data.push(MeanDate {
+                    mean,
+                    date: trade.timestamp,
+                    stddev,
+                });
+            }
         };
 
         match message {
@@ -233,7 +278,7 @@ impl Chart<Message> for StatsChart {
             .max()
             .unwrap();
 
-        let std = self.curr.stddev() * dec!(2);
+        let std = self.curr.stddev().unwrap_or_default() * dec!(2);
 
         let mut chart = builder
             .caption(
@@ -7,18 +7,42 @@ use gemini::{
         client::{Client, Error},
     },
     symbol::Symbol,
+    ws::record::Speed,
 };
+use gui::app::Feed;
 use iced::{Application, Settings};
 use itertools::{Itertools, MinMaxResult::MinMax};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::convert::TryFrom;
 
+/// Usage: `gui [--record <file>] [--replay <file> [--speed <factor>|max]]`
 fn main() {
-    gui::App::run(Settings {
-        ..Settings::default()
-    })
-    .unwrap();
+    let mut args = std::env::args().skip(1);
+    let mut feed = Feed::default();
+    let mut speed = Speed::RealTime;
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .unwrap_or_else(|| panic!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--record" => feed = Feed::Live(Some(value().into())),
+            "--replay" => feed = Feed::Replay(value().into(), Speed::RealTime),
+            "--speed" => {
+                speed = match value().as_str() {
+                    "max" => Speed::Max,
+                    factor => Speed::Factor(factor.parse().expect("Invalid speed")),
+                }
+            }
+            _ => panic!("Unknown argument: {}", arg),
+        }
+    }
+    if let Feed::Replay(_, replay_speed) = &mut feed {
+        *replay_speed = speed;
+    }
+
+    gui::App::run(Settings::with_flags(feed)).unwrap();
 }
 
 /*
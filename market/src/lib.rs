@@ -1,2 +1,6 @@
 pub mod order_book;
+#[cfg(feature = "gemini")]
+pub mod paper;
+#[cfg(feature = "gemini")]
+pub mod returns;
 pub mod stats;
@@ -132,6 +132,13 @@ impl OrderBook {
         }
     }
 
+    /// The midpoint between the best bid and ask, or `None` if either side is empty.
+    pub fn mid(&self) -> Option<Decimal> {
+        let bid = self.top(Order::Bid(()))?.price.0;
+        let ask = self.top(Order::Ask(()))?.price.0;
+        Some((bid + ask) / Decimal::from(2))
+    }
+
     fn update_impl(set: &mut BTreeSet<OrderData>, order: OrderData) {
         if order.quantity.0.is_zero() {
             set.remove(&order.price);
@@ -176,5 +183,9 @@ mod test {
 
         book.update(Order::Ask(OrderData::new(dec!(2.0), dec!(0))));
         assert_eq!(1, book.asks_len());
+
+        assert_eq!(None, book.mid());
+        book.update(Order::Bid(OrderData::new(dec!(0.5), dec!(1.0))));
+        assert_eq!(Some(dec!(0.75)), book.mid());
     }
 }
@@ -0,0 +1,797 @@
+use crate::order_book::{Order as BookOrder, OrderBook, OrderData};
+use chrono::{DateTime, Duration, Utc};
+use gemini::symbol::Symbol;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+pub type OrderId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    fn sign(self) -> Decimal {
+        match self {
+            Self::Buy => Decimal::ONE,
+            Self::Sell => -Decimal::ONE,
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            Self::Buy => Self::Sell,
+            Self::Sell => Self::Buy,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeInForce {
+    /// Rests on the book until it is filled or cancelled.
+    GoodTillCancel,
+    /// Fills as much as possible on arrival and cancels the remainder.
+    ImmediateOrCancel,
+    /// Rejected if any of it would fill on arrival.
+    MakerOrCancel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderKind {
+    Market,
+    Limit(Decimal, TimeInForce),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewOrder {
+    pub symbol: Symbol,
+    pub side: Side,
+    pub quantity: Decimal,
+    pub kind: OrderKind,
+}
+
+impl NewOrder {
+    pub fn market(symbol: Symbol, side: Side, quantity: Decimal) -> Self {
+        Self {
+            symbol,
+            side,
+            quantity,
+            kind: OrderKind::Market,
+        }
+    }
+
+    pub fn limit(symbol: Symbol, side: Side, quantity: Decimal, price: Decimal) -> Self {
+        Self::limit_with(symbol, side, quantity, price, TimeInForce::GoodTillCancel)
+    }
+
+    pub fn limit_with(
+        symbol: Symbol,
+        side: Side,
+        quantity: Decimal,
+        price: Decimal,
+        time_in_force: TimeInForce,
+    ) -> Self {
+        Self {
+            symbol,
+            side,
+            quantity,
+            kind: OrderKind::Limit(price, time_in_force),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Liquidity {
+    Maker,
+    Taker,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fill {
+    pub order_id: OrderId,
+    pub time: DateTime<Utc>,
+    pub symbol: Symbol,
+    pub side: Side,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    pub fee: Decimal,
+    pub liquidity: Liquidity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reject {
+    /// The quantity was zero or negative.
+    InvalidQuantity,
+    /// The limit price was zero or negative.
+    InvalidPrice,
+    /// The balance not already committed to open orders doesn't cover the order.
+    InsufficientFunds,
+    /// A maker-or-cancel order would have filled on arrival.
+    WouldTake,
+}
+
+/// Fees as a fraction of the notional value, e.g. `0.001` for 10 basis points.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Fees {
+    pub maker: Decimal,
+    pub taker: Decimal,
+}
+
+impl Fees {
+    pub fn fee(&self, notional: Decimal, liquidity: Liquidity) -> Decimal {
+        match liquidity {
+            Liquidity::Maker => notional * self.maker,
+            Liquidity::Taker => notional * self.taker,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Resting {
+    order: NewOrder,
+    price: Decimal,
+    remaining: Decimal,
+    // The quantity at the order's price level which has priority over it.
+    queue_ahead: Decimal,
+}
+
+#[derive(Debug, Clone)]
+enum Request {
+    Submit(OrderId, NewOrder),
+    Cancel(OrderId),
+}
+
+/// A simulated exchange which matches orders against a local copy of the order book.
+///
+/// The books are maintained from L2 updates and resting orders are filled by the
+/// trades reported alongside them, after the quantity queued ahead of them at their
+/// price level has traded. Requests reach the exchange `latency` after they were made,
+/// so orders match against the book as it is by then. Liquidity taken by an order
+/// stays consumed until the level is updated again.
+///
+/// The exchange is driven by the timestamps of the data it receives and never shorts
+/// or borrows: buys are limited by the cash and sells by the position which isn't
+/// committed to other orders.
+#[derive(Debug, Clone)]
+pub struct PaperExchange {
+    fees: Fees,
+    latency: Duration,
+    time: Option<DateTime<Utc>>,
+    books: HashMap<Symbol, OrderBook>,
+    // The quantity taken from a price level of a book since it was last updated.
+    consumed: HashMap<(Symbol, Side, Decimal), Decimal>,
+    cash: Decimal,
+    positions: HashMap<Symbol, Decimal>,
+    next_id: OrderId,
+    requests: VecDeque<(DateTime<Utc>, Request)>,
+    pending: HashMap<OrderId, NewOrder>,
+    open: BTreeMap<OrderId, Resting>,
+    fills: Vec<Fill>,
+    rejections: Vec<(OrderId, Reject)>,
+}
+
+impl PaperExchange {
+    pub fn new(cash: Decimal) -> Self {
+        Self {
+            fees: Fees::default(),
+            latency: Duration::zero(),
+            time: None,
+            books: HashMap::new(),
+            consumed: HashMap::new(),
+            cash,
+            positions: HashMap::new(),
+            next_id: 0,
+            requests: VecDeque::new(),
+            pending: HashMap::new(),
+            open: BTreeMap::new(),
+            fills: Vec::new(),
+            rejections: Vec::new(),
+        }
+    }
+
+    pub fn with_fees(mut self, fees: Fees) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    pub fn with_position(mut self, symbol: Symbol, quantity: Decimal) -> Self {
+        self.positions.insert(symbol, quantity);
+        self
+    }
+
+    /// The time of the latest data received, if any.
+    pub fn time(&self) -> Option<DateTime<Utc>> {
+        self.time
+    }
+
+    pub fn book(&self, symbol: Symbol) -> Option<&OrderBook> {
+        self.books.get(&symbol)
+    }
+
+    pub fn cash(&self) -> Decimal {
+        self.cash
+    }
+
+    pub fn position(&self, symbol: Symbol) -> Decimal {
+        self.positions.get(&symbol).copied().unwrap_or_default()
+    }
+
+    pub fn fills(&self) -> &[Fill] {
+        &self.fills
+    }
+
+    /// Removes and returns the orders rejected on arrival at the exchange.
+    pub fn take_rejections(&mut self) -> Vec<(OrderId, Reject)> {
+        std::mem::take(&mut self.rejections)
+    }
+
+    /// The orders which are resting on the book, with their remaining quantity.
+    pub fn open_orders(&self) -> impl Iterator<Item = (OrderId, &NewOrder, Decimal)> {
+        self.open
+            .iter()
+            .map(|(id, resting)| (*id, &resting.order, resting.remaining))
+    }
+
+    /// The number of orders which are on their way to the exchange or resting on the book.
+    pub fn open_order_count(&self) -> usize {
+        self.pending.len() + self.open.len()
+    }
+
+    /// The remaining quantity of the orders of `symbol` which are on their way to the
+    /// exchange or resting on the book, negative when they sell more than they buy.
+    pub fn open_quantity(&self, symbol: Symbol) -> Decimal {
+        self.pending
+            .values()
+            .map(|order| (order, order.quantity))
+            .chain(self.open.values().map(|r| (&r.order, r.remaining)))
+            .filter(|(order, _)| order.symbol == symbol)
+            .map(|(order, quantity)| order.side.sign() * quantity)
+            .sum()
+    }
+
+    /// Sends an order to the exchange. Orders which are invalid or not covered by the
+    /// uncommitted balance are rejected immediately.
+    pub fn submit(&mut self, order: NewOrder) -> Result<OrderId, Reject> {
+        if order.quantity <= Decimal::ZERO {
+            return Err(Reject::InvalidQuantity);
+        }
+        if let OrderKind::Limit(price, _) = order.kind {
+            if price <= Decimal::ZERO {
+                return Err(Reject::InvalidPrice);
+            }
+            let required = match order.side {
+                Side::Buy => self.cost(order.quantity, price),
+                Side::Sell => order.quantity,
+            };
+            if required > self.available(order.symbol, order.side) {
+                return Err(Reject::InsufficientFunds);
+            }
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.pending.insert(id, order.clone());
+        self.send(Request::Submit(id, order));
+        Ok(id)
+    }
+
+    /// Requests an order to be cancelled. Returns `false` if the order isn't open.
+    /// Until the request arrives the order may still be filled.
+    pub fn cancel(&mut self, id: OrderId) -> bool {
+        let open = self.pending.contains_key(&id) || self.open.contains_key(&id);
+        if open {
+            self.send(Request::Cancel(id));
+        }
+        open
+    }
+
+    /// Cancels all open orders immediately.
+    pub fn cancel_all(&mut self) {
+        self.requests.clear();
+        self.pending.clear();
+        self.open.clear();
+    }
+
+    /// Processes the requests which arrived at the exchange by `time`.
+    pub fn advance(&mut self, time: DateTime<Utc>) {
+        let time = self.time.map_or(time, |t| t.max(time));
+        self.time = Some(time);
+
+        while matches!(self.requests.front(), Some((arrival, _)) if *arrival <= time) {
+            let (arrival, request) = self.requests.pop_front().unwrap();
+            match request {
+                Request::Submit(id, order) => {
+                    if self.pending.remove(&id).is_some() {
+                        self.activate(id, order, arrival);
+                    }
+                }
+                Request::Cancel(id) => {
+                    self.pending.remove(&id);
+                    self.open.remove(&id);
+                }
+            }
+        }
+    }
+
+    /// Applies a change of a price level to the book of `symbol`.
+    pub fn update_book(
+        &mut self,
+        symbol: Symbol,
+        time: DateTime<Utc>,
+        change: BookOrder<OrderData>,
+    ) {
+        self.advance(time);
+
+        let (side, data) = match &change {
+            BookOrder::Bid(data) => (Side::Buy, data),
+            BookOrder::Ask(data) => (Side::Sell, data),
+        };
+        let (price, quantity) = (data.price.0, data.quantity.0);
+        self.consumed.remove(&(symbol, side, price));
+
+        // orders behind cancellations move up the queue
+        for resting in self.open.values_mut() {
+            if resting.order.symbol == symbol
+                && resting.order.side == side
+                && resting.price == price
+            {
+                resting.queue_ahead = resting.queue_ahead.min(quantity);
+            }
+        }
+
+        self.books.entry(symbol).or_default().update(change);
+    }
+
+    /// Matches a trade of `symbol` against the resting orders. `side` is the side of
+    /// the order which took liquidity.
+    pub fn trade(
+        &mut self,
+        symbol: Symbol,
+        time: DateTime<Utc>,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+    ) {
+        self.advance(time);
+
+        let maker_side = side.opposite();
+        let crossed = |limit: Decimal| match maker_side {
+            Side::Buy => limit >= price,
+            Side::Sell => limit <= price,
+        };
+
+        // orders at better prices trade first, orders at the same price in time priority
+        let mut ids = self
+            .open
+            .iter()
+            .filter(|(_, r)| r.order.symbol == symbol && r.order.side == maker_side)
+            .filter(|(_, r)| crossed(r.price))
+            .map(|(id, r)| (maker_side.sign() * r.price, *id))
+            .collect::<Vec<_>>();
+        ids.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        let mut left = quantity;
+        for (_, id) in ids {
+            if left <= Decimal::ZERO {
+                break;
+            }
+            let resting = self.open.get_mut(&id).unwrap();
+            if resting.price == price {
+                let ahead = resting.queue_ahead.min(left);
+                resting.queue_ahead -= ahead;
+                left -= ahead;
+            }
+
+            let filled = resting.remaining.min(left);
+            if filled <= Decimal::ZERO {
+                continue;
+            }
+            left -= filled;
+            resting.remaining -= filled;
+            let (order, limit) = (resting.order.clone(), resting.price);
+            if resting.remaining.is_zero() {
+                self.open.remove(&id);
+            }
+            self.fill(id, &order, time, filled, limit, Liquidity::Maker);
+        }
+    }
+
+    fn send(&mut self, request: Request) {
+        let arrival = self.time.unwrap_or_else(Utc::now) + self.latency;
+        self.requests.push_back((arrival, request));
+    }
+
+    fn available(&self, symbol: Symbol, side: Side) -> Decimal {
+        let committed = self
+            .pending
+            .values()
+            .map(|order| (order, order.quantity))
+            .chain(self.open.values().map(|r| (&r.order, r.remaining)))
+            .filter(|(order, _)| order.side == side)
+            .filter_map(|(order, quantity)| match (side, order.kind) {
+                (Side::Buy, OrderKind::Limit(price, _)) => Some(self.cost(quantity, price)),
+                (Side::Sell, _) if order.symbol == symbol => Some(quantity),
+                _ => None,
+            })
+            .sum::<Decimal>();
+
+        match side {
+            Side::Buy => self.cash - committed,
+            Side::Sell => self.position(symbol) - committed,
+        }
+    }
+
+    // The most buying `quantity` at `price` can cost, as the order may take liquidity.
+    fn cost(&self, quantity: Decimal, price: Decimal) -> Decimal {
+        quantity * price * (Decimal::ONE + self.fees.taker)
+    }
+
+    fn activate(&mut self, id: OrderId, order: NewOrder, time: DateTime<Utc>) {
+        let (limit, time_in_force) = match order.kind {
+            OrderKind::Market => (None, TimeInForce::ImmediateOrCancel),
+            OrderKind::Limit(price, time_in_force) => (Some(price), time_in_force),
+        };
+
+        if time_in_force == TimeInForce::MakerOrCancel && !self.liquidity(&order, limit).is_empty()
+        {
+            self.rejections.push((id, Reject::WouldTake));
+            return;
+        }
+
+        let mut remaining = order.quantity;
+        for (price, quantity) in self.liquidity(&order, limit) {
+            let mut filled = remaining.min(quantity);
+            if order.side == Side::Buy && limit.is_none() {
+                // market buys are limited by the cash not committed to limit buys,
+                // including the fee
+                let affordable =
+                    self.available(order.symbol, Side::Buy) / self.cost(Decimal::ONE, price);
+                filled = filled.min(affordable.max(Decimal::ZERO));
+            }
+            if order.side == Side::Sell {
+                filled = filled.min(self.available(order.symbol, Side::Sell));
+            }
+            if filled <= Decimal::ZERO {
+                break;
+            }
+
+            let opposite = order.side.opposite();
+            *self
+                .consumed
+                .entry((order.symbol, opposite, price))
+                .or_default() += filled;
+            remaining -= filled;
+            self.fill(id, &order, time, filled, price, Liquidity::Taker);
+        }
+
+        if let (Some(price), TimeInForce::GoodTillCancel | TimeInForce::MakerOrCancel) =
+            (limit, time_in_force)
+        {
+            if remaining > Decimal::ZERO {
+                let queue_ahead = self.level(order.symbol, order.side, price);
+                self.open.insert(
+                    id,
+                    Resting {
+                        order,
+                        price,
+                        remaining,
+                        queue_ahead,
+                    },
+                );
+            }
+        }
+    }
+
+    // The untaken quantity at a price level of a book.
+    fn level(&self, symbol: Symbol, side: Side, price: Decimal) -> Decimal {
+        let book = match self.books.get(&symbol) {
+            Some(book) => book,
+            None => return Decimal::ZERO,
+        };
+        let mut levels = match side {
+            Side::Buy => Box::new(book.iter_bids()) as Box<dyn Iterator<Item = &OrderData>>,
+            Side::Sell => Box::new(book.iter_asks()),
+        };
+        let quantity = levels
+            .find(|level| level.price.0 == price)
+            .map_or(Decimal::ZERO, |level| level.quantity.0);
+        let consumed = self
+            .consumed
+            .get(&(symbol, side, price))
+            .copied()
+            .unwrap_or_default();
+        (quantity - consumed).max(Decimal::ZERO)
+    }
+
+    // The untaken opposite levels `order` can trade against, best first.
+    fn liquidity(&self, order: &NewOrder, limit: Option<Decimal>) -> Vec<(Decimal, Decimal)> {
+        let book = match self.books.get(&order.symbol) {
+            Some(book) => book,
+            None => return Vec::new(),
+        };
+        let opposite = order.side.opposite();
+        let levels = match order.side {
+            Side::Buy => Box::new(book.iter_asks()) as Box<dyn Iterator<Item = &OrderData>>,
+            Side::Sell => Box::new(book.iter_bids()),
+        };
+
+        levels
+            .map(|level| level.price.0)
+            .take_while(|price| match (order.side, limit) {
+                (_, None) => true,
+                (Side::Buy, Some(limit)) => *price <= limit,
+                (Side::Sell, Some(limit)) => *price >= limit,
+            })
+            .map(|price| (price, self.level(order.symbol, opposite, price)))
+            .filter(|(_, quantity)| *quantity > Decimal::ZERO)
+            .collect()
+    }
+
+    fn fill(
+        &mut self,
+        order_id: OrderId,
+        order: &NewOrder,
+        time: DateTime<Utc>,
+        quantity: Decimal,
+        price: Decimal,
+        liquidity: Liquidity,
+    ) {
+        let fee = self.fees.fee(quantity * price, liquidity);
+        self.cash -= order.side.sign() * quantity * price + fee;
+        *self.positions.entry(order.symbol).or_default() += order.side.sign() * quantity;
+        self.fills.push(Fill {
+            order_id,
+            time,
+            symbol: order.symbol,
+            side: order.side,
+            quantity,
+            price,
+            fee,
+            liquidity,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    const BTC: Symbol = Symbol::BTCUSD;
+
+    fn at(ms: i64) -> DateTime<Utc> {
+        Utc.timestamp_millis_opt(ms).unwrap()
+    }
+
+    // asks at 101 (1) and 102 (2), bids at 99 (1) and 98 (2)
+    fn exchange() -> PaperExchange {
+        let mut exchange = PaperExchange::new(dec!(1000))
+            .with_position(BTC, dec!(5))
+            .with_fees(Fees {
+                maker: dec!(0.001),
+                taker: dec!(0.002),
+            });
+        for change in [
+            BookOrder::Ask(OrderData::new(dec!(101), dec!(1))),
+            BookOrder::Ask(OrderData::new(dec!(102), dec!(2))),
+            BookOrder::Bid(OrderData::new(dec!(99), dec!(1))),
+            BookOrder::Bid(OrderData::new(dec!(98), dec!(2))),
+        ] {
+            exchange.update_book(BTC, at(0), change);
+        }
+        exchange
+    }
+
+    fn executions(exchange: &PaperExchange) -> Vec<(Decimal, Decimal, Liquidity)> {
+        exchange
+            .fills()
+            .iter()
+            .map(|fill| (fill.quantity, fill.price, fill.liquidity))
+            .collect()
+    }
+
+    #[test]
+    fn test_market_order_walks_book() {
+        let mut exchange = exchange();
+        exchange
+            .submit(NewOrder::market(BTC, Side::Buy, dec!(2)))
+            .unwrap();
+        exchange.advance(at(0));
+
+        assert_eq!(
+            vec![
+                (dec!(1), dec!(101), Liquidity::Taker),
+                (dec!(1), dec!(102), Liquidity::Taker)
+            ],
+            executions(&exchange)
+        );
+        assert_eq!(dec!(7), exchange.position(BTC));
+        assert_eq!(dec!(1000) - dec!(203) - dec!(0.406), exchange.cash());
+
+        // the taken liquidity isn't available again until the level is updated
+        exchange
+            .submit(NewOrder::market(BTC, Side::Buy, dec!(2)))
+            .unwrap();
+        exchange.advance(at(0));
+        assert_eq!((dec!(1), dec!(102)), {
+            let fill = &exchange.fills()[2];
+            (fill.quantity, fill.price)
+        });
+        assert_eq!(3, exchange.fills().len());
+    }
+
+    #[test]
+    fn test_market_buy_leaves_committed_cash() {
+        let mut exchange = exchange().with_fees(Fees {
+            maker: dec!(0.001),
+            taker: dec!(0.25),
+        });
+        // commits 699 and its taker fee of 174.75, leaving enough for 1 at 101
+        exchange
+            .submit(NewOrder::limit(BTC, Side::Buy, dec!(10), dec!(69.9)))
+            .unwrap();
+        exchange
+            .submit(NewOrder::market(BTC, Side::Buy, dec!(2)))
+            .unwrap();
+        exchange.advance(at(0));
+
+        assert_eq!(
+            vec![(dec!(1), dec!(101), Liquidity::Taker)],
+            executions(&exchange)
+        );
+        assert_eq!(dec!(1000) - dec!(101) - dec!(25.25), exchange.cash());
+        assert_eq!(dec!(10), exchange.open_quantity(BTC));
+        assert_eq!(Decimal::ZERO, exchange.available(BTC, Side::Buy));
+    }
+
+    #[test]
+    fn test_limit_order_time_in_force() {
+        let mut exchange = exchange();
+
+        let ioc = NewOrder::limit_with(
+            BTC,
+            Side::Buy,
+            dec!(2),
+            dec!(101),
+            TimeInForce::ImmediateOrCancel,
+        );
+        exchange.submit(ioc).unwrap();
+        let maker = NewOrder::limit_with(
+            BTC,
+            Side::Sell,
+            dec!(1),
+            dec!(99),
+            TimeInForce::MakerOrCancel,
+        );
+        let rejected = exchange.submit(maker).unwrap();
+        let gtc = NewOrder::limit(BTC, Side::Buy, dec!(3), dec!(102));
+        let resting = exchange.submit(gtc).unwrap();
+        exchange.advance(at(0));
+
+        assert_eq!(
+            vec![
+                (dec!(1), dec!(101), Liquidity::Taker),
+                (dec!(2), dec!(102), Liquidity::Taker)
+            ],
+            executions(&exchange)
+        );
+        assert_eq!(
+            vec![(rejected, Reject::WouldTake)],
+            exchange.take_rejections()
+        );
+        // the remainder of the good-till-cancel order rests on the book
+        let open = exchange.open_orders().collect::<Vec<_>>();
+        assert_eq!(1, open.len());
+        assert_eq!((resting, dec!(1)), (open[0].0, open[0].2));
+    }
+
+    #[test]
+    fn test_queue_position() {
+        let mut exchange = exchange();
+        let id = exchange
+            .submit(NewOrder::limit(BTC, Side::Buy, dec!(1), dec!(99)))
+            .unwrap();
+        exchange.advance(at(0));
+        assert!(exchange.fills().is_empty());
+
+        // the quantity already at 99 trades first
+        exchange.trade(BTC, at(1), Side::Sell, dec!(99), dec!(0.5));
+        assert!(exchange.fills().is_empty());
+        exchange.update_book(
+            BTC,
+            at(1),
+            BookOrder::Bid(OrderData::new(dec!(99), dec!(0.5))),
+        );
+
+        // a cancellation ahead moves the order up
+        exchange.update_book(
+            BTC,
+            at(2),
+            BookOrder::Bid(OrderData::new(dec!(99), dec!(0.2))),
+        );
+        exchange.trade(BTC, at(3), Side::Sell, dec!(99), dec!(0.6));
+        assert_eq!(
+            vec![(dec!(0.4), dec!(99), Liquidity::Maker)],
+            executions(&exchange)
+        );
+
+        // trades through the price fill the order completely
+        exchange.trade(BTC, at(4), Side::Sell, dec!(98), dec!(5));
+        assert_eq!(dec!(0.6), exchange.fills()[1].quantity);
+        assert_eq!(id, exchange.fills()[1].order_id);
+        assert_eq!(0, exchange.open_orders().count());
+        assert_eq!(dec!(6), exchange.position(BTC));
+        assert_eq!(dec!(1000) - dec!(99) - dec!(0.099), exchange.cash());
+    }
+
+    #[test]
+    fn test_latency() {
+        let mut exchange = exchange().with_latency(Duration::milliseconds(10));
+        exchange
+            .submit(NewOrder::market(BTC, Side::Sell, dec!(1)))
+            .unwrap();
+
+        // the book moves before the order arrives
+        exchange.update_book(
+            BTC,
+            at(5),
+            BookOrder::Bid(OrderData::new(dec!(99), dec!(0))),
+        );
+        assert!(exchange.fills().is_empty());
+        exchange.advance(at(10));
+        assert_eq!(
+            vec![(dec!(1), dec!(98), Liquidity::Taker)],
+            executions(&exchange)
+        );
+
+        // an order can fill before its cancellation arrives
+        let id = exchange
+            .submit(NewOrder::limit(BTC, Side::Sell, dec!(1), dec!(100)))
+            .unwrap();
+        exchange.advance(at(20));
+        assert!(exchange.cancel(id));
+        exchange.trade(BTC, at(25), Side::Buy, dec!(100), dec!(1));
+        exchange.advance(at(30));
+        assert_eq!(2, exchange.fills().len());
+        assert!(!exchange.cancel(id));
+    }
+
+    #[test]
+    fn test_rejections() {
+        let mut exchange = exchange();
+        // the taker fee isn't covered
+        assert_eq!(
+            Err(Reject::InsufficientFunds),
+            exchange.submit(NewOrder::limit(BTC, Side::Buy, dec!(10), dec!(100)))
+        );
+        assert_eq!(
+            Err(Reject::InvalidQuantity),
+            exchange.submit(NewOrder::market(BTC, Side::Buy, dec!(0)))
+        );
+        assert_eq!(
+            Err(Reject::InvalidPrice),
+            exchange.submit(NewOrder::limit(BTC, Side::Buy, dec!(1), dec!(-1)))
+        );
+
+        exchange
+            .submit(NewOrder::limit(BTC, Side::Buy, dec!(9), dec!(100)))
+            .unwrap();
+        assert_eq!(dec!(9), exchange.open_quantity(BTC));
+        assert_eq!(
+            Err(Reject::InsufficientFunds),
+            exchange.submit(NewOrder::limit(BTC, Side::Buy, dec!(2), dec!(51)))
+        );
+        assert_eq!(
+            Err(Reject::InsufficientFunds),
+            exchange.submit(NewOrder::limit(BTC, Side::Sell, dec!(6), dec!(200)))
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use chrono::{DateTime, Utc};
+use gemini::common::Candle;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+/// The simultaneous returns of two instruments over the same interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PairedReturn {
+    // The close time of the interval.
+    pub time: DateTime<Utc>,
+    pub x: Decimal,
+    pub y: Decimal,
+}
+
+fn closes(candles: &[Candle]) -> BTreeMap<DateTime<Utc>, Decimal> {
+    candles
+        .iter()
+        .map(|candle| (candle.time, candle.close))
+        .collect()
+}
+
+fn simple_return(prev: Decimal, close: Decimal) -> Option<Decimal> {
+    (!prev.is_zero()).then(|| (close - prev) / prev)
+}
+
+/// Close-to-close returns of a candle series in chronological order.
+///
+/// The candles may be given in any order, e.g. newest first as returned by Gemini.
+pub fn returns(candles: &[Candle]) -> Vec<(DateTime<Utc>, Decimal)> {
+    let closes = closes(candles);
+    closes
+        .iter()
+        .zip(closes.iter().skip(1))
+        .filter_map(|((_, prev), (time, close))| Some((*time, simple_return(*prev, *close)?)))
+        .collect()
+}
+
+/// Aligns the candle series of two instruments by timestamp and computes the
+/// close-to-close returns of both over the timestamps present in both series.
+pub fn paired_returns(x: &[Candle], y: &[Candle]) -> Vec<PairedReturn> {
+    let y = closes(y);
+    let aligned = closes(x)
+        .into_iter()
+        .filter_map(|(time, x)| Some((time, x, *y.get(&time)?)))
+        .collect::<Vec<_>>();
+
+    aligned
+        .windows(2)
+        .filter_map(|pair| {
+            let ((_, prev_x, prev_y), (time, x, y)) = (pair[0], pair[1]);
+            Some(PairedReturn {
+                time,
+                x: simple_return(prev_x, x)?,
+                y: simple_return(prev_y, y)?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::stats::pair::PairStats;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn candle(minute: i64, close: Decimal) -> Candle {
+        Candle {
+            time: Utc.timestamp_opt(minute * 60, 0).unwrap(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: dec!(1),
+        }
+    }
+
+    #[test]
+    fn test_returns() {
+        let candles = [
+            candle(2, dec!(99)),
+            candle(1, dec!(110)),
+            candle(0, dec!(100)),
+        ];
+
+        let returns = returns(&candles);
+        assert_eq!(
+            returns,
+            vec![
+                (Utc.timestamp_opt(60, 0).unwrap(), dec!(0.1)),
+                (Utc.timestamp_opt(120, 0).unwrap(), dec!(-0.1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_paired_returns() {
+        let btc = [
+            candle(0, dec!(100)),
+            candle(1, dec!(110)),
+            candle(2, dec!(99)),
+            candle(3, dec!(108.9)),
+        ];
+        // missing the candle at minute 2
+        let eth = [candle(3, dec!(12)), candle(1, dec!(10)), candle(0, dec!(8))];
+
+        let returns = paired_returns(&btc, &eth);
+        assert_eq!(2, returns.len());
+        assert_eq!(dec!(0.1), returns[0].x);
+        assert_eq!(dec!(0.25), returns[0].y);
+        assert_eq!(dec!(-0.01), returns[1].x);
+        assert_eq!(dec!(0.2), returns[1].y);
+
+        let stats = returns
+            .iter()
+            .map(|r| (r.x, r.y))
+            .collect::<PairStats<Decimal>>();
+        assert_eq!(2, stats.size());
+        assert_eq!(
+            Some((dec!(5) / dec!(11)).round_dp(20)),
+            stats.beta().map(|b| b.round_dp(20))
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use super::{from_usize, StatsType};
+
+/// Exponentially weighted moving mean and variance.
+///
+/// Each new sample is weighted by `alpha` and the weight of every older sample
+/// decays by a factor of `1 - alpha`.
+#[derive(Debug, Clone)]
+pub struct Ewma<T> {
+    alpha: T,
+    size: usize,
+    mean: T,
+    variance: T,
+}
+
+impl<T> Ewma<T>
+where
+    T: StatsType,
+{
+    /// Constructs a new `Ewma` with the smoothing factor `alpha`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `alpha` is not within `(0, 1]`.
+    #[must_use]
+    pub fn new(alpha: T) -> Self {
+        if alpha <= T::zero() || alpha > from_usize(1) {
+            panic!("Ewma alpha must be within (0, 1]");
+        }
+        Self {
+            alpha,
+            size: 0,
+            mean: T::zero(),
+            variance: T::zero(),
+        }
+    }
+
+    /// Constructs a new `Ewma` whose smoothing factor corresponds to an `span` sample
+    /// moving average, i.e. `alpha = 2 / (span + 1)`.
+    #[must_use]
+    pub fn with_span(span: usize) -> Self {
+        Self::new(from_usize::<T>(2) / from_usize(span + 1))
+    }
+
+    pub fn add(&mut self, sample: T) {
+        if self.size == 0 {
+            self.mean = sample;
+        } else {
+            let diff = sample - self.mean;
+            let incr = self.alpha * diff;
+            self.mean = self.mean + incr;
+            self.variance = (from_usize::<T>(1) - self.alpha) * (self.variance + diff * incr);
+        }
+        self.size += 1;
+    }
+
+    #[must_use]
+    pub fn alpha(&self) -> T {
+        self.alpha
+    }
+
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[must_use]
+    pub fn mean(&self) -> Option<T> {
+        (self.size > 0).then_some(self.mean)
+    }
+
+    #[must_use]
+    pub fn variance(&self) -> Option<T> {
+        (self.size > 0).then_some(self.variance)
+    }
+
+    #[must_use]
+    pub fn stddev(&self) -> Option<T> {
+        self.variance()?.sqrt()
+    }
+}
+
+impl<U, T> Extend<U> for Ewma<T>
+where
+    U: Into<T>,
+    T: StatsType,
+{
+    fn extend<I: IntoIterator<Item = U>>(&mut self, iter: I) {
+        for sample in iter {
+            self.add(sample.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_ewma() {
+        let mut ewma = Ewma::<Decimal>::new(dec!(0.5));
+        assert_eq!(None, ewma.mean());
+
+        ewma.extend([dec!(1), dec!(3), dec!(5)].iter().copied());
+
+        assert_eq!(3, ewma.size());
+        assert_eq!(Some(dec!(3.5)), ewma.mean());
+        // var1 = 0.5 * (0 + 2 * 1) = 1, var2 = 0.5 * (1 + 3 * 1.5) = 2.75
+        assert_eq!(Some(dec!(2.75)), ewma.variance());
+    }
+
+    #[test]
+    fn test_ewma_span() {
+        let ewma = Ewma::<f64>::with_span(3);
+        assert_eq!(0.5, ewma.alpha());
+    }
+
+    #[test]
+    #[should_panic = "Ewma alpha must be within (0, 1]"]
+    fn test_ewma_invalid_alpha() {
+        let _ = Ewma::<f64>::new(0.0);
+    }
+}
@@ -0,0 +1,262 @@
+pub mod ewma;
+pub mod moments;
+pub mod pair;
+pub mod rolling;
+
+use num_traits::{FromPrimitive, Zero};
+use rust_decimal::{Decimal, MathematicalOps};
+use std::iter::FromIterator;
+
+/// The population standard deviation of `samples`, or `None` if there are none.
+pub fn stddev<I>(samples: I) -> Option<I::Item>
+where
+    I: Iterator,
+    I::Item: StatsType,
+{
+    samples.collect::<Stats<I::Item>>().stddev()
+}
+
+/// The population variance of `samples`, or `None` if there are none.
+pub fn variance<I>(samples: I) -> Option<I::Item>
+where
+    I: Iterator,
+    I::Item: StatsType,
+{
+    samples.collect::<Stats<I::Item>>().variance()
+}
+
+/// The mean of `samples`, or `None` if there are none.
+pub fn mean<I>(samples: I) -> Option<I::Item>
+where
+    I: Iterator,
+    I::Item: StatsType,
+{
+    samples.collect::<Stats<I::Item>>().mean()
+}
+
+/// Cumulative mean and variance of a sample set, computed with Welford's algorithm.
+///
+/// Statistics which are undefined for the current number of samples are `None`.
+#[derive(Debug, Clone, Default)]
+pub struct Stats<T> {
+    size: usize,
+    mean: T,
+    // The sum of squared differences from the mean.
+    m2: T,
+}
+
+pub trait Sqrt: Sized {
+    fn sqrt(&self) -> Option<Self>;
+}
+
+pub trait StatsType:
+    Sqrt
+    + std::ops::Mul<Self, Output = Self>
+    + FromPrimitive
+    + Copy
+    + PartialOrd
+    + std::ops::Add<Self, Output = Self>
+    + std::ops::Sub<Self, Output = Self>
+    + std::ops::Div<Self, Output = Self>
+    + Zero
+{
+}
+
+impl<T> StatsType for T where
+    T: Sqrt
+        + std::ops::Mul<T, Output = T>
+        + FromPrimitive
+        + Copy
+        + PartialOrd
+        + std::ops::Add<T, Output = T>
+        + std::ops::Sub<T, Output = T>
+        + std::ops::Div<T, Output = T>
+        + Zero
+{
+}
+
+// Converts a sample count into `T`. Every `StatsType` can represent any `usize`.
+pub(crate) fn from_usize<T: StatsType>(n: usize) -> T {
+    T::from_usize(n).expect("sample count must be representable")
+}
+
+impl<T> Stats<T>
+where
+    T: StatsType,
+{
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            size: 0,
+            mean: T::zero(),
+            m2: T::zero(),
+        }
+    }
+
+    #[must_use]
+    pub fn mean(&self) -> Option<T> {
+        (self.size > 0).then_some(self.mean)
+    }
+
+    /// The population variance. `None` if there are no samples.
+    #[must_use]
+    pub fn variance(&self) -> Option<T> {
+        (self.size > 0).then(|| self.m2 / from_usize(self.size))
+    }
+
+    /// The (Bessel corrected) sample variance. `None` if there are less than two samples.
+    #[must_use]
+    pub fn sample_variance(&self) -> Option<T> {
+        (self.size > 1).then(|| self.m2 / from_usize(self.size - 1))
+    }
+
+    /// The population standard deviation. `None` if there are no samples.
+    #[must_use]
+    pub fn stddev(&self) -> Option<T> {
+        self.variance()?.sqrt()
+    }
+
+    /// The sample standard deviation. `None` if there are less than two samples.
+    #[must_use]
+    pub fn sample_stddev(&self) -> Option<T> {
+        self.sample_variance()?.sqrt()
+    }
+
+    pub fn add(&mut self, sample: T) {
+        self.size += 1;
+
+        let delta = sample - self.mean;
+        self.mean = self.mean + delta / from_usize(self.size);
+        self.m2 = self.m2 + delta * (sample - self.mean);
+    }
+
+    /// Combines the samples of `other` into `self`, as if every sample added to `other`
+    /// had been added to `self` instead.
+    pub fn merge(&mut self, other: &Self) {
+        if other.size == 0 {
+            return;
+        }
+        if self.size == 0 {
+            *self = other.clone();
+            return;
+        }
+
+        let (na, nb) = (from_usize::<T>(self.size), from_usize::<T>(other.size));
+        self.size += other.size;
+        let n = from_usize::<T>(self.size);
+
+        let delta = other.mean - self.mean;
+        self.mean = self.mean + delta * nb / n;
+        self.m2 = self.m2 + other.m2 + delta * delta * na * nb / n;
+    }
+
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+}
+
+impl<U, T> Extend<U> for Stats<T>
+where
+    U: Into<T>,
+    T: StatsType,
+{
+    fn extend<I: IntoIterator<Item = U>>(&mut self, iter: I) {
+        for sample in iter {
+            self.add(sample.into())
+        }
+    }
+}
+
+impl<U, T> FromIterator<U> for Stats<T>
+where
+    U: Into<T>,
+    T: StatsType,
+{
+    fn from_iter<Iter: IntoIterator<Item = U>>(iter: Iter) -> Self {
+        let mut stats = Stats::new();
+        stats.extend(iter);
+        stats
+    }
+}
+
+// impls
+
+impl Sqrt for Decimal {
+    fn sqrt(&self) -> Option<Self> {
+        <Decimal as MathematicalOps>::sqrt(self)
+    }
+}
+
+impl Sqrt for f64 {
+    fn sqrt(&self) -> Option<Self> {
+        (*self >= 0.0).then(|| f64::sqrt(*self))
+    }
+}
+
+impl Sqrt for f32 {
+    fn sqrt(&self) -> Option<Self> {
+        (*self >= 0.0).then(|| f32::sqrt(*self))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_stats() {
+        let stats = [2, 4, 4, 4, 5, 5, 7, 9]
+            .iter()
+            .map(|x| Decimal::from(*x))
+            .collect::<Stats<Decimal>>();
+
+        assert_eq!(8, stats.size());
+        assert_eq!(Some(dec!(5)), stats.mean());
+        assert_eq!(Some(dec!(4)), stats.variance());
+        assert_eq!(Some(dec!(2)), stats.stddev());
+        assert_eq!(Some(dec!(32) / dec!(7)), stats.sample_variance());
+    }
+
+    #[test]
+    fn test_stats_empty() {
+        let stats = Stats::<f64>::new();
+        assert!(stats.is_empty());
+        assert_eq!(None, stats.mean());
+        assert_eq!(None, stats.variance());
+        assert_eq!(None, stats.stddev());
+
+        assert_eq!(None, mean(std::iter::empty::<Decimal>()));
+        assert_eq!(None, variance(std::iter::empty::<f32>()));
+        assert_eq!(None, stddev(std::iter::empty::<f64>()));
+
+        let single = std::iter::once(dec!(0)).collect::<Stats<Decimal>>();
+        assert_eq!(Some(dec!(0)), single.mean());
+        assert_eq!(Some(dec!(0)), single.variance());
+        assert_eq!(None, single.sample_variance());
+    }
+
+    #[test]
+    fn test_stats_merge() {
+        let samples = [1.5, 2.25, 8.0, 3.0, 4.75, 6.5, 0.25];
+        let all = samples.iter().copied().collect::<Stats<f64>>();
+
+        let mut merged = samples[..3].iter().copied().collect::<Stats<f64>>();
+        merged.merge(&samples[3..].iter().copied().collect());
+        merged.merge(&Stats::new());
+
+        assert_eq!(all.size(), merged.size());
+        assert!((all.mean().unwrap() - merged.mean().unwrap()).abs() < 1e-12);
+        assert!((all.variance().unwrap() - merged.variance().unwrap()).abs() < 1e-12);
+
+        let mut empty = Stats::new();
+        empty.merge(&all);
+        assert_eq!(all.mean(), empty.mean());
+    }
+}
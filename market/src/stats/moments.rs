@@ -0,0 +1,236 @@
+use super::{from_usize, StatsType};
+
+/// Running central moments of a sample set, up to the fourth order.
+///
+/// Unlike `Stats`, samples can also be removed again, which allows the moments
+/// of a sliding window to be maintained in constant time.
+#[derive(Debug, Clone, Default)]
+pub struct Moments<T> {
+    size: usize,
+    mean: T,
+    m2: T,
+    m3: T,
+    m4: T,
+}
+
+impl<T> Moments<T>
+where
+    T: StatsType,
+{
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            size: 0,
+            mean: T::zero(),
+            m2: T::zero(),
+            m3: T::zero(),
+            m4: T::zero(),
+        }
+    }
+
+    pub fn add(&mut self, sample: T) {
+        let (two, three, four, six) = constants::<T>();
+
+        let n1 = from_usize::<T>(self.size);
+        self.size += 1;
+        let n = from_usize::<T>(self.size);
+
+        let delta = sample - self.mean;
+        let delta_n = delta / n;
+        let delta_n2 = delta_n * delta_n;
+        let term1 = delta * delta_n * n1;
+
+        self.mean = self.mean + delta_n;
+        self.m4 =
+            self.m4 + term1 * delta_n2 * (n * n - three * n + three) + six * delta_n2 * self.m2
+                - four * delta_n * self.m3;
+        self.m3 = self.m3 + term1 * delta_n * (n - two) - three * delta_n * self.m2;
+        self.m2 = self.m2 + term1;
+    }
+
+    /// Removes a sample that was previously added.
+    ///
+    /// This is the exact inverse of `add`, so the sample must be one that is
+    /// currently accounted for.
+    pub fn remove(&mut self, sample: T) {
+        if self.size <= 1 {
+            *self = Self::new();
+            return;
+        }
+
+        let (two, three, four, six) = constants::<T>();
+
+        let n = from_usize::<T>(self.size);
+        self.size -= 1;
+        let n1 = from_usize::<T>(self.size);
+
+        let mean = (self.mean * n - sample) / n1;
+        let delta = sample - mean;
+        let delta_n = delta / n;
+        let delta_n2 = delta_n * delta_n;
+        let term1 = delta * delta_n * n1;
+
+        self.mean = mean;
+        self.m2 = self.m2 - term1;
+        self.m3 = self.m3 - term1 * delta_n * (n - two) + three * delta_n * self.m2;
+        self.m4 =
+            self.m4 - term1 * delta_n2 * (n * n - three * n + three) - six * delta_n2 * self.m2
+                + four * delta_n * self.m3;
+    }
+
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    #[must_use]
+    pub fn mean(&self) -> Option<T> {
+        (self.size > 0).then_some(self.mean)
+    }
+
+    /// The population variance. `None` if there are no samples.
+    #[must_use]
+    pub fn variance(&self) -> Option<T> {
+        (self.size > 0).then(|| self.m2() / from_usize(self.size))
+    }
+
+    /// The sample variance. `None` if there are less than two samples.
+    #[must_use]
+    pub fn sample_variance(&self) -> Option<T> {
+        (self.size > 1).then(|| self.m2() / from_usize(self.size - 1))
+    }
+
+    /// The population standard deviation. `None` if there are no samples.
+    #[must_use]
+    pub fn stddev(&self) -> Option<T> {
+        self.variance()?.sqrt()
+    }
+
+    /// The sample standard deviation. `None` if there are less than two samples.
+    #[must_use]
+    pub fn sample_stddev(&self) -> Option<T> {
+        self.sample_variance()?.sqrt()
+    }
+
+    /// The population skewness. `None` if the samples have no spread.
+    #[must_use]
+    pub fn skewness(&self) -> Option<T> {
+        let m2 = self.spread()?;
+        let n = from_usize::<T>(self.size);
+        Some(n.sqrt()? * self.m3 / (m2 * m2.sqrt()?))
+    }
+
+    /// The population excess kurtosis. `None` if the samples have no spread.
+    #[must_use]
+    pub fn kurtosis(&self) -> Option<T> {
+        let m2 = self.spread()?;
+        let (_, three, _, _) = constants::<T>();
+        Some(from_usize::<T>(self.size) * self.m4 / (m2 * m2) - three)
+    }
+
+    fn m2(&self) -> T {
+        // removal can leave a tiny negative remainder due to rounding
+        if self.m2 < T::zero() {
+            T::zero()
+        } else {
+            self.m2
+        }
+    }
+
+    fn spread(&self) -> Option<T> {
+        let m2 = self.m2();
+        (m2 > T::zero()).then_some(m2)
+    }
+}
+
+fn constants<T: StatsType>() -> (T, T, T, T) {
+    (from_usize(2), from_usize(3), from_usize(4), from_usize(6))
+}
+
+impl<U, T> Extend<U> for Moments<T>
+where
+    U: Into<T>,
+    T: StatsType,
+{
+    fn extend<I: IntoIterator<Item = U>>(&mut self, iter: I) {
+        for sample in iter {
+            self.add(sample.into())
+        }
+    }
+}
+
+impl<U, T> std::iter::FromIterator<U> for Moments<T>
+where
+    U: Into<T>,
+    T: StatsType,
+{
+    fn from_iter<Iter: IntoIterator<Item = U>>(iter: Iter) -> Self {
+        let mut moments = Moments::new();
+        moments.extend(iter);
+        moments
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_moments() {
+        let moments = [2, 4, 4, 4, 5, 5, 7, 9]
+            .iter()
+            .map(|x| Decimal::from(*x))
+            .collect::<Moments<Decimal>>();
+
+        assert_eq!(8, moments.size());
+        assert_eq!(Some(dec!(5)), moments.mean());
+        assert_eq!(Some(dec!(4)), moments.variance());
+        assert_eq!(Some(dec!(2)), moments.stddev());
+        assert_eq!(
+            Some(dec!(0.65625)),
+            moments.skewness().map(|s| s.round_dp(20))
+        );
+        assert_eq!(
+            Some(dec!(-0.21875)),
+            moments.kurtosis().map(|k| k.round_dp(20))
+        );
+    }
+
+    #[test]
+    fn test_moments_remove() {
+        let mut moments = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]
+            .iter()
+            .copied()
+            .collect::<Moments<f64>>();
+        moments.remove(2.0);
+        moments.remove(4.0);
+
+        assert_eq!(6, moments.size());
+        assert!((moments.mean().unwrap() - 5.666666666666667).abs() < 1e-12);
+        assert!((moments.variance().unwrap() - 3.222222222222222).abs() < 1e-12);
+        assert!((moments.skewness().unwrap() - 0.8516372406050994).abs() < 1e-12);
+        assert!((moments.kurtosis().unwrap() + 0.7134363852556485).abs() < 1e-12);
+
+        for x in [4.0, 5.0, 5.0, 7.0, 9.0, 4.0].iter() {
+            moments.remove(*x);
+        }
+        assert!(moments.is_empty());
+        assert_eq!(None, moments.mean());
+    }
+
+    #[test]
+    fn test_moments_no_spread() {
+        let moments = [3.0, 3.0, 3.0].iter().copied().collect::<Moments<f64>>();
+
+        assert_eq!(Some(0.0), moments.variance());
+        assert_eq!(None, moments.skewness());
+        assert_eq!(None, moments.kurtosis());
+    }
+}
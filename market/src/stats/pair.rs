@@ -0,0 +1,324 @@
+use super::{from_usize, StatsType};
+use std::{collections::VecDeque, num::NonZeroUsize};
+
+/// Cumulative bivariate statistics of paired samples `(x, y)`.
+///
+/// The regression statistics describe `y` as a linear function of `x`, so when `x`
+/// holds the returns of a hedge instrument, `beta` is the hedge ratio for `y`.
+#[derive(Debug, Clone, Default)]
+pub struct PairStats<T> {
+    size: usize,
+    mean_x: T,
+    mean_y: T,
+    // Sums of squared differences from the means and the co-moment.
+    m2_x: T,
+    m2_y: T,
+    c: T,
+}
+
+impl<T> PairStats<T>
+where
+    T: StatsType,
+{
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            size: 0,
+            mean_x: T::zero(),
+            mean_y: T::zero(),
+            m2_x: T::zero(),
+            m2_y: T::zero(),
+            c: T::zero(),
+        }
+    }
+
+    pub fn add(&mut self, x: T, y: T) {
+        self.size += 1;
+        let n = from_usize::<T>(self.size);
+
+        let dx = x - self.mean_x;
+        let dy = y - self.mean_y;
+        self.mean_x = self.mean_x + dx / n;
+        self.mean_y = self.mean_y + dy / n;
+
+        self.m2_x = self.m2_x + dx * (x - self.mean_x);
+        self.m2_y = self.m2_y + dy * (y - self.mean_y);
+        self.c = self.c + dx * (y - self.mean_y);
+    }
+
+    /// Removes a pair that was previously added. This is the exact inverse of `add`.
+    pub fn remove(&mut self, x: T, y: T) {
+        if self.size <= 1 {
+            *self = Self::new();
+            return;
+        }
+
+        let n = from_usize::<T>(self.size);
+        self.size -= 1;
+        let n1 = from_usize::<T>(self.size);
+
+        let mean_x = (self.mean_x * n - x) / n1;
+        let mean_y = (self.mean_y * n - y) / n1;
+
+        self.m2_x = self.m2_x - (x - mean_x) * (x - self.mean_x);
+        self.m2_y = self.m2_y - (y - mean_y) * (y - self.mean_y);
+        self.c = self.c - (x - mean_x) * (y - self.mean_y);
+
+        self.mean_x = mean_x;
+        self.mean_y = mean_y;
+    }
+
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    #[must_use]
+    pub fn mean_x(&self) -> Option<T> {
+        (self.size > 0).then_some(self.mean_x)
+    }
+
+    #[must_use]
+    pub fn mean_y(&self) -> Option<T> {
+        (self.size > 0).then_some(self.mean_y)
+    }
+
+    /// The population covariance. `None` if there are no samples.
+    #[must_use]
+    pub fn covariance(&self) -> Option<T> {
+        (self.size > 0).then(|| self.c / from_usize(self.size))
+    }
+
+    /// The sample covariance. `None` if there are less than two samples.
+    #[must_use]
+    pub fn sample_covariance(&self) -> Option<T> {
+        (self.size > 1).then(|| self.c / from_usize(self.size - 1))
+    }
+
+    /// The Pearson correlation coefficient. `None` if either variable has no spread.
+    #[must_use]
+    pub fn correlation(&self) -> Option<T> {
+        let (m2_x, m2_y) = (positive(self.m2_x)?, positive(self.m2_y)?);
+        Some(self.c / (m2_x.sqrt()? * m2_y.sqrt()?))
+    }
+
+    /// The slope of the least squares regression of `y` on `x`.
+    /// `None` if `x` has no spread.
+    #[must_use]
+    pub fn beta(&self) -> Option<T> {
+        Some(self.c / positive(self.m2_x)?)
+    }
+
+    /// The intercept of the least squares regression of `y` on `x`.
+    /// `None` if `x` has no spread.
+    #[must_use]
+    pub fn alpha(&self) -> Option<T> {
+        Some(self.mean_y - self.beta()? * self.mean_x)
+    }
+
+    /// The population standard deviation of the residuals of the least squares
+    /// regression of `y` on `x`. `None` if `x` has no spread.
+    #[must_use]
+    pub fn residual_stddev(&self) -> Option<T> {
+        let m2_x = positive(self.m2_x)?;
+        let residual = self.m2_y - self.c * self.c / m2_x;
+        let residual = if residual < T::zero() {
+            T::zero()
+        } else {
+            residual
+        };
+        (residual / from_usize(self.size)).sqrt()
+    }
+}
+
+// Removal can leave tiny negative remainders due to rounding.
+fn positive<T: StatsType>(value: T) -> Option<T> {
+    (value > T::zero()).then_some(value)
+}
+
+impl<U, V, T> Extend<(U, V)> for PairStats<T>
+where
+    U: Into<T>,
+    V: Into<T>,
+    T: StatsType,
+{
+    fn extend<I: IntoIterator<Item = (U, V)>>(&mut self, iter: I) {
+        for (x, y) in iter {
+            self.add(x.into(), y.into())
+        }
+    }
+}
+
+impl<U, V, T> std::iter::FromIterator<(U, V)> for PairStats<T>
+where
+    U: Into<T>,
+    V: Into<T>,
+    T: StatsType,
+{
+    fn from_iter<Iter: IntoIterator<Item = (U, V)>>(iter: Iter) -> Self {
+        let mut stats = PairStats::new();
+        stats.extend(iter);
+        stats
+    }
+}
+
+/// Bivariate statistics over the most recent `period` pairs.
+#[derive(Debug, Clone)]
+pub struct RollingPairStats<T> {
+    stats: PairStats<T>,
+    pairs: VecDeque<(T, T)>,
+    period: usize,
+}
+
+impl<T> RollingPairStats<T>
+where
+    T: StatsType,
+{
+    #[must_use]
+    pub fn new(period: NonZeroUsize) -> Self {
+        Self {
+            stats: PairStats::new(),
+            pairs: VecDeque::with_capacity(period.get()),
+            period: period.get(),
+        }
+    }
+
+    /// Adds a new pair, returning the oldest pair if it was evicted
+    /// from a fully populated window.
+    pub fn add(&mut self, x: T, y: T) -> Option<(T, T)> {
+        self.stats.add(x, y);
+        self.pairs.push_back((x, y));
+
+        if self.pairs.len() > self.period {
+            let (x, y) = self.pairs.pop_front()?;
+            self.stats.remove(x, y);
+            Some((x, y))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.pairs.len() == self.period
+    }
+
+    /// The statistics of the pairs currently in the window.
+    #[must_use]
+    pub fn stats(&self) -> &PairStats<T> {
+        &self.stats
+    }
+
+    /// Returns an iterator over the pairs in the window ordered from oldest to newest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &(T, T)> + ExactSizeIterator {
+        self.pairs.iter()
+    }
+}
+
+impl<U, V, T> Extend<(U, V)> for RollingPairStats<T>
+where
+    U: Into<T>,
+    V: Into<T>,
+    T: StatsType,
+{
+    fn extend<I: IntoIterator<Item = (U, V)>>(&mut self, iter: I) {
+        for (x, y) in iter {
+            self.add(x.into(), y.into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::stats::Sqrt;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_pair_stats() {
+        let stats = [(1, 2), (2, 1), (3, 4), (4, 3)]
+            .iter()
+            .map(|(x, y)| (Decimal::from(*x), Decimal::from(*y)))
+            .collect::<PairStats<Decimal>>();
+
+        assert_eq!(4, stats.size());
+        assert_eq!(Some(dec!(2.5)), stats.mean_x());
+        assert_eq!(Some(dec!(0.75)), stats.covariance());
+        assert_eq!(Some(dec!(1)), stats.sample_covariance());
+        assert_eq!(Some(dec!(0.6)), stats.correlation().map(|r| r.round_dp(20)));
+        assert_eq!(Some(dec!(0.6)), stats.beta());
+        assert_eq!(Some(dec!(1)), stats.alpha());
+        assert_eq!(
+            Some(dec!(0.8).sqrt().unwrap().round_dp(20)),
+            stats.residual_stddev().map(|s| s.round_dp(20))
+        );
+    }
+
+    #[test]
+    fn test_pair_stats_linear() {
+        let stats = (1..=5)
+            .map(|x| (x as f64, 2.0 * x as f64 + 1.0))
+            .collect::<PairStats<f64>>();
+
+        assert_eq!(Some(2.0), stats.beta());
+        assert_eq!(Some(1.0), stats.alpha());
+        assert!((stats.correlation().unwrap() - 1.0).abs() < 1e-12);
+        assert!(stats.residual_stddev().unwrap() < 1e-12);
+    }
+
+    #[test]
+    fn test_pair_stats_undefined() {
+        let mut stats = PairStats::<f64>::new();
+        assert_eq!(None, stats.covariance());
+        assert_eq!(None, stats.beta());
+
+        stats.extend([(1.0, 2.0), (1.0, 3.0)].iter().copied());
+        assert_eq!(None, stats.beta());
+        assert_eq!(None, stats.correlation());
+        assert_eq!(Some(0.0), stats.covariance());
+    }
+
+    #[test]
+    fn test_rolling_pair_stats() {
+        let pairs = [
+            (0.01, 0.02),
+            (-0.02, -0.03),
+            (0.005, 0.004),
+            (0.03, 0.05),
+            (-0.01, -0.012),
+            (0.0, 0.001),
+            (0.02, 0.018),
+        ];
+        let mut rolling = RollingPairStats::<f64>::new(NonZeroUsize::new(3).unwrap());
+
+        for (i, (x, y)) in pairs.iter().enumerate() {
+            rolling.add(*x, *y);
+
+            let start = (i + 1).saturating_sub(3);
+            let expected = pairs[start..=i].iter().copied().collect::<PairStats<f64>>();
+            let close = |a: Option<f64>, b: Option<f64>| match (a, b) {
+                (Some(a), Some(b)) => (a - b).abs() < 1e-12,
+                (a, b) => a.is_none() && b.is_none(),
+            };
+            assert!(close(rolling.stats().covariance(), expected.covariance()));
+            assert!(close(rolling.stats().correlation(), expected.correlation()));
+            assert!(close(rolling.stats().beta(), expected.beta()));
+            assert!(close(
+                rolling.stats().residual_stddev(),
+                expected.residual_stddev()
+            ));
+        }
+        assert!(rolling.is_full());
+    }
+}
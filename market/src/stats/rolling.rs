@@ -0,0 +1,325 @@
+use super::{moments::Moments, StatsType};
+use chrono::{DateTime, Duration, Utc};
+use std::{collections::VecDeque, num::NonZeroUsize};
+
+/// Samples in insertion order with their running moments and extrema.
+#[derive(Debug, Clone)]
+struct Window<T> {
+    samples: VecDeque<T>,
+    moments: Moments<T>,
+    // Monotonic queues of (sequence number, sample) whose fronts are the
+    // current minimum and maximum.
+    mins: VecDeque<(u64, T)>,
+    maxs: VecDeque<(u64, T)>,
+    // The sequence number of the oldest sample.
+    head: u64,
+}
+
+impl<T> Window<T>
+where
+    T: StatsType,
+{
+    fn new() -> Self {
+        Self {
+            samples: VecDeque::new(),
+            moments: Moments::new(),
+            mins: VecDeque::new(),
+            maxs: VecDeque::new(),
+            head: 0,
+        }
+    }
+
+    fn push(&mut self, sample: T) {
+        let seq = self.head + self.samples.len() as u64;
+
+        self.samples.push_back(sample);
+        self.moments.add(sample);
+
+        while matches!(self.mins.back(), Some((_, min)) if *min >= sample) {
+            self.mins.pop_back();
+        }
+        self.mins.push_back((seq, sample));
+
+        while matches!(self.maxs.back(), Some((_, max)) if *max <= sample) {
+            self.maxs.pop_back();
+        }
+        self.maxs.push_back((seq, sample));
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let sample = self.samples.pop_front()?;
+        self.moments.remove(sample);
+
+        if matches!(self.mins.front(), Some((seq, _)) if *seq == self.head) {
+            self.mins.pop_front();
+        }
+        if matches!(self.maxs.front(), Some((seq, _)) if *seq == self.head) {
+            self.maxs.pop_front();
+        }
+
+        self.head += 1;
+        Some(sample)
+    }
+}
+
+macro_rules! window_stats_impl {
+    () => {
+        #[must_use]
+        pub fn size(&self) -> usize {
+            self.window.samples.len()
+        }
+
+        #[must_use]
+        pub fn is_empty(&self) -> bool {
+            self.window.samples.is_empty()
+        }
+
+        #[must_use]
+        pub fn mean(&self) -> Option<T> {
+            self.window.moments.mean()
+        }
+
+        #[must_use]
+        pub fn variance(&self) -> Option<T> {
+            self.window.moments.variance()
+        }
+
+        #[must_use]
+        pub fn sample_variance(&self) -> Option<T> {
+            self.window.moments.sample_variance()
+        }
+
+        #[must_use]
+        pub fn stddev(&self) -> Option<T> {
+            self.window.moments.stddev()
+        }
+
+        #[must_use]
+        pub fn sample_stddev(&self) -> Option<T> {
+            self.window.moments.sample_stddev()
+        }
+
+        #[must_use]
+        pub fn skewness(&self) -> Option<T> {
+            self.window.moments.skewness()
+        }
+
+        #[must_use]
+        pub fn kurtosis(&self) -> Option<T> {
+            self.window.moments.kurtosis()
+        }
+
+        #[must_use]
+        pub fn min(&self) -> Option<T> {
+            self.window.mins.front().map(|(_, min)| *min)
+        }
+
+        #[must_use]
+        pub fn max(&self) -> Option<T> {
+            self.window.maxs.front().map(|(_, max)| *max)
+        }
+
+        /// Returns an iterator over the samples in the window ordered
+        /// from oldest to newest.
+        pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+            self.window.samples.iter()
+        }
+    };
+}
+
+/// Statistics over the most recent `period` samples.
+#[derive(Debug, Clone)]
+pub struct RollingStats<T> {
+    window: Window<T>,
+    period: usize,
+}
+
+impl<T> RollingStats<T>
+where
+    T: StatsType,
+{
+    #[must_use]
+    pub fn new(period: NonZeroUsize) -> Self {
+        Self {
+            window: Window::new(),
+            period: period.get(),
+        }
+    }
+
+    /// Adds a new sample, returning the oldest sample if it was evicted
+    /// from a fully populated window.
+    pub fn add(&mut self, sample: T) -> Option<T> {
+        self.window.push(sample);
+        if self.window.samples.len() > self.period {
+            self.window.pop()
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.window.samples.len() == self.period
+    }
+
+    window_stats_impl!();
+}
+
+impl<U, T> Extend<U> for RollingStats<T>
+where
+    U: Into<T>,
+    T: StatsType,
+{
+    fn extend<I: IntoIterator<Item = U>>(&mut self, iter: I) {
+        for sample in iter {
+            self.add(sample.into());
+        }
+    }
+}
+
+/// Statistics over the samples within a trailing time window.
+///
+/// A sample stamped `t` is part of the window `(now - duration, now]`, where
+/// `now` is the timestamp of the most recent sample or call to `expire`.
+/// Samples are expected to be added in chronological order.
+#[derive(Debug, Clone)]
+pub struct TimeWindowStats<T> {
+    window: Window<T>,
+    times: VecDeque<DateTime<Utc>>,
+    duration: Duration,
+}
+
+impl<T> TimeWindowStats<T>
+where
+    T: StatsType,
+{
+    #[must_use]
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            window: Window::new(),
+            times: VecDeque::new(),
+            duration,
+        }
+    }
+
+    pub fn add(&mut self, time: DateTime<Utc>, sample: T) {
+        self.window.push(sample);
+        self.times.push_back(time);
+        self.expire(time);
+    }
+
+    /// Evicts all samples that are outside of the window ending at `now`.
+    pub fn expire(&mut self, now: DateTime<Utc>) {
+        let start = now - self.duration;
+        while matches!(self.times.front(), Some(time) if *time <= start) {
+            self.times.pop_front();
+            self.window.pop();
+        }
+    }
+
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// The timestamp of the oldest sample in the window.
+    #[must_use]
+    pub fn oldest_time(&self) -> Option<DateTime<Utc>> {
+        self.times.front().copied()
+    }
+
+    /// The timestamp of the newest sample in the window.
+    #[must_use]
+    pub fn newest_time(&self) -> Option<DateTime<Utc>> {
+        self.times.back().copied()
+    }
+
+    window_stats_impl!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_rolling_stats() {
+        let mut stats = RollingStats::<Decimal>::new(NonZeroUsize::new(3).unwrap());
+        assert!(stats.is_empty());
+        assert_eq!(None, stats.min());
+
+        assert_eq!(None, stats.add(dec!(5)));
+        assert_eq!(None, stats.add(dec!(1)));
+        assert_eq!(None, stats.add(dec!(3)));
+        assert!(stats.is_full());
+        assert_eq!(Some(dec!(3)), stats.mean());
+        assert_eq!(Some(dec!(1)), stats.min());
+        assert_eq!(Some(dec!(5)), stats.max());
+
+        assert_eq!(Some(dec!(5)), stats.add(dec!(2)));
+        assert_eq!(3, stats.size());
+        assert_eq!(Some(dec!(2)), stats.mean());
+        assert_eq!(Some(dec!(1)), stats.min());
+        assert_eq!(Some(dec!(3)), stats.max());
+
+        stats.extend([dec!(4), dec!(4)].iter().copied());
+        assert_eq!(Some(dec!(2)), stats.min());
+        assert_eq!(Some(dec!(4)), stats.max());
+        assert!(stats.iter().eq([dec!(2), dec!(4), dec!(4)].iter()));
+    }
+
+    #[test]
+    fn test_rolling_stats_matches_cumulative() {
+        let samples = [3.5, 1.25, 9.0, 4.0, 4.5, 2.0, 8.75, 6.0, 5.5, 7.25];
+        let mut rolling = RollingStats::<f64>::new(NonZeroUsize::new(4).unwrap());
+
+        for (i, sample) in samples.iter().enumerate() {
+            rolling.add(*sample);
+
+            let start = (i + 1).saturating_sub(4);
+            let expected = samples[start..=i].iter().copied().collect::<Moments<f64>>();
+            let close = |a: Option<f64>, b: Option<f64>| match (a, b) {
+                (Some(a), Some(b)) => (a - b).abs() < 1e-9,
+                (a, b) => a.is_none() && b.is_none(),
+            };
+            assert!(close(rolling.mean(), expected.mean()));
+            assert!(close(rolling.variance(), expected.variance()));
+            assert!(close(rolling.sample_variance(), expected.sample_variance()));
+            assert!(close(rolling.skewness(), expected.skewness()));
+            assert!(close(rolling.kurtosis(), expected.kurtosis()));
+        }
+    }
+
+    #[test]
+    fn test_time_window_stats() {
+        let time = |s| Utc.timestamp_opt(s, 0).unwrap();
+        let mut stats = TimeWindowStats::<Decimal>::new(Duration::seconds(10));
+
+        stats.add(time(0), dec!(1));
+        stats.add(time(5), dec!(2));
+        stats.add(time(9), dec!(3));
+        assert_eq!(3, stats.size());
+        assert_eq!(Some(dec!(2)), stats.mean());
+
+        stats.add(time(10), dec!(7));
+        assert_eq!(3, stats.size());
+        assert_eq!(Some(dec!(4)), stats.mean());
+        assert_eq!(Some(time(5)), stats.oldest_time());
+        assert_eq!(Some(dec!(2)), stats.min());
+
+        stats.expire(time(19));
+        assert_eq!(1, stats.size());
+        assert_eq!(Some(dec!(7)), stats.max());
+
+        stats.expire(time(30));
+        assert!(stats.is_empty());
+        assert_eq!(None, stats.mean());
+    }
+}